@@ -0,0 +1,24 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use hinge_analyzer::bench::Fixture;
+
+// Matches in the generated file, more than anyone has but few enough that a run of every phase takes seconds
+const PROFILES: usize = 5_000;
+
+// The simulations at the default --iterations
+const ITERATIONS: u32 = 10_000;
+
+fn analysis(criterion: &mut Criterion) {
+    let fixture = Fixture::new(PROFILES).expect("Could not generate the matches to benchmark");
+    criterion.bench_function("ingest", |bencher| bencher.iter(|| fixture.ingest()));
+    criterion.bench_function("race aggregation", |bencher| bencher.iter(|| fixture.race_aggregation()));
+    criterion.bench_function("report", |bencher| bencher.iter(|| fixture.report()));
+
+    let mut simulation = criterion.benchmark_group("simulation");
+    simulation.sample_size(10);
+    simulation.bench_function("bootstrap test", |bencher| bencher.iter(|| fixture.bootstrap_test(ITERATIONS)));
+    simulation.bench_function("monte carlo", |bencher| bencher.iter(|| fixture.simulate_null(ITERATIONS)));
+    simulation.finish();
+}
+
+criterion_group!(benches, analysis);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+use std::{env, error::Error, fs, path::Path};
+
+// Generates the C header of the extern "C" functions of src/ffi.rs into OUT_DIR. The copy in include/ is what C
+// programs use, builds only compare it and warn when it's stale since they must not modify the source directory.
+// HINGE_ANALYZER_UPDATE_HEADER=1 cargo build rewrites it.
+fn main() -> Result<(), Box<dyn Error>> {
+    println!("cargo::rerun-if-changed=src/ffi.rs");
+    println!("cargo::rerun-if-changed=cbindgen.toml");
+    println!("cargo::rerun-if-changed=include/hinge_analyzer.h");
+    println!("cargo::rerun-if-env-changed=HINGE_ANALYZER_UPDATE_HEADER");
+
+    let crate_dir = env::var("CARGO_MANIFEST_DIR").map_err(|err| format!("CARGO_MANIFEST_DIR is not set: {}", err))?;
+    let out_dir = env::var("OUT_DIR").map_err(|err| format!("OUT_DIR is not set: {}", err))?;
+    let crate_dir = Path::new(&crate_dir);
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).map_err(|err| format!("Could not read cbindgen.toml: {}", err))?;
+    let bindings = match cbindgen::Builder::new().with_config(config).with_src(crate_dir.join("src/ffi.rs")).generate() {
+        Ok(bindings) => bindings,
+        // a syntax error is reported better by the compiler than by cbindgen
+        Err(err) => {
+            println!("cargo::warning=Could not generate the C header: {}", err);
+            return Ok(());
+        }
+    };
+
+    let mut header = Vec::new();
+    bindings.write(&mut header);
+    let generated = Path::new(&out_dir).join("hinge_analyzer.h");
+    fs::write(&generated, &header).map_err(|err| format!("Could not write {}: {}", generated.display(), err))?;
+
+    let committed = crate_dir.join("include/hinge_analyzer.h");
+    if env::var_os("HINGE_ANALYZER_UPDATE_HEADER").is_some() {
+        fs::write(&committed, &header).map_err(|err| format!("Could not write {}: {}", committed.display(), err))?;
+    } else if fs::read(&committed).ok().as_ref() != Some(&header) {
+        println!("cargo::warning=include/hinge_analyzer.h is out of date with src/ffi.rs, rebuild with HINGE_ANALYZER_UPDATE_HEADER=1 to update it");
+    }
+    Ok(())
+}
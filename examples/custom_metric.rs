@@ -0,0 +1,27 @@
+// The command line program with a metric computer of its own, run with
+// cargo run --example custom_metric -- --matches matches.csv
+use std::error::Error;
+
+use hinge_analyzer::metric::{Matches, MetricComputer, Metrics, Registry};
+
+// Share of matches farther than 20 miles away and how often they turned into a date
+struct LongDistance;
+
+impl MetricComputer for LongDistance {
+    fn name(&self) -> &str {
+        "long_distance"
+    }
+
+    fn compute(&self, matches: &Matches) -> Result<Metrics, Box<dyn Error>> {
+        let far = matches.profiles.iter().filter(|profile| profile.distance_miles.is_some_and(|miles| miles > 20.0)).collect::<Vec<_>>();
+        let dates = far.iter().filter(|profile| profile.last_reply == "Met").count();
+        Ok(Metrics::from([
+            (String::from("share"), far.len() as f64 / matches.profiles.len().max(1) as f64),
+            (String::from("date_rate"), dates as f64 / far.len().max(1) as f64)
+        ]))
+    }
+}
+
+fn main() {
+    hinge_analyzer::run_with(Registry::new().register(LongDistance));
+}
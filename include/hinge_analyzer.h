@@ -0,0 +1,35 @@
+#ifndef HINGE_ANALYZER_H
+#define HINGE_ANALYZER_H
+
+/* Generated by cbindgen from src/ffi.rs, edit that instead and rebuild with HINGE_ANALYZER_UPDATE_HEADER=1 */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Analyzes a matches.csv held in memory and returns the report as JSON, the same as the daemon's snapshots plus
+// `rejected_rows` with the line, reason and text of every row that could not be read and `warnings` with what the
+// command line would have printed about the baselines, or an object with an error when the analysis fails. Options
+// are TOML in the format of hinge-analyzer.toml, e.g. `bayesian = true`, or NULL for the defaults. Relative baseline
+// paths are resolved against the working directory of the process, so pass absolute ones or `offline = true` with
+// the bundled baselines built in.
+//
+// Nothing is printed and the HINGE_ANALYZER_* environment variables of the process are not read. Census baselines
+// (`counties` or `zip`) are refused rather than downloaded. With `offline = true` the bundled baselines are
+// decompressed into a temporary directory that is removed before the call returns.
+//
+// # Safety
+//
+// `csv` must point to `length` readable bytes and `options` must be NULL or a NUL-terminated UTF-8 string. The
+// returned string belongs to the caller and must be released with hinge_analyzer_free.
+char *hinge_analyzer_analyze(const uint8_t *csv,
+                             size_t length,
+                             const char *options);
+
+// Releases a string returned by hinge_analyzer_analyze, NULL is ignored.
+//
+// # Safety
+//
+// `json` must be NULL or a string returned by hinge_analyzer_analyze that has not been released yet.
+void hinge_analyzer_free(char *json);
+
+#endif  /* HINGE_ANALYZER_H */
@@ -0,0 +1,87 @@
+;; Example plugin counting the matches you met and the messages you sent, by looking for "last_reply":"Met" and
+;; "from_me":true in the input rather than parsing it. Run it with --plugin plugins/dates.wat, the interface is
+;; described in src/plugin.rs. Plugins are usually compiled to .wasm from another language, e.g. Rust with
+;; --target wasm32-unknown-unknown and serde_json.
+(module
+  (memory (export "memory") 1)
+
+  ;; patterns at 0 and 32, pieces of the output at 64 and 80, the output at 128 and the input at 1024
+  (data (i32.const 0) "\"last_reply\":\"Met\"")
+  (data (i32.const 32) "\"from_me\":true")
+  (data (i32.const 64) "{\"dates\":")
+  (data (i32.const 80) ",\"messages_sent\":")
+
+  (func (export "hinge_abi_version") (result i32)
+    (i32.const 1))
+
+  ;; the input always goes at 1024, memory grows to fit it
+  (func (export "hinge_alloc") (param $length i32) (result i32)
+    (local $pages i32)
+    (local.set $pages
+      (i32.sub
+        (i32.shr_u (i32.add (local.get $length) (i32.const 66559)) (i32.const 16))
+        (memory.size)))
+    (if (i32.gt_s (local.get $pages) (i32.const 0))
+      (then (drop (memory.grow (local.get $pages)))))
+    (i32.const 1024))
+
+  ;; occurrences of the pattern of the given size in the input
+  (func $count (param $input i32) (param $length i32) (param $pattern i32) (param $size i32) (result i32)
+    (local $i i32)
+    (local $j i32)
+    (local $count i32)
+    (block $done
+      (loop $scan
+        (br_if $done (i32.gt_u (i32.add (local.get $i) (local.get $size)) (local.get $length)))
+        (local.set $j (i32.const 0))
+        (block $mismatch
+          (loop $compare
+            (br_if $mismatch
+              (i32.ne
+                (i32.load8_u (i32.add (local.get $input) (i32.add (local.get $i) (local.get $j))))
+                (i32.load8_u (i32.add (local.get $pattern) (local.get $j)))))
+            (local.set $j (i32.add (local.get $j) (i32.const 1)))
+            (br_if $compare (i32.lt_u (local.get $j) (local.get $size))))
+          (local.set $count (i32.add (local.get $count) (i32.const 1))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $scan)))
+    (local.get $count))
+
+  ;; copies size bytes to the output at offset, returning the offset after them
+  (func $write (param $at i32) (param $source i32) (param $size i32) (result i32)
+    (memory.copy (local.get $at) (local.get $source) (local.get $size))
+    (i32.add (local.get $at) (local.get $size)))
+
+  ;; writes the number in decimal at offset, returning the offset after it
+  (func $number (param $at i32) (param $number i32) (result i32)
+    (local $digits i32)
+    (local $rest i32)
+    (local.set $rest (local.get $number))
+    (local.set $digits (i32.const 1))
+    (block $counted
+      (loop $digit
+        (br_if $counted (i32.lt_u (local.get $rest) (i32.const 10)))
+        (local.set $rest (i32.div_u (local.get $rest) (i32.const 10)))
+        (local.set $digits (i32.add (local.get $digits) (i32.const 1)))
+        (br $digit)))
+    (local.set $rest (local.get $digits))
+    (loop $digit
+      (local.set $rest (i32.sub (local.get $rest) (i32.const 1)))
+      (i32.store8
+        (i32.add (local.get $at) (local.get $rest))
+        (i32.add (i32.const 48) (i32.rem_u (local.get $number) (i32.const 10))))
+      (local.set $number (i32.div_u (local.get $number) (i32.const 10)))
+      (br_if $digit (i32.gt_u (local.get $rest) (i32.const 0))))
+    (i32.add (local.get $at) (local.get $digits)))
+
+  ;; {"dates":N,"messages_sent":N} at 128
+  (func (export "hinge_metrics") (param $input i32) (param $length i32) (result i64)
+    (local $at i32)
+    (local.set $at (call $write (i32.const 128) (i32.const 64) (i32.const 9)))
+    (local.set $at (call $number (local.get $at) (call $count (local.get $input) (local.get $length) (i32.const 0) (i32.const 18))))
+    (local.set $at (call $write (local.get $at) (i32.const 80) (i32.const 17)))
+    (local.set $at (call $number (local.get $at) (call $count (local.get $input) (local.get $length) (i32.const 32) (i32.const 14))))
+    (i32.store8 (local.get $at) (i32.const 125))
+    (i64.or
+      (i64.shl (i64.const 128) (i64.const 32))
+      (i64.extend_i32_u (i32.sub (i32.add (local.get $at) (i32.const 1)) (i32.const 128))))))
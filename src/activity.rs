@@ -0,0 +1,89 @@
+use chrono::{Datelike, NaiveDateTime, Timelike};
+
+use crate::{export::{Export, Sender}, format};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+// Hours with fewer of your messages than this are left out of the reply rate ranking
+const MIN_HOUR_MESSAGES: u32 = 5;
+const TOP_HOURS: usize = 5;
+
+// Counts of timestamps by local weekday (Monday first) and hour
+fn activity_matrix(export: &Export, timestamps: impl Iterator<Item = NaiveDateTime>) -> [[u32; 24]; 7] {
+    let mut matrix = [[0; 24]; 7];
+    for timestamp in timestamps.map(|timestamp| export.local(timestamp)) {
+        matrix[timestamp.weekday().num_days_from_monday() as usize][timestamp.hour() as usize] += 1;
+    }
+    matrix
+}
+
+fn print_heatmap(title: &str, matrix: &[[u32; 24]; 7]) {
+    let max = matrix.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+    println!("\n\t         {} (Darkest = {} Messages)", title, max);
+    let hours = (0..24).step_by(3).map(|hour| format!("{:<6}", format!("{:02}", hour))).collect::<String>();
+    println!("\t{:<5}{}  Total", "", hours);
+    for (weekday, row) in WEEKDAYS.iter().zip(matrix) {
+        let cells = row
+            .iter()
+            .map(|&count| {
+                // any activity gets at least the lightest shade
+                let shade = if count == 0 { 0 } else { ((count as f64 / max as f64) * (SHADES.len() - 1) as f64).ceil() as usize };
+                SHADES[shade].to_string().repeat(2)
+            })
+            .collect::<String>();
+        println!("\t{:<5}{}  {}", weekday, cells, format::count(row.iter().sum::<u32>()));
+    }
+}
+
+// Weekday by hour heatmaps of sent and received messages in local time, followed by the hours your messages were most
+// likely to get a reply. Without received messages in the export a message counts as replied when the chat continued
+// after it.
+pub fn print_activity(export: &Export) {
+    let has_received_messages = export.has_received_messages();
+    let messages = export.interactions.iter().flat_map(|interaction| &interaction.chats);
+
+    let sent = activity_matrix(export, messages.clone().filter(|message| message.sender == Sender::Me).map(|message| message.timestamp));
+    print_heatmap("Messages Sent by Day and Hour", &sent);
+    if has_received_messages {
+        let received = activity_matrix(export, messages.filter(|message| message.sender == Sender::Them).map(|message| message.timestamp));
+        print_heatmap("Messages Received by Day and Hour", &received);
+    }
+
+    // [messages, replied] by local hour of your message
+    let mut hours = [[0_u32; 2]; 24];
+    for interaction in &export.interactions {
+        let chat = interaction.messages();
+        for (i, message) in chat.iter().enumerate().filter(|(_, message)| message.sender == Sender::Me) {
+            let replied = if has_received_messages {
+                chat[i + 1..].iter().any(|later| later.sender == Sender::Them)
+            } else {
+                i + 1 < chat.len()
+            };
+
+            let hour = export.local(message.timestamp).hour() as usize;
+            hours[hour][0] += 1;
+            hours[hour][1] += replied as u32;
+        }
+    }
+
+    let mut ranked = hours
+        .iter()
+        .enumerate()
+        .filter(|(_, [messages, _])| *messages >= MIN_HOUR_MESSAGES)
+        .map(|(hour, [messages, replied])| (hour, *messages, *replied as f64 / *messages as f64))
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| b.1.cmp(&a.1)));
+
+    println!(
+        "\n\t         Best Hours to Message ({}, At Least {} Messages, UTC{:+})",
+        if has_received_messages { "Reply Rate" } else { "Conversation Continued Rate" },
+        MIN_HOUR_MESSAGES,
+        export.utc_offset
+    );
+    println!("\t{:<7}   Messages   Rate", "Hour");
+    for (hour, messages, rate) in ranked.into_iter().take(TOP_HOURS) {
+        println!("\t{:02}:00     {:<8}   {}", hour, format::count(messages), format::percent(rate));
+    }
+}
@@ -0,0 +1,88 @@
+use std::{error::Error, path::Path};
+
+use crate::{baseline, format, preference, HingeProfile};
+
+// An inclusive range of ages from the census age pyramid, open ended when there is no max
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgeBracket {
+    pub min: u8,
+    pub max: Option<u8>
+}
+
+impl AgeBracket {
+    // Parses column names of the form age_18_to_19 or age_50_and_over
+    fn from_column(column: &str) -> Option<Self> {
+        let bounds = column.strip_prefix("age_")?;
+        if let Some(min) = bounds.strip_suffix("_and_over") {
+            return Some(AgeBracket { min: min.parse().ok()?, max: None });
+        }
+
+        let (min, max) = bounds.split_once("_to_")?;
+        Some(AgeBracket { min: min.parse().ok()?, max: Some(max.parse().ok()?) })
+    }
+
+    pub fn contains(&self, age: u8) -> bool {
+        age >= self.min && self.max.is_none_or(|max| age <= max)
+    }
+}
+
+impl std::fmt::Display for AgeBracket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.max {
+            Some(max) => write!(f, "{}-{}", self.min, max)?,
+            None => write!(f, "{}+", self.min)?
+        }
+        Ok(())
+    }
+}
+
+// Population share of every age bracket combined over the counties in the file, brackets are read from the header so
+// the pyramid can be regrouped without code changes
+pub fn load_age_weights(path: impl AsRef<Path>, units: baseline::Units) -> Result<Vec<(AgeBracket, f64)>, Box<dyn Error>> {
+    let populations = baseline::read_columns(path.as_ref(), units)?
+        .into_iter()
+        .map(|(column, population)| AgeBracket::from_column(&column)
+            .map(|bracket| (bracket, population))
+            .ok_or_else(|| format!("Invalid age bracket column {}", column)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let total_population = populations.iter().map(|(_, population)| population).sum::<f64>();
+    Ok(populations
+        .into_iter()
+        .map(|(bracket, population)| (bracket, population / total_population))
+        .collect())
+}
+
+// Matches in every age bracket, ages outside the pyramid are left out
+pub fn age_categories<'a>(profiles: impl Iterator<Item = &'a HingeProfile>, age_weights: &[(AgeBracket, f64)]) -> Vec<preference::Category> {
+    let mut categories = age_weights
+        .iter()
+        .map(|(bracket, population)| preference::Category { label: bracket.to_string(), count: 0, population: *population })
+        .collect::<Vec<_>>();
+
+    for age in profiles.filter_map(|profile| profile.age) {
+        if let Some(i) = age_weights.iter().position(|(bracket, _)| bracket.contains(age)) {
+            categories[i].count += 1;
+        }
+    }
+
+    categories
+}
+
+pub fn print_age_histogram(categories: &[preference::Category]) {
+    const BAR_WIDTH: f64 = 40.0;
+
+    let total_count = preference::counts(categories).iter().sum::<u32>().max(1) as f64;
+    let max_share = categories
+        .iter()
+        .map(|category| (category.count as f64 / total_count).max(category.population))
+        .fold(0.0, f64::max);
+
+    println!("\n\t         Age Histogram (# = Matches, . = Population)");
+    println!("\t{:>7}   Matches   Share      Population", "Age");
+    for category in categories {
+        let share = category.count as f64 / total_count;
+        println!("\t{:>7}   {:<7}   {}   {}   {}", category.label, category.count, format::percent(share), format::percent(category.population), "#".repeat((share / max_share * BAR_WIDTH).round() as usize));
+        println!("\t{:>7}   {:<7}   {:>8}   {:>8}   {}", "", "", "", "", ".".repeat((category.population / max_share * BAR_WIDTH).round() as usize));
+    }
+}
@@ -0,0 +1,259 @@
+use std::{fs, path::Path};
+
+use crate::report::Report;
+
+// Exit code of a run that tripped an alert, errors exit with 1
+pub const EXIT_CODE: i32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    Matches,
+    Conversations,
+    Dates,
+    ConversationRate,
+    DateRate
+}
+
+impl Metric {
+    const ALL: [(&'static str, Metric); 5] = [
+        ("matches", Metric::Matches),
+        ("conversations", Metric::Conversations),
+        ("dates", Metric::Dates),
+        ("conversation_rate", Metric::ConversationRate),
+        ("date_rate", Metric::DateRate)
+    ];
+
+    fn name(self) -> &'static str {
+        Metric::ALL.iter().find(|(_, metric)| *metric == self).map_or("", |(name, _)| name)
+    }
+
+    fn is_rate(self) -> bool {
+        matches!(self, Metric::ConversationRate | Metric::DateRate)
+    }
+
+    fn of(self, report: &Report) -> f64 {
+        match self {
+            Metric::Matches => report.total_matches as f64,
+            Metric::Conversations => report.conversations as f64,
+            Metric::Dates => report.dates as f64,
+            Metric::ConversationRate => report.conversation_rate,
+            Metric::DateRate => report.date_rate
+        }
+    }
+
+    // Rates as percentages, counts as they are
+    fn format(self, value: f64) -> String {
+        if self.is_rate() {
+            format!("{:.1}%", value * 100.0)
+        } else {
+            format!("{}", value)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Condition {
+    Below,
+    Above,
+    Drops,
+    Rises
+}
+
+// A threshold on a headline number written like "date_rate < 10%", "matches > 500" or, against the previous snapshot,
+// "date_rate drops 5 points" and "conversations rises 20". Rates take percentages or points, or a fraction like 0.1.
+#[derive(Debug, Clone, Copy)]
+pub struct Alert {
+    metric: Metric,
+    condition: Condition,
+    threshold: f64
+}
+
+impl std::str::FromStr for Alert {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let expected = || format!("Expected an alert like \"date_rate < 10%\" or \"date_rate drops 5 points\", found {}", value);
+        let mut words = value.split_whitespace().peekable();
+        let metric = words.next().ok_or_else(expected)?;
+        let metric = Metric::ALL.iter().find(|(name, _)| name.eq_ignore_ascii_case(metric)).map(|(_, metric)| *metric).ok_or_else(|| {
+            format!("Unknown metric {} in alert, expected one of {}", metric, Metric::ALL.map(|(name, _)| name).join(", "))
+        })?;
+        let condition = match words.next().map(str::to_lowercase).as_deref() {
+            Some("<") => Condition::Below,
+            Some(">") => Condition::Above,
+            Some("drops" | "falls") => Condition::Drops,
+            Some("rises") => Condition::Rises,
+            _ => return Err(expected())
+        };
+        // "drops > 5 points" and "drops by 5 points" read the same as "drops 5 points"
+        if matches!(condition, Condition::Drops | Condition::Rises) && words.peek().is_some_and(|word| *word == ">" || *word == "by") {
+            words.next();
+        }
+
+        let amount = words.collect::<Vec<_>>().join(" ");
+        let (number, unit) = amount.split_at(amount.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(amount.len()));
+        let number = number.parse::<f64>().map_err(|_| expected())?;
+        let threshold = match unit.trim().to_lowercase().as_str() {
+            "" => number,
+            "%" | "points" | "point" | "pts" if metric.is_rate() => number / 100.0,
+            _ => return Err(expected())
+        };
+        Ok(Alert { metric, condition, threshold })
+    }
+}
+
+impl std::fmt::Display for Alert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let threshold = if self.metric.is_rate() { format!("{} points", self.threshold * 100.0) } else { self.threshold.to_string() };
+        match self.condition {
+            Condition::Below => write!(f, "{} < {}", self.metric.name(), self.metric.format(self.threshold))?,
+            Condition::Above => write!(f, "{} > {}", self.metric.name(), self.metric.format(self.threshold))?,
+            Condition::Drops => write!(f, "{} drops {}", self.metric.name(), threshold)?,
+            Condition::Rises => write!(f, "{} rises {}", self.metric.name(), threshold)?
+        }
+        Ok(())
+    }
+}
+
+// Why each tripped alert tripped. Alerts on the change since the previous snapshot stay quiet without one.
+pub fn check(alerts: &[Alert], report: &Report, previous: Option<&Report>) -> Vec<String> {
+    alerts
+        .iter()
+        .filter_map(|alert| {
+            let (metric, current) = (alert.metric, alert.metric.of(report));
+            let change = previous.map(|previous| current - metric.of(previous));
+            let tripped = match alert.condition {
+                Condition::Below => current < alert.threshold,
+                Condition::Above => current > alert.threshold,
+                Condition::Drops => change.is_some_and(|change| -change > alert.threshold),
+                Condition::Rises => change.is_some_and(|change| change > alert.threshold)
+            };
+            let since = previous.map_or(String::new(), |previous| format!(" from {} at {}", metric.format(metric.of(previous)), previous.generated));
+            tripped.then(|| format!("{} tripped, {} is {}{}", alert, metric.name(), metric.format(current), since))
+        })
+        .collect()
+}
+
+// The newest snapshot the daemon saved in the directory. Snapshots are named by when they were taken so the last name
+// is the newest, ones in an older report format that can't be read are skipped.
+pub fn latest_snapshot(directory: &Path) -> Option<Report> {
+    let mut paths = fs::read_dir(directory)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths.iter().rev().find_map(|path| serde_json::from_str(&fs::read_to_string(path).ok()?).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::report::Provenance;
+
+    fn alert(value: &str) -> Alert {
+        value.parse().unwrap()
+    }
+
+    fn assert_alert(alert: Alert, metric: Metric, condition: Condition, threshold: f64) {
+        assert_eq!((alert.metric, alert.condition), (metric, condition), "{}", alert);
+        assert!((alert.threshold - threshold).abs() < 1e-12, "{} has a threshold of {}", alert, alert.threshold);
+    }
+
+    fn report(matches: usize, dates: usize) -> Report {
+        Report {
+            report_version: 1,
+            generated: String::from("2026-01-01T00:00:00Z"),
+            matches_file: String::from("matches.csv"),
+            total_matches: matches,
+            conversations: matches / 2,
+            dates,
+            conversation_rate: 0.5,
+            date_rate: dates as f64 / matches as f64,
+            bayesian: false,
+            funnel: Vec::new(),
+            sections: BTreeMap::new(),
+            metrics: BTreeMap::new(),
+            custom_metrics: BTreeMap::new(),
+            provenance: Provenance {
+                crate_version: String::new(),
+                inputs: BTreeMap::new(),
+                geography: String::new(),
+                national_geography: String::new(),
+                counties: Vec::new(),
+                baseline_source: None,
+                bundled_baselines: None,
+                baseline_units: String::new(),
+                zero_population: String::new(),
+                filter: None,
+                strictness: String::new(),
+                dedup: String::new(),
+                sample_cutoff: 2,
+                index_formula: String::new(),
+                prior_strength: None,
+                height_baseline: String::new(),
+                height_bucket: 2,
+                stages: Vec::new()
+            }
+        }
+    }
+
+    #[test]
+    fn parses_thresholds() {
+        assert_alert(alert("date_rate < 10%"), Metric::DateRate, Condition::Below, 0.1);
+        assert_alert(alert("date_rate < 0.1"), Metric::DateRate, Condition::Below, 0.1);
+        assert_alert(alert("CONVERSATION_RATE > 40 points"), Metric::ConversationRate, Condition::Above, 0.4);
+        assert_alert(alert("matches > 500"), Metric::Matches, Condition::Above, 500.0);
+        assert_alert(alert("conversations rises 20"), Metric::Conversations, Condition::Rises, 20.0);
+    }
+
+    #[test]
+    fn changes_read_the_same_however_they_are_written() {
+        for value in ["date_rate drops 5 points", "date_rate drops by 5 points", "date_rate drops > 5 pts", "date_rate falls 5%"] {
+            assert_alert(alert(value), Metric::DateRate, Condition::Drops, 0.05);
+        }
+    }
+
+    #[test]
+    fn display_parses_back() {
+        for value in ["date_rate < 12.5%", "matches > 500", "date_rate drops 5 points", "dates rises 3"] {
+            let parsed = alert(value);
+            assert_alert(alert(&parsed.to_string()), parsed.metric, parsed.condition, parsed.threshold);
+        }
+    }
+
+    #[test]
+    fn malformed_alerts_are_errors() {
+        let error = |value: &str| value.parse::<Alert>().unwrap_err();
+        assert!(error("replies < 5").starts_with("Unknown metric replies in alert"));
+        assert!(error("").starts_with("Expected an alert like"));
+        assert!(error("date_rate").starts_with("Expected an alert like"));
+        assert!(error("date_rate = 5%").starts_with("Expected an alert like"));
+        assert!(error("date_rate < lots").starts_with("Expected an alert like"));
+        // percentages only make sense for rates
+        assert!(error("matches > 5%").starts_with("Expected an alert like"));
+    }
+
+    #[test]
+    fn thresholds_trip_strictly() {
+        let current = report(100, 10);
+        assert_eq!(check(&[alert("date_rate < 10%"), alert("matches > 100")], &current, None), Vec::<String>::new());
+        assert_eq!(check(&[alert("date_rate < 11%")], &current, None), vec![String::from("date_rate < 11.0% tripped, date_rate is 10.0%")]);
+        assert_eq!(check(&[alert("matches > 99")], &current, None).len(), 1);
+    }
+
+    #[test]
+    fn changes_need_a_previous_snapshot() {
+        let (previous, current) = (report(100, 20), report(100, 10));
+        let alerts = [alert("date_rate drops 5 points"), alert("dates rises 1")];
+        assert!(check(&alerts, &current, None).is_empty());
+        assert_eq!(check(&alerts, &current, Some(&previous)), vec![String::from(
+            "date_rate drops 5 points tripped, date_rate is 10.0% from 20.0% at 2026-01-01T00:00:00Z"
+        )]);
+        assert_eq!(check(&alerts, &previous, Some(&current)).len(), 1);
+        assert!(check(&[alert("date_rate drops 10 points")], &current, Some(&previous)).is_empty());
+    }
+}
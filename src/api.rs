@@ -0,0 +1,135 @@
+use std::error::Error;
+
+use crate::{filter::Filter, funnel, report, server, HingeProfile};
+
+// A profile as the API returns it, with values written the way --filter compares them so they can be fed back into
+// the filter parameter
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, async_graphql::SimpleObject)]
+pub struct ProfileRow {
+    pub name: String,
+    pub id: Option<String>,
+    pub matched: bool,
+    pub convo: bool,
+    pub last_reply: String,
+    pub race: Option<String>,
+    pub age: Option<u8>,
+    pub height: Option<u8>,
+    pub religion: Option<String>,
+    pub education: Option<String>,
+    pub job: Option<String>,
+    pub industry: Option<String>,
+    pub politics: Option<String>,
+    pub drinking: Option<String>,
+    pub smoking: Option<String>,
+    pub marijuana: Option<String>,
+    pub drugs: Option<String>,
+    pub wants_kids: Option<String>,
+    pub distance_miles: Option<f64>,
+    pub neighborhood: Option<String>,
+    pub zodiac: Option<String>,
+    pub stage: Option<String>,
+    pub date_rating: Option<u8>
+}
+
+impl From<&HingeProfile> for ProfileRow {
+    fn from(profile: &HingeProfile) -> Self {
+        ProfileRow {
+            name: profile.name.clone(),
+            id: profile.id.clone(),
+            matched: profile.matched,
+            convo: profile.convo,
+            last_reply: format!("{:?}", profile.who_last_replied),
+            race: profile.race.map(|race| race.to_string()),
+            age: profile.age,
+            height: profile.height,
+            religion: profile.religion.clone(),
+            education: profile.education.map(|education| education.to_string()),
+            job: profile.job.clone(),
+            industry: profile.industry().map(|industry| industry.to_string()),
+            politics: profile.politics.map(|politics| politics.to_string()),
+            drinking: profile.drinking.map(|habit| habit.to_string()),
+            smoking: profile.smoking.map(|habit| habit.to_string()),
+            marijuana: profile.marijuana.map(|habit| habit.to_string()),
+            drugs: profile.drugs.map(|habit| habit.to_string()),
+            wants_kids: profile.wants_kids.map(|plans| plans.to_string()),
+            distance_miles: profile.distance_miles,
+            neighborhood: profile.neighborhood.clone(),
+            zodiac: profile.zodiac.map(|sign| sign.to_string()),
+            stage: profile.stage.clone(),
+            date_rating: profile.date_rating
+        }
+    }
+}
+
+// A funnel stage with its share of the matches
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, async_graphql::SimpleObject)]
+pub struct FunnelRow {
+    pub name: String,
+    pub profiles: usize,
+    pub share: f64
+}
+
+pub fn funnel_rows(stages: Vec<(String, usize)>, matches: usize) -> Vec<FunnelRow> {
+    stages.into_iter().map(|(name, profiles)| FunnelRow { name, profiles, share: profiles as f64 / matches.max(1) as f64 }).collect()
+}
+
+// Longest filter parameter accepted, filters come from clients and a long enough one could be made costly to parse
+pub const MAX_FILTER_LENGTH: usize = 2048;
+
+// The filter parameter of a request, if any
+pub fn parse_filter(filter: Option<&str>) -> Result<Option<Filter>, String> {
+    match filter.filter(|filter| !filter.trim().is_empty()) {
+        Some(filter) if filter.len() > MAX_FILTER_LENGTH => Err(format!("Filters are limited to {} characters", MAX_FILTER_LENGTH)),
+        filter => filter.map(str::parse::<Filter>).transpose()
+    }
+}
+
+// How the endpoints get their numbers, both rerun against the matches file on every request so edits to it show up
+// without a restart. The filter is the one of the request, if any.
+pub struct Analysis {
+    pub profiles: Analyzer<Vec<HingeProfile>>,
+    pub report: Analyzer<report::Report>,
+    pub funnel: funnel::Funnel
+}
+
+type Analyzer<T> = Box<dyn Fn(Option<&Filter>) -> Result<T, Box<dyn Error>> + Send + Sync>;
+
+fn error(status: u16, message: impl std::fmt::Display) -> server::Response {
+    server::Response::json(status, &serde_json::json!({ "error": message.to_string() }))
+}
+
+// GET /api/profiles, /api/preferences and /api/funnel, each taking a filter parameter in the syntax of --filter and
+// /api/preferences an attribute parameter for a single attribute's section
+pub fn respond(request: &server::Request, analysis: &Analysis) -> server::Response {
+    if !["/api/profiles", "/api/preferences", "/api/funnel"].contains(&request.path.as_str()) {
+        return error(404, format!("Nothing at {}", request.path));
+    }
+    if request.method != "GET" {
+        return error(405, "Only GET is supported");
+    }
+
+    let filter = match parse_filter(request.parameter("filter")) {
+        Ok(filter) => filter,
+        Err(err) => return error(400, err)
+    };
+    let filter = filter.as_ref();
+
+    match request.path.as_str() {
+        "/api/profiles" => match (analysis.profiles)(filter) {
+            Ok(profiles) => server::Response::json(200, &profiles.iter().map(ProfileRow::from).collect::<Vec<_>>()),
+            Err(err) => error(500, err)
+        },
+        "/api/preferences" => match ((analysis.report)(filter), request.parameter("attribute")) {
+            (Ok(report), None) => server::Response::json(200, &report.sections),
+            (Ok(report), Some(attribute)) => match report.sections.get(attribute) {
+                Some(section) => server::Response::json(200, section),
+                None => error(404, format!("No preferences for {}, profiles may not have it or it has no baseline", attribute))
+            },
+            (Err(err), _) => error(500, err)
+        },
+        _ => match (analysis.profiles)(filter) {
+            Ok(profiles) => server::Response::json(200, &funnel_rows(funnel::stage_counts(profiles.iter(), &analysis.funnel), profiles.len())),
+            Err(err) => error(500, err)
+        }
+    }
+}
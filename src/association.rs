@@ -0,0 +1,143 @@
+use crate::{attribute, models::Outcome, stats, HingeProfile};
+
+// Numeric attributes, everything else in attribute::NAMES is categorical
+const NUMERIC: &[&str] = &["age", "height", "distance_miles"];
+
+enum Values {
+    Categorical(Vec<Option<String>>),
+    Numeric(Vec<Option<f64>>)
+}
+
+struct Variable {
+    label: String,
+    values: Values
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Association {
+    value: f64,
+    p_value: f64,
+    // signed correlations print with a sign, Cramér's V is never negative
+    signed: bool
+}
+
+fn paired<A: Clone, B: Clone>(a: &[Option<A>], b: &[Option<B>]) -> (Vec<A>, Vec<B>) {
+    a.iter().zip(b).filter_map(|(a, b)| Some((a.clone()?, b.clone()?))).unzip()
+}
+
+fn cramers_v(a: &[String], b: &[String]) -> Option<Association> {
+    let mut rows: Vec<&String> = Vec::new();
+    let mut columns: Vec<&String> = Vec::new();
+    for (a, b) in a.iter().zip(b) {
+        if !rows.contains(&a) {
+            rows.push(a);
+        }
+        if !columns.contains(&b) {
+            columns.push(b);
+        }
+    }
+
+    let table = rows
+        .iter()
+        .map(|row| columns
+            .iter()
+            .map(|column| a.iter().zip(b).filter(|(a, b)| a == row && b == column).count() as u32)
+            .collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let test = stats::independence_test(&table)?;
+
+    Some(Association { value: test.cramers_v, p_value: test.p_value, signed: false })
+}
+
+// Point-biserial against a categorical variable with exactly two values, flagged as the later value in sorted order
+// so true, Yes and the like are coded as 1
+fn numeric_categorical(numbers: &[f64], categories: &[String]) -> Option<Association> {
+    let mut levels = categories.to_vec();
+    levels.sort();
+    levels.dedup();
+    let [_, positive] = levels.as_slice() else {
+        return None;
+    };
+
+    let flags = categories.iter().map(|category| category == positive).collect::<Vec<_>>();
+    let correlation = stats::point_biserial(numbers, &flags)?;
+    Some(Association { value: correlation.r, p_value: correlation.p_value, signed: true })
+}
+
+fn associate(a: &Values, b: &Values) -> Option<Association> {
+    match (a, b) {
+        (Values::Categorical(a), Values::Categorical(b)) => {
+            let (a, b) = paired(a, b);
+            cramers_v(&a, &b)
+        },
+        (Values::Numeric(a), Values::Numeric(b)) => {
+            let (a, b) = paired(a, b);
+            let correlation = stats::pearson(&a, &b)?;
+            Some(Association { value: correlation.r, p_value: correlation.p_value, signed: true })
+        },
+        (Values::Numeric(numbers), Values::Categorical(categories)) | (Values::Categorical(categories), Values::Numeric(numbers)) => {
+            let (numbers, categories) = paired(numbers, categories);
+            numeric_categorical(&numbers, &categories)
+        }
+    }
+}
+
+fn variables(profiles: &[HingeProfile]) -> Vec<Variable> {
+    let attributes = attribute::NAMES
+        .iter()
+        .chain(NUMERIC)
+        .filter(|&&name| profiles.iter().any(|profile| attribute::value(profile, name).is_some()))
+        .map(|&name| {
+            let values = profiles.iter().map(|profile| attribute::value(profile, name));
+            Variable {
+                label: attribute::title(name),
+                values: if NUMERIC.contains(&name) {
+                    Values::Numeric(values.map(|value| value.and_then(|value| value.parse().ok())).collect())
+                } else {
+                    Values::Categorical(values.collect())
+                }
+            }
+        });
+    let outcomes = Outcome::entries().map(|outcome| Variable {
+        label: outcome.to_string(),
+        values: Values::Categorical(profiles.iter().map(|profile| Some(outcome.observed(profile).to_string())).collect())
+    });
+
+    attributes.chain(outcomes).collect()
+}
+
+// Pairwise association of every attribute present in the data and the outcome flags. Categorical pairs use Cramér's V,
+// numeric variables against binary ones use the point-biserial correlation and numeric pairs Pearson's r. Pairs of a
+// numeric and a many-valued categorical variable have no single coefficient and are left blank.
+pub fn print_association_matrix(profiles: &[HingeProfile], alpha: f64) {
+    const CELL_WIDTH: usize = 7;
+
+    let variables = variables(profiles);
+
+    println!("\n\t         Association Matrix ({} Profiles, * = p < {})", profiles.len(), alpha);
+    println!("\tCramér's V between categorical variables, signed r when either variable is numeric, - = no coefficient");
+    let header = (1..=variables.len()).map(|i| format!("{:>CELL_WIDTH$}", format!("[{}]", i))).collect::<Vec<_>>();
+    println!("\t{:<25}   {}", "", header.join(" "));
+    for (i, row) in variables.iter().enumerate() {
+        let cells = variables
+            .iter()
+            .enumerate()
+            .map(|(j, column)| {
+                let cell = if i == j {
+                    String::from("1")
+                } else {
+                    associate(&row.values, &column.values).map_or(String::from("-"), |association| {
+                        let marker = if association.p_value < alpha { "*" } else { "" };
+                        if association.signed {
+                            format!("{:+.2}{}", association.value, marker)
+                        } else {
+                            format!("{:.2}{}", association.value, marker)
+                        }
+                    })
+                };
+                format!("{:>CELL_WIDTH$}", cell)
+            })
+            .collect::<Vec<_>>();
+        println!("\t{:<25}   {}", format!("[{}] {}", i + 1, row.label), cells.join(" "));
+    }
+}
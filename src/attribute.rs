@@ -0,0 +1,308 @@
+use std::{error::Error, path::Path};
+
+use crate::{baseline, format, preference, stats, HingeProfile, WhoLastReplied};
+
+#[derive(Debug, serde::Deserialize)]
+struct BaselineCSVRecord {
+    geography: String,
+    category: String,
+    population: String
+}
+
+#[derive(Debug, Clone)]
+pub struct OutcomeRates {
+    pub label: String,
+    pub matches: u32,
+    pub conversations: u32,
+    pub dates: u32
+}
+
+impl OutcomeRates {
+    pub fn conversation(&self) -> format::Rate {
+        format::Rate::new("convo rate", self.conversations as usize, "convos", self.matches as usize, "matches")
+    }
+
+    pub fn date(&self) -> format::Rate {
+        format::Rate::new("date rate", self.dates as usize, "dates", self.matches as usize, "matches")
+    }
+
+    pub fn conversation_rate(&self) -> f64 {
+        self.conversation().value()
+    }
+
+    pub fn date_rate(&self) -> f64 {
+        self.date().value()
+    }
+}
+
+impl std::fmt::Display for OutcomeRates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:<25}   {:<7}   {:<7}   {}     {:<7}   {}", self.label, self.matches, self.conversations, format::percent(self.conversation_rate()), self.dates, format::percent(self.date_rate()))?;
+        Ok(())
+    }
+}
+
+// Matches, conversations and dates for every value of an attribute ignoring case, most common value first
+pub fn outcome_rates<'a, S: AsRef<str>>(profiles: impl Iterator<Item = &'a HingeProfile>, value: impl Fn(&HingeProfile) -> Option<S>) -> Vec<OutcomeRates> {
+    let mut rates: Vec<OutcomeRates> = Vec::new();
+    for profile in profiles {
+        if let Some(label) = value(profile) {
+            add_outcome(&mut rates, label.as_ref(), profile);
+        }
+    }
+
+    sort_outcome_rates(&mut rates);
+    rates
+}
+
+// Counts a profile toward its label, labels differing only in case are the same
+pub fn add_outcome(rates: &mut Vec<OutcomeRates>, label: &str, profile: &HingeProfile) {
+    let index = match rates.iter().position(|rates| rates.label.eq_ignore_ascii_case(label)) {
+        Some(index) => index,
+        None => {
+            rates.push(OutcomeRates { label: label.to_string(), matches: 0, conversations: 0, dates: 0 });
+            rates.len() - 1
+        }
+    };
+
+    rates[index].matches += 1;
+    rates[index].conversations += profile.convo as u32;
+    rates[index].dates += (profile.who_last_replied == WhoLastReplied::Met) as u32;
+}
+
+// Most matched first, ties by label
+pub fn sort_outcome_rates(rates: &mut [OutcomeRates]) {
+    rates.sort_by(|a, b| b.matches.cmp(&a.matches).then_with(|| a.label.cmp(&b.label)));
+}
+
+pub fn print_outcome_rates(attribute: &str, rates: &[OutcomeRates]) {
+    println!("\n\t         {} Outcomes ({} Profiles with {})", attribute, rates.iter().map(|rates| rates.matches).sum::<u32>(), attribute);
+    println!("\t{:^25}   Matches   Convos    Convo Rate    Dates     Date Rate", attribute);
+    for rates in rates {
+        println!("\t{}", rates);
+        format::explain(&[rates.conversation(), rates.date()]);
+    }
+}
+
+// Population of every category of a long format baseline (geography,category,population) within one geography,
+// normalized to shares. Populations can be counts or percentages per `units`.
+pub fn load_baseline(path: impl AsRef<Path>, geography: &str, units: baseline::Units) -> Result<Vec<(String, f64)>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut reader = csv::Reader::from_path(path)?;
+
+    let mut geographies = Vec::new();
+    let mut baseline = Vec::new();
+    for record in reader.deserialize() {
+        let record: BaselineCSVRecord = record?;
+        if record.geography.eq_ignore_ascii_case(geography) {
+            let population = record.population.parse::<baseline::Value>().map_err(|err| format!("{} of {} in {}: {}", record.category, record.geography, path.display(), err))?;
+            baseline.push((record.category, population));
+        } else if !geographies.contains(&record.geography) {
+            geographies.push(record.geography);
+        }
+    }
+
+    if baseline.is_empty() {
+        return Err(format!("No baseline for {} in {}, available geographies: {}", geography, path.display(), geographies.join(", ")).into());
+    }
+
+    let populations = baseline.iter().map(|(_, population)| *population).collect::<Vec<_>>();
+    let populations = baseline::combine(path, units, &[(geography.to_string(), populations)])?;
+    let total_population = populations.iter().sum::<f64>();
+    Ok(baseline.into_iter().zip(populations).map(|((category, _), population)| (category, population / total_population)).collect())
+}
+
+// Baseline categories with the matches whose value names them, values missing from the baseline and categories with no
+// population are left out
+pub fn baseline_categories(rates: &[OutcomeRates], baseline: &[(String, f64)]) -> Vec<preference::Category> {
+    baseline
+        .iter()
+        .filter(|(_, population)| *population > 0.0)
+        .map(|(category, population)| preference::Category {
+            label: category.clone(),
+            count: rates
+                .iter()
+                .filter(|rates| rates.label.eq_ignore_ascii_case(category))
+                .map(|rates| rates.matches)
+                .sum(),
+            population: *population
+        })
+        .collect()
+}
+
+// Categorical attributes that can be reported and cross-tabulated by name
+pub const NAMES: &[&str] = &[
+    "race", "religion", "education", "industry", "politics", "drinking", "smoking", "marijuana", "drugs", "wants_kids",
+    "neighborhood", "zodiac"
+];
+
+// Every attribute that can be looked up by name, including outcomes and numeric columns
+pub const FIELDS: &[&str] = &[
+    "name", "matched", "convo", "last_reply", "specified", "age", "height", "job", "race", "religion", "education", "industry",
+    "politics", "drinking", "smoking", "marijuana", "drugs", "wants_kids", "distance_miles", "neighborhood", "zodiac", "stage",
+    "date_rating", "id"
+];
+
+pub fn value(profile: &HingeProfile, name: &str) -> Option<String> {
+    match name {
+        "name" => Some(profile.name.clone()),
+        "id" => profile.id.clone(),
+        "matched" => Some(profile.matched.to_string()),
+        "convo" => Some(profile.convo.to_string()),
+        "last_reply" => Some(format!("{:?}", profile.who_last_replied)),
+        "specified" => Some(profile.ethnicity_specified.to_string()),
+        "age" => profile.age.map(|age| age.to_string()),
+        "height" => profile.height.map(|height| height.to_string()),
+        "job" => profile.job.clone(),
+        "race" => profile.race.map(|race| race.to_string()),
+        "religion" => profile.religion.clone(),
+        "education" => profile.education.map(|education| education.to_string()),
+        "industry" => profile.industry().map(|industry| industry.to_string()),
+        "politics" => profile.politics.map(|politics| politics.to_string()),
+        "drinking" => profile.drinking.map(|habit| habit.to_string()),
+        "smoking" => profile.smoking.map(|habit| habit.to_string()),
+        "marijuana" => profile.marijuana.map(|habit| habit.to_string()),
+        "drugs" => profile.drugs.map(|habit| habit.to_string()),
+        "wants_kids" => profile.wants_kids.map(|plans| plans.to_string()),
+        "distance_miles" => profile.distance_miles.map(|distance| distance.to_string()),
+        "neighborhood" => profile.neighborhood.clone(),
+        "zodiac" => profile.zodiac.map(|sign| sign.to_string()),
+        "stage" => profile.stage.clone(),
+        "date_rating" => profile.date_rating.map(|rating| rating.to_string()),
+        _ => None
+    }
+}
+
+// wants_kids => Wants Kids
+pub fn title(name: &str) -> String {
+    name
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Distinct values, most common first
+pub fn labels_by_count<'a>(values: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut counts: Vec<(String, u32)> = Vec::new();
+    for value in values {
+        match counts.iter_mut().find(|(label, _)| label == value) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((value.clone(), 1))
+        }
+    }
+
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts.into_iter().map(|(label, _)| label).collect()
+}
+
+// Match counts of every pair of values of two attributes as counts, row percentages and column percentages, followed by
+// a chi-square test of the attributes being independent. Profiles missing either value are left out.
+pub fn print_crosstab(profiles: &[HingeProfile], row_attribute: &str, column_attribute: &str) {
+    let pairs = profiles
+        .iter()
+        .filter_map(|profile| Some((value(profile, row_attribute)?, value(profile, column_attribute)?)))
+        .collect::<Vec<_>>();
+    let row_labels = labels_by_count(pairs.iter().map(|(row, _)| row));
+    let column_labels = labels_by_count(pairs.iter().map(|(_, column)| column));
+    let widths = column_labels.iter().map(|label| label.len().max(7)).collect::<Vec<_>>();
+    let label_width = row_labels.iter().map(String::len).max().unwrap_or(0).max(25);
+
+    let table = row_labels
+        .iter()
+        .map(|row_label| column_labels
+            .iter()
+            .map(|column_label| pairs.iter().filter(|(row, column)| row == row_label && column == column_label).count() as u32)
+            .collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let row_totals = table.iter().map(|row| row.iter().sum::<u32>()).collect::<Vec<_>>();
+    let column_totals = (0..column_labels.len()).map(|j| table.iter().map(|row| row[j]).sum::<u32>()).collect::<Vec<_>>();
+
+    let print_table = |heading: &str, cell: &dyn Fn(usize, usize) -> String, total: &dyn Fn(usize) -> String| {
+        println!("\n\t         {} by {} ({})", title(row_attribute), title(column_attribute), heading);
+        let header = column_labels
+            .iter()
+            .zip(&widths)
+            .map(|(label, width)| format!("{:>width$}", label, width = width))
+            .collect::<Vec<_>>();
+        println!("\t{:<label_width$}   {}   {:>7}", title(row_attribute), header.join("   "), "Total");
+        for (i, row_label) in row_labels.iter().enumerate() {
+            let cells = widths
+                .iter()
+                .enumerate()
+                .map(|(j, width)| format!("{:>width$}", cell(i, j), width = width))
+                .collect::<Vec<_>>();
+            println!("\t{:<label_width$}   {}   {:>7}", row_label, cells.join("   "), total(i));
+        }
+    };
+
+    print_table("Matches", &|i, j| table[i][j].to_string(), &|i| row_totals[i].to_string());
+    print_table(
+        "Row %",
+        &|i, j| format::inline_percent(table[i][j] as f64 / row_totals[i].max(1) as f64, 1),
+        &|_| String::from("100.0%")
+    );
+    print_table(
+        "Column %",
+        &|i, j| format::inline_percent(table[i][j] as f64 / column_totals[j].max(1) as f64, 1),
+        &|i| format::inline_percent(row_totals[i] as f64 / pairs.len().max(1) as f64, 1)
+    );
+
+    match stats::independence_test(&table) {
+        Some(test) => println!("\tIndependence: {}", test),
+        None => println!("\tIndependence: not enough variation to test")
+    }
+}
+
+// Validates an attribute name given on the command line
+pub fn parse_name(name: &str) -> Result<String, String> {
+    let name = name.to_ascii_lowercase();
+    if FIELDS.contains(&name.as_str()) {
+        Ok(name)
+    } else {
+        Err(format!("expected one of {}", FIELDS.join(", ")))
+    }
+}
+
+// How conversations with each value of an attribute fared, conversations survive unless they end on an unanswered
+// message from you and convert when you met. Each outcome is tested for independence from the attribute.
+pub fn print_conversation_outcomes(profiles: &[HingeProfile], name: &str) {
+    let conversations = profiles
+        .iter()
+        .filter(|profile| profile.convo)
+        .filter_map(|profile| Some((value(profile, name)?, profile)))
+        .collect::<Vec<_>>();
+    let labels = labels_by_count(conversations.iter().map(|(label, _)| label));
+
+    // rows of [conversations, survived, dates] per label
+    let counts = labels
+        .iter()
+        .map(|label| conversations
+            .iter()
+            .filter(|(value, _)| value == label)
+            .fold([0, 0, 0], |[total, survived, dates], (_, profile)| [
+                total + 1,
+                survived + (profile.who_last_replied != WhoLastReplied::You) as u32,
+                dates + (profile.who_last_replied == WhoLastReplied::Met) as u32
+            ]))
+        .collect::<Vec<_>>();
+
+    println!("\n\t         Conversation Outcomes by {} ({} Conversations)", title(name), conversations.len());
+    println!("\t{:^25}   Convos    Survived   Survival Rate   Dates     Date Conversion", title(name));
+    for (label, [total, survived, dates]) in labels.iter().zip(&counts) {
+        let total_share = (*total).max(1) as f64;
+        println!("\t{:<25}   {:<7}   {:<7}    {}        {:<7}   {}", label, total, survived, format::percent(*survived as f64 / total_share), dates, format::percent(*dates as f64 / total_share));
+    }
+
+    let survival_table = counts.iter().map(|[total, survived, _]| vec![*survived, total - survived]).collect::<Vec<_>>();
+    let date_table = counts.iter().map(|[total, _, dates]| vec![*dates, total - dates]).collect::<Vec<_>>();
+    for (outcome, table) in [("Survival", survival_table), ("Date Conversion", date_table)] {
+        match stats::independence_test(&table) {
+            Some(test) => println!("\t{} vs {}: {}", outcome, title(name), test),
+            None => println!("\t{} vs {}: not enough variation to test", outcome, title(name))
+        }
+    }
+}
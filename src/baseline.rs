@@ -0,0 +1,119 @@
+use std::{error::Error, path::Path};
+
+use crate::warning;
+
+// How far a geography's percentages can add up to from 100, or its fractions from 1, before they aren't taken as
+// percentages. Published tables round each cell so they rarely add up exactly.
+const PERCENT_TOLERANCE: f64 = 0.01;
+
+// Whether the values of a baseline file are population counts or percentages of each geography's population
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Units {
+    /// Percentages when values have a % sign or every geography's values add up to 100 or to 1, counts otherwise
+    #[default]
+    Auto,
+    /// Population counts
+    Counts,
+    /// Percentages of each geography's population, or fractions of 1
+    Percentages
+}
+
+// A value of one geography's row, marked when it was written with a % sign
+#[derive(Debug, Clone, Copy)]
+pub struct Value {
+    pub amount: f64,
+    pub percent_sign: bool
+}
+
+impl std::str::FromStr for Value {
+    type Err = String;
+
+    // Thousands separators and a trailing % are allowed, e.g. 1,234 or 12.5%
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let trimmed = value.trim();
+        let (number, percent_sign) = match trimmed.strip_suffix('%') {
+            Some(number) => (number, true),
+            None => (trimmed, false)
+        };
+        match number.trim().replace(',', "").parse::<f64>() {
+            Ok(amount) if amount >= 0.0 && amount.is_finite() => Ok(Value { amount, percent_sign }),
+            _ => Err(format!("Expected a population count or percentage, found {}", value))
+        }
+    }
+}
+
+// Whether a total is 100 percent, or 1 as a fraction
+fn is_whole(total: f64) -> bool {
+    (total - 100.0).abs() <= 100.0 * PERCENT_TOLERANCE || (total - 1.0).abs() <= PERCENT_TOLERANCE
+}
+
+// Each category's combined value over the geographies of a baseline, each geography given as its values in the same
+// category order. Counts are summed. Percentages must add up to 100 (or 1) in every geography and are averaged with the
+// geographies weighted equally, since a percentage doesn't say how many people it covers. Only the proportions between
+// the returned values matter.
+pub fn combine(path: &Path, units: Units, geographies: &[(String, Vec<Value>)]) -> Result<Vec<f64>, Box<dyn Error>> {
+    let total = |values: &[Value]| values.iter().map(|value| value.amount).sum::<f64>();
+    let percent_sign = geographies.iter().any(|(_, values)| values.iter().any(|value| value.percent_sign));
+    let percentages = match units {
+        Units::Counts if percent_sign => {
+            return Err(format!("{} has percentages but --baseline-units is counts", path.display()).into());
+        },
+        Units::Counts => false,
+        Units::Percentages => true,
+        Units::Auto => percent_sign || (!geographies.is_empty() && geographies.iter().all(|(_, values)| is_whole(total(values))))
+    };
+
+    let categories = geographies.first().map_or(0, |(_, values)| values.len());
+    let mut combined = vec![0.0; categories];
+    for (geography, values) in geographies {
+        if percentages && !is_whole(total(values)) {
+            return Err(format!(
+                "{} in {} has percentages adding up to {}, not 100, pass --baseline-units counts if they are counts",
+                geography,
+                path.display(),
+                total(values)
+            )
+            .into());
+        }
+
+        // fractions of 1 are scaled so they average evenly with percentages out of 100
+        let scale = if percentages && total(values) <= 1.0 + PERCENT_TOLERANCE { 100.0 } else { 1.0 };
+        for (sum, value) in combined.iter_mut().zip(values) {
+            *sum += value.amount * scale;
+        }
+    }
+
+    if percentages && geographies.len() > 1 {
+        warning::warn(format!("Warning: {} has percentages for {} geographies, weighting each equally", path.display(), geographies.len()));
+    }
+    Ok(combined)
+}
+
+// A baseline with a county column and a column of values per category, like demographics.csv, as each category column
+// with its combined value
+pub fn read_columns(path: &Path, units: Units) -> Result<Vec<(String, f64)>, Box<dyn Error>> {
+    let mut reader = csv::Reader::from_path(path).map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+    let headers = reader.headers()?.clone();
+    let county = headers.iter().position(|column| column == "county");
+    let columns = headers.iter().enumerate().filter(|(i, _)| Some(*i) != county).collect::<Vec<_>>();
+
+    let mut geographies = Vec::new();
+    for (row, record) in reader.records().enumerate() {
+        let record = record?;
+        let geography = county.and_then(|i| record.get(i)).map_or_else(|| format!("Row {}", row + 1), str::to_string);
+        let values = columns
+            .iter()
+            .map(|(i, column)| {
+                record
+                    .get(*i)
+                    .unwrap_or_default()
+                    .parse::<Value>()
+                    .map_err(|err| format!("{} of {} in {}: {}", column, geography, path.display(), err))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        geographies.push((geography, values));
+    }
+
+    let combined = combine(path, units, &geographies)?;
+    Ok(columns.into_iter().map(|(_, column)| column.to_string()).zip(combined).collect())
+}
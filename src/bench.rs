@@ -0,0 +1,80 @@
+use std::{error::Error, ffi::OsString, hint::black_box};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    build_report_of, generate, ingest, load_race_weights, preference, profile_from_record, racial_categories, resolve_baselines, schema, stats,
+    try_parse_from, vocabulary, write_generated, Args, HingeProfile, HingeProfileCSVRecord, RaceWeights
+};
+
+// The phases of the analysis benches/analysis.rs times, reached through here since the modules they live in are
+// private. Not part of the interface of the library.
+pub struct Fixture {
+    args: Args,
+    // holds the generated matches file, removed with the fixture
+    _directory: tempfile::TempDir,
+    profiles: Vec<HingeProfile>,
+    race_weights: RaceWeights,
+    hispanic_race_weights: RaceWeights,
+    counts: Vec<u32>,
+    shares: Vec<f64>
+}
+
+impl Fixture {
+    // `profiles` matches generated with the defaults of the generate command and a fixed seed, analyzed with the
+    // default options. The baselines are read from the working directory like the command line reads them.
+    pub fn new(profiles: usize) -> Result<Self, Box<dyn Error>> {
+        let directory = tempfile::tempdir()?;
+        let matches = directory.path().join("matches.csv");
+        let arguments = ["hinge-analyzer", "--summary", "--seed", "0", "--matches"].map(OsString::from).into_iter().chain([matches.clone().into()]);
+        let args = resolve_baselines(try_parse_from(arguments)?)?;
+
+        let distributions = generate::Distributions {
+            conversation_rate: 0.5,
+            date_rate: 0.15,
+            missing_rate: 0.1,
+            mean_age: 29.0,
+            mean_distance_miles: 8.0,
+            height_baseline: args.height_baseline,
+            race_baseline: args.baseline.clone(),
+            baseline_units: args.baseline_units
+        };
+        write_generated(&args, profiles, &distributions, Some(&matches), None)?;
+
+        let (race_weights, hispanic_race_weights) = load_race_weights(&args)?;
+        let mut fixture =
+            Fixture { args, _directory: directory, profiles: Vec::new(), race_weights, hispanic_race_weights, counts: Vec::new(), shares: Vec::new() };
+        fixture.profiles = fixture.read()?;
+        let categories = racial_categories(fixture.profiles.iter(), &fixture.race_weights, &fixture.hispanic_race_weights);
+        (fixture.counts, fixture.shares) = (preference::counts(&categories), preference::shares(&categories));
+        Ok(fixture)
+    }
+
+    fn read(&self) -> Result<Vec<HingeProfile>, Box<dyn Error>> {
+        let vocabulary = vocabulary::load(&self.args.last_reply_vocabulary)?;
+        ingest::ingest::<HingeProfileCSVRecord, _>(&self.args.matches, &schema(&self.args), |record| profile_from_record(record, self.args.strictness, &vocabulary))
+    }
+
+    // Reading and checking every row of the matches file
+    pub fn ingest(&self) {
+        black_box(self.read().expect("Could not read the generated matches"));
+    }
+
+    // Counting the matches of each race and pairing them with the population shares
+    pub fn race_aggregation(&self) {
+        black_box(racial_categories(self.profiles.iter(), &self.race_weights, &self.hispanic_race_weights));
+    }
+
+    // Everything the report command computes, the preferences of every attribute, the outcomes and the metrics
+    pub fn report(&self) {
+        black_box(build_report_of(&self.args, self.profiles.clone()).expect("Could not analyze the generated matches"));
+    }
+
+    pub fn bootstrap_test(&self, iterations: u32) {
+        black_box(stats::bootstrap_test(&self.counts, &self.shares, iterations, &mut StdRng::seed_from_u64(0)));
+    }
+
+    pub fn simulate_null(&self, iterations: u32) {
+        black_box(stats::simulate_null(&self.counts, &self.shares, iterations, &mut StdRng::seed_from_u64(0)));
+    }
+}
@@ -0,0 +1,103 @@
+use std::{collections::BTreeMap, error::Error, fs::File, io::Write, path::Path};
+
+use crate::{format, stats, HingeProfile, WhoLastReplied};
+
+const BUNDLE_VERSION: u32 = 1;
+
+const METRICS: [&str; 5] = ["conversation_rate", "date_rate", "date_conversion", "you_ghosted_rate", "they_ghosted_rate"];
+
+// Rates are rounded so a bundle cannot be matched back to exact counts
+const RATE_PRECISION: f64 = 1000.0;
+
+// Anonymized outcome rates of one person, a rate is left out when fewer than the floor of matches went into it
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Bundle {
+    pub version: u32,
+    pub metrics: BTreeMap<String, f64>
+}
+
+// A benchmark file holds a single bundle or a list of them
+#[derive(Debug, serde::Deserialize)]
+#[serde(untagged)]
+enum BundleFile {
+    One(Bundle),
+    Many(Vec<Bundle>)
+}
+
+fn rate(count: usize, total: usize, floor: u32) -> Option<f64> {
+    (total >= floor as usize && total > 0).then(|| (count as f64 / total as f64 * RATE_PRECISION).round() / RATE_PRECISION)
+}
+
+pub fn bundle(profiles: &[HingeProfile], floor: u32) -> Bundle {
+    let matches = profiles.len();
+    let conversations = profiles.iter().filter(|profile| profile.convo).count();
+    let count = |outcome: WhoLastReplied| profiles.iter().filter(|profile| profile.who_last_replied == outcome).count();
+
+    // in METRICS order
+    let rates = [
+        rate(conversations, matches, floor),
+        rate(count(WhoLastReplied::Met), matches, floor),
+        rate(count(WhoLastReplied::Met), conversations, floor),
+        rate(count(WhoLastReplied::Them), matches, floor),
+        rate(count(WhoLastReplied::You), matches, floor)
+    ];
+
+    Bundle {
+        version: BUNDLE_VERSION,
+        metrics: METRICS.into_iter().zip(rates).filter_map(|(metric, rate)| Some((metric.to_string(), rate?))).collect()
+    }
+}
+
+pub fn write_bundle(bundle: &Bundle, mut output: impl Write) -> Result<(), Box<dyn Error>> {
+    serde_json::to_writer_pretty(&mut output, bundle)?;
+    writeln!(output)?;
+    Ok(())
+}
+
+pub fn load_benchmarks(paths: &[impl AsRef<Path>]) -> Result<Vec<Bundle>, Box<dyn Error>> {
+    let mut bundles = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| format!("Could not open benchmarks {}: {}", path.display(), err))?;
+        match serde_json::from_reader(file)? {
+            BundleFile::One(bundle) => bundles.push(bundle),
+            BundleFile::Many(many) => bundles.extend(many)
+        }
+    }
+
+    if let Some(bundle) = bundles.iter().find(|bundle| bundle.version != BUNDLE_VERSION) {
+        return Err(format!("Benchmark bundle version {} is not supported, expected {}", bundle.version, BUNDLE_VERSION).into());
+    }
+    Ok(bundles)
+}
+
+// Where each of your rates falls among the same rate of the benchmark bundles, ties count as half
+pub fn print_benchmark(mine: &Bundle, benchmarks: &[Bundle]) {
+    println!("\n\t         Your Rates Against {} Benchmark Bundles (Percentile = Share of Benchmarks Below You)", benchmarks.len());
+    println!("\t{:<20}   {:<8}   Benchmarks   {:<8}   Percentile", "Metric", "You", "Median");
+    for (metric, value) in &mine.metrics {
+        let mut values = benchmarks.iter().filter_map(|bundle| bundle.metrics.get(metric).copied()).collect::<Vec<_>>();
+        if values.is_empty() {
+            println!("\t{:<20}   {}   0            -          -", metric, format::percent(*value));
+            continue;
+        }
+
+        values.sort_by(f64::total_cmp);
+        let below = values.iter().filter(|other| *other < value).count();
+        let ties = values.iter().filter(|other| *other == value).count();
+        let percentile = (below as f64 + ties as f64 / 2.0) / values.len() as f64 * 100.0;
+        println!(
+            "\t{:<20}   {}   {:<10}   {}   {:.0}",
+            metric,
+            format::percent(*value),
+            values.len(),
+            format::percent(stats::quantile(&values, 0.5)),
+            percentile
+        );
+    }
+
+    let skipped = METRICS.into_iter().filter(|metric| !mine.metrics.contains_key(*metric)).collect::<Vec<_>>();
+    if !skipped.is_empty() {
+        println!("\tToo few matches to report {}", skipped.join(", "));
+    }
+}
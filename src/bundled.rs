@@ -0,0 +1,100 @@
+use std::{error::Error, fs, path::{Path, PathBuf}};
+
+// Baseline tables compiled into the binary with the vendored-baselines feature so --offline runs need neither the
+// network nor prepared CSVs. Each set is the baseline files gzipped under data/baselines/<set>. The national race
+// counts are the 2020 census shares of NATIONAL_RACE_WEIGHTS applied to the 2020 population.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum BaselineSet {
+    /// Cook and DuPage counties with Chicago metro area religion and education
+    #[default]
+    Chicago,
+    /// United States race, religion and education, with no age baseline
+    National
+}
+
+impl BaselineSet {
+    // Geography of the rows of the religion and education baselines of the set
+    pub fn geography(self) -> &'static str {
+        match self {
+            BaselineSet::Chicago => "Chicago Metro Area",
+            BaselineSet::National => "United States"
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            BaselineSet::Chicago => "chicago",
+            BaselineSet::National => "national"
+        }
+    }
+}
+
+// Paths of the files of a set once written out, the age baseline only when the set has one
+#[derive(Debug, Clone)]
+pub struct Baselines {
+    pub race: PathBuf,
+    pub hispanic: PathBuf,
+    pub age: Option<PathBuf>,
+    pub religion: PathBuf,
+    pub education: PathBuf
+}
+
+#[cfg(feature = "vendored-baselines")]
+macro_rules! baseline_files {
+    ($set:literal: $($name:literal),+) => {
+        &[$(($name, include_bytes!(concat!("../data/baselines/", $set, "/", $name, ".gz")))),+]
+    };
+}
+
+#[cfg(feature = "vendored-baselines")]
+fn files(set: BaselineSet) -> &'static [(&'static str, &'static [u8])] {
+    match set {
+        BaselineSet::Chicago => baseline_files!(
+            "chicago": "demographics.csv", "hispanic_demographics.csv", "age_demographics.csv", "religion_demographics.csv", "education_demographics.csv"
+        ),
+        BaselineSet::National => baseline_files!(
+            "national": "demographics.csv", "hispanic_demographics.csv", "religion_demographics.csv", "education_demographics.csv"
+        )
+    }
+}
+
+#[cfg(not(feature = "vendored-baselines"))]
+fn files(_: BaselineSet) -> &'static [(&'static str, &'static [u8])] {
+    &[]
+}
+
+// Decompresses the files of a set into `directory` so they are read like any other baseline
+pub fn extract(set: BaselineSet, directory: &Path) -> Result<Baselines, Box<dyn Error>> {
+    let files = files(set);
+    if files.is_empty() {
+        return Err("--offline needs the bundled baselines, build with --features vendored-baselines".into());
+    }
+
+    let directory = directory.join(set.name());
+    fs::create_dir_all(&directory).map_err(|err| format!("Could not create {}: {}", directory.display(), err))?;
+    for (name, compressed) in files {
+        let path = directory.join(name);
+        fs::write(&path, decompress(compressed)?).map_err(|err| format!("Could not write {}: {}", path.display(), err))?;
+    }
+
+    let path = |name: &str| directory.join(name);
+    Ok(Baselines {
+        race: path("demographics.csv"),
+        hispanic: path("hispanic_demographics.csv"),
+        age: files.iter().any(|(name, _)| *name == "age_demographics.csv").then(|| path("age_demographics.csv")),
+        religion: path("religion_demographics.csv"),
+        education: path("education_demographics.csv")
+    })
+}
+
+#[cfg(feature = "vendored-baselines")]
+fn decompress(compressed: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut contents = Vec::new();
+    std::io::Read::read_to_end(&mut flate2::read::GzDecoder::new(compressed), &mut contents)?;
+    Ok(contents)
+}
+
+#[cfg(not(feature = "vendored-baselines"))]
+fn decompress(_: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    Err("Built without the bundled baselines".into())
+}
@@ -0,0 +1,144 @@
+use chrono::{Datelike, NaiveDateTime};
+use colored::Colorize;
+
+use crate::{export::{Export, Sender}, format, stats};
+
+// One observation of an engagement signal at a point in time
+struct Observation {
+    at: NaiveDateTime,
+    value: f64
+}
+
+struct Signal {
+    name: &'static str,
+    unit: &'static str,
+    // whether a rising value means less engagement
+    rising_is_decline: bool,
+    observations: Vec<Observation>
+}
+
+// Hours until you answered each of their messages, or until your first message after matching when the export has
+// no received messages
+fn reply_latency(export: &Export) -> Signal {
+    let mut observations = Vec::new();
+    for (matched_at, interaction) in export.matches() {
+        let chat = interaction.messages();
+        if export.has_received_messages() {
+            for pair in chat.windows(2).filter(|pair| pair[0].sender == Sender::Them && pair[1].sender == Sender::Me) {
+                observations.push(Observation { at: pair[1].timestamp, value: (pair[1].timestamp - pair[0].timestamp).num_minutes() as f64 / 60.0 });
+            }
+        } else if let Some(first) = chat.iter().find(|message| message.sender == Sender::Me) {
+            observations.push(Observation { at: first.timestamp, value: (first.timestamp - matched_at).num_minutes().max(0) as f64 / 60.0 });
+        }
+    }
+
+    Signal {
+        name: if export.has_received_messages() { "Reply Latency" } else { "Match to First Message" },
+        unit: "hours",
+        rising_is_decline: true,
+        observations
+    }
+}
+
+fn message_length(export: &Export) -> Signal {
+    let observations = export
+        .interactions
+        .iter()
+        .flat_map(|interaction| &interaction.chats)
+        .filter(|message| message.sender == Sender::Me)
+        .map(|message| Observation { at: message.timestamp, value: message.body.chars().count() as f64 })
+        .collect();
+
+    Signal { name: "Message Length", unit: "characters", rising_is_decline: false, observations }
+}
+
+// 1 when you opened the conversation with a match, the first message is yours when senders are known and otherwise
+// any message from you counts
+fn initiation(export: &Export) -> Signal {
+    let observations = export
+        .matches()
+        .map(|(matched_at, interaction)| {
+            let initiated = if export.has_received_messages() {
+                interaction.messages().first().is_some_and(|message| message.sender == Sender::Me)
+            } else {
+                !interaction.chats.is_empty()
+            };
+            Observation { at: matched_at, value: initiated as u8 as f64 }
+        })
+        .collect();
+
+    Signal { name: "Conversation Initiation", unit: "share of matches", rising_is_decline: false, observations }
+}
+
+fn month_key(at: NaiveDateTime) -> (i32, u32) {
+    (at.year(), at.month())
+}
+
+// Trends each engagement signal over the local time of the export. A signal is flagged when its correlation with time
+// points towards less engagement at the given significance level.
+pub fn print_burnout(export: &Export, alpha: f64) {
+    let signals = [reply_latency(export), message_length(export), initiation(export)];
+    let Some(start) = signals.iter().flat_map(|signal| &signal.observations).map(|observation| observation.at).min() else {
+        println!("\nNo messages in the export, skipping burnout detection");
+        return;
+    };
+
+    let mut months = signals
+        .iter()
+        .flat_map(|signal| &signal.observations)
+        .map(|observation| month_key(export.local(observation.at)))
+        .collect::<Vec<_>>();
+    months.sort();
+    months.dedup();
+
+    println!("\n\t         Engagement by Month (Mean per Month)");
+    println!("\t{:<7}   {:>22}   {:>22}   {:>23}", "Month", signals[0].name, signals[1].name, signals[2].name);
+    for month in &months {
+        let means = signals
+            .iter()
+            .map(|signal| {
+                let values = signal
+                    .observations
+                    .iter()
+                    .filter(|observation| month_key(export.local(observation.at)) == *month)
+                    .map(|observation| observation.value)
+                    .collect::<Vec<_>>();
+                if values.is_empty() {
+                    String::from("-")
+                } else {
+                    format::decimal(values.iter().sum::<f64>() / values.len() as f64, 2)
+                }
+            })
+            .collect::<Vec<_>>();
+        println!("\t{}-{:02}   {:>22}   {:>22}   {:>23}", month.0, month.1, means[0], means[1], means[2]);
+    }
+
+    println!("\n\t         Engagement Trends (Pearson r Against Days Since {}, * = p < {})", start.date(), alpha);
+    let mut declines = Vec::new();
+    for signal in &signals {
+        let days = signal.observations.iter().map(|observation| (observation.at - start).num_minutes() as f64 / 1440.0).collect::<Vec<_>>();
+        // latencies are heavily skewed so they are trended on a log scale
+        let values = signal
+            .observations
+            .iter()
+            .map(|observation| if signal.rising_is_decline { observation.value.ln_1p() } else { observation.value })
+            .collect::<Vec<_>>();
+
+        let Some(correlation) = stats::pearson(&days, &values) else {
+            println!("\t{:<25}   not enough variation to test", signal.name);
+            continue;
+        };
+
+        let declining = (correlation.r > 0.0) == signal.rising_is_decline && correlation.p_value < alpha;
+        if declining {
+            declines.push(signal.name);
+        }
+        println!("\t{:<25}   {}{}   ({})", signal.name, correlation, if correlation.p_value < alpha { " *" } else { "" }, signal.unit);
+    }
+
+    if declines.is_empty() {
+        println!("\tNo significant decline in engagement");
+    } else {
+        println!("\t{}", format!("You may be burning out on the app, declining: {}", declines.join(", ")).red().bold());
+    }
+}
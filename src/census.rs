@@ -0,0 +1,257 @@
+use std::{collections::BTreeMap, error::Error, fs, io::Write, path::{Path, PathBuf}, process};
+
+use crate::{datausa, webhook};
+
+// Hispanic or Latino origin by race, counts of each race alone outside the Hispanic population and within it
+pub const RACE_TABLE: &str = "B03002";
+
+// FIPS codes of the states, DC and Puerto Rico
+const STATES: [(&str, &str); 52] = [
+    ("01", "Alabama"), ("02", "Alaska"), ("04", "Arizona"), ("05", "Arkansas"), ("06", "California"), ("08", "Colorado"),
+    ("09", "Connecticut"), ("10", "Delaware"), ("11", "District of Columbia"), ("12", "Florida"), ("13", "Georgia"),
+    ("15", "Hawaii"), ("16", "Idaho"), ("17", "Illinois"), ("18", "Indiana"), ("19", "Iowa"), ("20", "Kansas"),
+    ("21", "Kentucky"), ("22", "Louisiana"), ("23", "Maine"), ("24", "Maryland"), ("25", "Massachusetts"),
+    ("26", "Michigan"), ("27", "Minnesota"), ("28", "Mississippi"), ("29", "Missouri"), ("30", "Montana"),
+    ("31", "Nebraska"), ("32", "Nevada"), ("33", "New Hampshire"), ("34", "New Jersey"), ("35", "New Mexico"),
+    ("36", "New York"), ("37", "North Carolina"), ("38", "North Dakota"), ("39", "Ohio"), ("40", "Oklahoma"),
+    ("41", "Oregon"), ("42", "Pennsylvania"), ("44", "Rhode Island"), ("45", "South Carolina"), ("46", "South Dakota"),
+    ("47", "Tennessee"), ("48", "Texas"), ("49", "Utah"), ("50", "Vermont"), ("51", "Virginia"), ("53", "Washington"),
+    ("54", "West Virginia"), ("55", "Wisconsin"), ("56", "Wyoming"), ("72", "Puerto Rico")
+];
+
+// A county by its 5 digit FIPS code, the state's 2 digits then the county's 3 like 17031 for Cook County, IL. Written
+// STATE:COUNTY like 17:031 works too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct County {
+    pub state: String,
+    pub county: String
+}
+
+impl County {
+    pub fn state_name(&self) -> &'static str {
+        STATES.iter().find(|(code, _)| *code == self.state).map_or("", |(_, name)| name)
+    }
+}
+
+impl std::str::FromStr for County {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let expected = || format!("Expected a 5 digit county FIPS code like 17031, found {}", value);
+        // a split inside a multibyte character is a typo like any other
+        let (state, county) = value.split_once(':').or_else(|| value.split_at_checked(value.len().min(2))).ok_or_else(expected)?;
+        let digits = |code: &str, length: usize| code.len() == length && code.chars().all(|c| c.is_ascii_digit());
+        if !digits(state, 2) || !digits(county, 3) {
+            return Err(expected());
+        }
+        if !STATES.iter().any(|(code, _)| *code == state) {
+            return Err(format!("{} is not the FIPS code of a state, county {}", state, value));
+        }
+        Ok(County { state: state.to_string(), county: county.to_string() })
+    }
+}
+
+impl std::fmt::Display for County {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.state, self.county)?;
+        Ok(())
+    }
+}
+
+// Where downloaded tables are kept, whether to download them again anyway, and whether downloading is allowed at all
+#[derive(Debug, Clone)]
+pub struct Cache {
+    pub directory: PathBuf,
+    pub refresh: bool,
+    pub offline: bool
+}
+
+impl Cache {
+    // $XDG_CACHE_HOME/hinge-analyzer, or ~/.cache/hinge-analyzer
+    pub fn default_directory() -> PathBuf {
+        std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .unwrap_or_else(|| PathBuf::from(".cache"))
+            .join("hinge-analyzer")
+    }
+
+    // The file at `key` under the cache directory, downloaded first when it is missing or refreshed. The file and its
+    // contents are returned.
+    pub fn get(&self, key: PathBuf, description: &str, download: impl FnOnce() -> Result<String, Box<dyn Error>>) -> Result<(PathBuf, String), Box<dyn Error>> {
+        let path = self.directory.join(key);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) if !self.refresh || self.offline => contents,
+            _ if self.offline => return Err(format!("{} isn't cached and --offline is set", description).into()),
+            _ => {
+                let contents = download()?;
+                fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))?;
+                fs::write(&path, &contents).map_err(|err| format!("Could not cache {}: {}", path.display(), err))?;
+                contents
+            }
+        };
+        Ok((path, contents))
+    }
+}
+
+// Where a county's race counts come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Source {
+    /// The Census Bureau's API, table B03002 of the American Community Survey
+    #[default]
+    Census,
+    /// Data USA's API, the same survey's estimates behind the race and ethnicity of its county profiles
+    Datausa
+}
+
+impl Source {
+    pub fn name(self) -> &'static str {
+        match self {
+            Source::Census => "census",
+            Source::Datausa => "datausa"
+        }
+    }
+
+    pub fn race_counts(self, county: &County, vintage: u16, cache: &Cache) -> Result<RaceCounts, Box<dyn Error>> {
+        match self {
+            Source::Census => race_counts(county, vintage, cache),
+            Source::Datausa => datausa::race_counts(county, vintage, cache)
+        }
+    }
+}
+
+// Races in the order of the counts of RaceCounts
+pub const RACES: [&str; 7] = [
+    "White",
+    "Black or African American",
+    "American Indian & Alaska Native",
+    "Asian",
+    "Native Hawaiian & Other Pacific Islander",
+    "Some Other Race",
+    "Two or More Races"
+];
+
+// A county's population by race alone outside the Hispanic population and within it, whichever source it came from
+#[derive(Debug, Clone)]
+pub struct RaceCounts {
+    pub geography: String,
+    pub non_hispanic: [u32; RACES.len()],
+    pub hispanic: [u32; RACES.len()]
+}
+
+// The estimates of one table for one county by variable, e.g. B03002_003E
+#[derive(Debug, Clone)]
+pub struct Table {
+    pub county: County,
+    pub name: String,
+    values: BTreeMap<String, String>
+}
+
+impl Table {
+    // The name the census gives the county with its code, e.g. Cook County, Illinois (17031)
+    pub fn geography(&self) -> String {
+        format!("{} ({})", self.name, self.county)
+    }
+
+    pub fn count(&self, variable: &str) -> Result<u32, String> {
+        let value = self.values.get(variable).ok_or_else(|| format!("No {} in the census table of {}", variable, self.name))?;
+        value.parse().map_err(|_| format!("{} of {} is {}, not a count", variable, self.name, value))
+    }
+}
+
+// A table of the 5-year American Community Survey for a county, from the cache unless it is missing or refreshed.
+// Downloads go through curl, with the key in HINGE_ANALYZER_CENSUS_API_KEY when set.
+pub fn fetch(county: &County, table: &str, vintage: u16, cache: &Cache) -> Result<Table, Box<dyn Error>> {
+    // one file per geography, table and vintage
+    let key = Path::new("census").join(vintage.to_string()).join(table).join(format!("{}.json", county));
+    let description = format!("Census table {} of {} for {}", table, county, vintage);
+    let (path, contents) = cache.get(key, &description, || download(county, table, vintage))?;
+
+    let table = parse(county, &contents).map_err(|err| format!("Could not read census table {} of {} ({}): {}", table, county, path.display(), err))?;
+    Ok(table)
+}
+
+// Race counts from B03002, whose variables 003 to 009 are the races outside the Hispanic population and 013 to 019 the
+// same races within it
+pub fn race_counts(county: &County, vintage: u16, cache: &Cache) -> Result<RaceCounts, Box<dyn Error>> {
+    let table = fetch(county, RACE_TABLE, vintage, cache)?;
+    let mut counts = RaceCounts { geography: table.geography(), non_hispanic: [0; RACES.len()], hispanic: [0; RACES.len()] };
+    for index in 0..RACES.len() {
+        counts.non_hispanic[index] = table.count(&format!("{}_{:03}E", RACE_TABLE, 3 + index))?;
+        counts.hispanic[index] = table.count(&format!("{}_{:03}E", RACE_TABLE, 13 + index))?;
+    }
+    Ok(counts)
+}
+
+// The body of a URL fetched with curl, failing on HTTP errors. The URL goes to curl on stdin since it can carry the API
+// key.
+pub fn curl(url: &str, description: &str) -> Result<String, Box<dyn Error>> {
+    let mut child = process::Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location", "--config", "-"])
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::piped())
+        .stderr(process::Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Could not run curl to download {}: {}", description, err))?;
+    let config = format!("url = {}\n", webhook::quote(url));
+    let written = child.stdin.take().map_or(Ok(()), |mut stdin| stdin.write_all(config.as_bytes()));
+    let output = child.wait_with_output()?;
+    written?;
+    if !output.status.success() {
+        return Err(format!("Could not download {}: {}", description, String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+fn download(county: &County, table: &str, vintage: u16) -> Result<String, Box<dyn Error>> {
+    let mut url = format!(
+        "https://api.census.gov/data/{}/acs/acs5?get=NAME,group({})&for=county:{}&in=state:{}",
+        vintage, table, county.county, county.state
+    );
+    if let Ok(key) = std::env::var("HINGE_ANALYZER_CENSUS_API_KEY") {
+        url.push_str(&format!("&key={}", key));
+    }
+
+    let description = format!("census table {} of {}, check it is a county of {}", table, county, county.state_name());
+    curl(&url, &description)
+}
+
+// The API answers with a header row of variables and a row of their values, the state and county of the row are checked
+// against the county asked for
+fn parse(county: &County, contents: &str) -> Result<Table, Box<dyn Error>> {
+    let rows = serde_json::from_str::<Vec<Vec<serde_json::Value>>>(contents)?;
+    let [headers, values, ..] = rows.as_slice() else {
+        return Err(format!("No county {} in {}", county, county.state_name()).into());
+    };
+
+    let text = |value: &serde_json::Value| value.as_str().map_or_else(|| value.to_string(), str::to_string);
+    let values = headers.iter().map(text).zip(values.iter().map(text)).collect::<BTreeMap<_, _>>();
+    let code = |variable: &str| values.get(variable).map(String::as_str);
+    if code("state") != Some(county.state.as_str()) || code("county") != Some(county.county.as_str()) {
+        return Err(format!("Expected county {} but the table is of {}{}", county, code("state").unwrap_or("?"), code("county").unwrap_or("???")).into());
+    }
+
+    let name = values.get("NAME").cloned().unwrap_or_else(|| county.to_string());
+    Ok(Table { county: county.clone(), name, values })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_county_codes() {
+        let cook = County { state: String::from("17"), county: String::from("031") };
+        assert_eq!("17031".parse::<County>(), Ok(cook.clone()));
+        assert_eq!(" 17:031 ".parse::<County>(), Ok(cook));
+    }
+
+    #[test]
+    fn malformed_codes_are_errors() {
+        for value in ["a€1", "€", "1703", "170311", "17:31", "17-031", ""] {
+            assert_eq!(value.parse::<County>(), Err(format!("Expected a 5 digit county FIPS code like 17031, found {}", value.trim())), "{}", value);
+        }
+        assert_eq!("03001".parse::<County>(), Err(String::from("03 is not the FIPS code of a state, county 03001")));
+    }
+}
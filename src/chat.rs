@@ -0,0 +1,217 @@
+use std::collections::{HashMap, HashSet};
+
+use indicatif::ParallelProgressIterator;
+use rayon::prelude::*;
+
+use crate::{export::{Export, Sender}, format, progress, stats};
+
+// Messages from the start of a conversation whose emoji density is compared with whether it survived past them
+const OPENING_MESSAGES: usize = 10;
+const TOP_EMOJI: usize = 10;
+
+const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "again", "all", "am", "an", "and", "any", "are", "as", "at", "be", "been", "before", "being", "but",
+    "by", "can", "could", "did", "do", "does", "doing", "don't", "for", "from", "had", "has", "have", "having", "he", "her",
+    "here", "hers", "him", "his", "how", "i", "i'm", "i'd", "i'll", "i've", "if", "in", "into", "is", "it", "it's", "its", "just",
+    "me", "more", "most", "my", "no", "not", "now", "of", "oh", "ok", "okay", "on", "one", "only", "or", "other", "our", "out",
+    "over", "really", "so", "some", "such", "than", "that", "that's", "the", "their", "them", "then", "there", "these", "they",
+    "this", "those", "to", "too", "up", "us", "very", "was", "we", "were", "what", "when", "where", "which", "while", "who",
+    "why", "will", "with", "would", "yeah", "yes", "you", "you're", "your", "yours"
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Word(String),
+    Emoji(String)
+}
+
+// Pictographic code points, the blocks emoji are drawn from along with the older symbols that render as emoji
+fn is_pictographic(c: char) -> bool {
+    matches!(c as u32,
+        0x1F300..=0x1F5FF | 0x1F600..=0x1F64F | 0x1F680..=0x1F6FF | 0x1F900..=0x1F9FF | 0x1FA70..=0x1FAFF |
+        0x2600..=0x26FF | 0x2700..=0x27BF | 0x2B50 | 0x2B55 | 0x1F004 | 0x1F0CF
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+// Variation selectors and skin tones change how the emoji before them looks without being emoji of their own
+fn is_modifier(c: char) -> bool {
+    c == '\u{FE0F}' || c == '\u{FE0E}' || ('\u{1F3FB}'..='\u{1F3FF}').contains(&c)
+}
+
+// Splits a message into lowercase words and emoji, keeping zero width joiner sequences like 👩‍❤️‍👨, skin tones and
+// flags together as one emoji. Apostrophes inside words are kept so contractions stay whole.
+pub fn tokenize(body: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if is_pictographic(c) {
+            let mut emoji = c.to_string();
+            loop {
+                if let Some(modifier) = chars.next_if(|&c| is_modifier(c)) {
+                    emoji.push(modifier);
+                } else if let Some(joiner) = chars.next_if_eq(&'\u{200D}') {
+                    emoji.push(joiner);
+                    if let Some(joined) = chars.next_if(|&c| is_pictographic(c)) {
+                        emoji.push(joined);
+                    }
+                } else {
+                    break;
+                }
+            }
+            tokens.push(Token::Emoji(emoji));
+        } else if is_regional_indicator(c) {
+            let mut flag = c.to_string();
+            flag.extend(chars.next_if(|&c| is_regional_indicator(c)));
+            tokens.push(Token::Emoji(flag));
+        } else if c.is_alphanumeric() {
+            let mut word = c.to_lowercase().collect::<String>();
+            while let Some(c) = chars.next_if(|&c| c.is_alphanumeric() || c == '\'' || c == '’') {
+                word.extend(c.to_lowercase());
+            }
+            tokens.push(Token::Word(word.trim_end_matches(['\'', '’']).to_string()));
+        }
+    }
+
+    tokens
+}
+
+fn emoji(body: &str) -> impl Iterator<Item = String> {
+    tokenize(body).into_iter().filter_map(|token| match token {
+        Token::Emoji(emoji) => Some(emoji),
+        Token::Word(_) => None
+    })
+}
+
+// Emoji counts by sender with the most used of each, and the point-biserial correlation between the emoji per message
+// of a conversation's opening messages and the conversation surviving past them or leading to a date
+pub fn print_emoji(export: &Export) {
+    let senders: &[(Sender, &str)] = if export.has_received_messages() { &[(Sender::Me, "You"), (Sender::Them, "Them")] } else { &[(Sender::Me, "You")] };
+    let messages = export.interactions.iter().flat_map(|interaction| &interaction.chats);
+
+    println!("\n\t         Emoji Usage");
+    println!("\t{:<6}   Messages   Emoji     Per Message   Most Used", "Sender");
+    for (sender, label) in senders {
+        let mut counts = HashMap::<String, u32>::new();
+        let mut sent = 0;
+        for message in messages.clone().filter(|message| message.sender == *sender) {
+            sent += 1;
+            for emoji in emoji(&message.body) {
+                *counts.entry(emoji).or_default() += 1;
+            }
+        }
+
+        let total = counts.values().sum::<u32>();
+        let mut ranked = counts.into_iter().collect::<Vec<_>>();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let most_used = ranked.iter().take(TOP_EMOJI).map(|(emoji, count)| format!("{} {}", emoji, count)).collect::<Vec<_>>();
+        println!("\t{:<6}   {:<8}   {:<7}   {:<11}   {}", label, format::count(sent), format::count(total), format::decimal(total as f64 / sent.max(1) as f64, 3), most_used.join("  "));
+    }
+
+    let (densities, survived): (Vec<f64>, Vec<bool>) = export
+        .interactions
+        .par_iter()
+        .progress_with(progress::bar(export.interactions.len(), "Counting emoji"))
+        .filter(|interaction| !interaction.chats.is_empty())
+        .map(|interaction| {
+            let chat = interaction.messages();
+            let opening = &chat[..chat.len().min(OPENING_MESSAGES)];
+            let density = opening.iter().map(|message| emoji(&message.body).count()).sum::<usize>() as f64 / opening.len() as f64;
+            (density, chat.len() > OPENING_MESSAGES || interaction.met())
+        })
+        .unzip();
+
+    println!("\n\t         Emoji per Message in the First {} Messages Against Surviving Past Them", OPENING_MESSAGES);
+    match stats::point_biserial(&densities, &survived) {
+        Some(correlation) => println!("\t{}", correlation),
+        None => println!("\tNot enough variation to test")
+    }
+}
+
+// Words and bigrams of the words left after dropping stopwords and single characters, bigrams only join words that
+// were next to each other in the message
+fn terms(body: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut previous: Option<String> = None;
+    for token in tokenize(body) {
+        let Token::Word(word) = token else {
+            continue;
+        };
+
+        if STOPWORDS.contains(&word.as_str()) || word.chars().count() < 2 || word.chars().all(|c| c.is_numeric()) {
+            previous = None;
+            continue;
+        }
+
+        if let Some(previous) = previous.replace(word.clone()) {
+            terms.push(format!("{} {}", previous, word));
+        }
+        terms.push(word);
+    }
+    terms
+}
+
+// Ranks the terms of one group of conversations by TF-IDF, the term's share of all terms in the group weighted by the
+// log inverse of how many conversations overall use it
+fn print_top_terms(title: &str, group: &[&Vec<String>], document_frequency: &HashMap<&str, usize>, conversations: usize, min_conversations: usize, top: usize) {
+    let mut counts = HashMap::<&str, usize>::new();
+    for term in group.iter().flat_map(|terms| terms.iter()) {
+        *counts.entry(term).or_default() += 1;
+    }
+    let total = counts.values().sum::<usize>().max(1) as f64;
+
+    let mut ranked = counts
+        .into_iter()
+        .filter(|(term, _)| document_frequency[term] >= min_conversations)
+        .map(|(term, count)| {
+            let in_group = group.iter().filter(|terms| terms.iter().any(|other| other == term)).count();
+            (term, count, in_group, count as f64 / total * (conversations as f64 / document_frequency[term] as f64).ln())
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| b.3.total_cmp(&a.3).then_with(|| a.0.cmp(b.0)));
+
+    println!("\n\t         {} ({} Conversations)", title, group.len());
+    println!("\t{:<25}   Count     Conversations   TF-IDF", "Term");
+    for (term, count, in_group, score) in ranked.into_iter().take(top) {
+        println!(
+            "\t{:<25}   {:<7}   {}        {:.5}",
+            term,
+            count,
+            format::percent(in_group as f64 / group.len() as f64),
+            score
+        );
+    }
+}
+
+// The most distinctive words and bigrams of conversations that led to a date against those that did not, terms used
+// in fewer than min_conversations conversations are left out since a single chat can make a rare term look distinctive
+pub fn print_topics(export: &Export, min_conversations: usize, top: usize) {
+    let conversations = export
+        .interactions
+        .par_iter()
+        .progress_with(progress::bar(export.interactions.len(), "Extracting terms"))
+        .filter(|interaction| !interaction.chats.is_empty())
+        .map(|interaction| (interaction.met(), interaction.chats.iter().flat_map(|message| terms(&message.body)).collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+
+    let mut document_frequency = HashMap::<&str, usize>::new();
+    for (_, terms) in &conversations {
+        for term in terms.iter().map(String::as_str).collect::<HashSet<_>>() {
+            *document_frequency.entry(term).or_default() += 1;
+        }
+    }
+
+    let (dated, died): (Vec<_>, Vec<_>) = conversations.iter().partition(|(met, _)| *met);
+    for (title, group) in [("Top Terms in Conversations that Led to a Date", dated), ("Top Terms in Conversations that Died", died)] {
+        if group.is_empty() {
+            println!("\nNo conversations for {}, skipping", title.to_lowercase());
+            continue;
+        }
+        let group = group.iter().map(|(_, terms)| terms).collect::<Vec<_>>();
+        print_top_terms(title, &group, &document_frequency, conversations.len(), min_conversations, top);
+    }
+}
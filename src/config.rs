@@ -0,0 +1,80 @@
+use std::{collections::BTreeMap, error::Error, ffi::OsString, fs, path::Path};
+
+// Read when --config isn't given, running without one is fine
+pub const DEFAULT_PATH: &str = "hinge-analyzer.toml";
+
+// Command line options kept in a file. The top level applies to every run and a [preset.NAME] table on top of it when
+// selected with --preset. Keys are option names without the dashes, e.g. `filter = "convo"` or `sample-cutoff = 0`.
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct Config {
+    #[serde(default, rename = "preset")]
+    presets: BTreeMap<String, toml::Table>,
+    #[serde(flatten)]
+    defaults: toml::Table
+}
+
+// The config file at the path, or the default one when there is one
+pub fn load(path: Option<&Path>) -> Result<Config, Box<dyn Error>> {
+    let path = match path {
+        Some(path) => path,
+        None if Path::new(DEFAULT_PATH).exists() => Path::new(DEFAULT_PATH),
+        None => return Ok(Config::default())
+    };
+
+    let contents = fs::read_to_string(path).map_err(|err| format!("Could not read config {}: {}", path.display(), err))?;
+    Ok(toml::from_str(&contents).map_err(|err| format!("Could not parse config {}: {}", path.display(), err))?)
+}
+
+// Environment variable of an option, e.g. HINGE_ANALYZER_SAMPLE_CUTOFF for sample_cutoff
+pub fn environment_variable(option: &str) -> String {
+    format!("HINGE_ANALYZER_{}", option.replace('-', "_").to_uppercase())
+}
+
+// The options of a config table as command line arguments, an array repeats its option and false leaves a flag off.
+// Options with their environment variable set are left to it when `environment` is read.
+fn table_arguments(table: &toml::Table, environment: bool) -> Result<Vec<OsString>, String> {
+    let mut arguments = Vec::new();
+    for (key, value) in table.iter().filter(|(key, _)| !environment || std::env::var_os(environment_variable(key)).is_none()) {
+        let option = format!("--{}", key.replace('_', "-"));
+        let values = match value {
+            toml::Value::Array(values) => values.iter().collect(),
+            value => vec![value]
+        };
+
+        for value in values {
+            let argument = match value {
+                toml::Value::Boolean(true) => option.clone(),
+                toml::Value::Boolean(false) => continue,
+                toml::Value::String(value) => format!("{}={}", option, value),
+                toml::Value::Integer(value) => format!("{}={}", option, value),
+                toml::Value::Float(value) => format!("{}={}", option, value),
+                _ => return Err(format!("Config option {} must be a string, number, boolean or an array of them", key))
+            };
+            arguments.push(argument.into());
+        }
+    }
+    Ok(arguments)
+}
+
+impl Config {
+    // Arguments of the top level and the preset, in that order so the preset wins over the top level
+    pub fn arguments(&self, preset: Option<&str>) -> Result<Vec<OsString>, String> {
+        let mut arguments = table_arguments(&self.defaults, true)?;
+        if let Some(name) = preset {
+            let preset = self.presets.get(name).ok_or_else(|| {
+                let names = self.presets.keys().map(String::as_str).collect::<Vec<_>>();
+                match names.is_empty() {
+                    true => format!("No preset {} in the config, it has no presets", name),
+                    false => format!("No preset {} in the config, expected one of {}", name, names.join(", "))
+                }
+            })?;
+            arguments.extend(table_arguments(preset, true)?);
+        }
+        Ok(arguments)
+    }
+
+    // Arguments of the top level whatever the environment says, for the C library whose host's environment is its own
+    pub fn own_arguments(&self) -> Result<Vec<OsString>, String> {
+        table_arguments(&self.defaults, false)
+    }
+}
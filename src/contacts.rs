@@ -0,0 +1,45 @@
+use std::{error::Error, io::Write};
+
+use crate::{export::Export, HingeProfile, WhoLastReplied};
+
+#[derive(Debug, serde::Serialize)]
+struct DateRecord {
+    name: String,
+    id: Option<String>,
+    matched: Option<String>,
+    met: Option<String>,
+    last_activity: Option<String>
+}
+
+// Everyone you met as CSV. The Hinge export has no names so its matches are numbered in the order you matched, while
+// matches.csv has names but no dates.
+pub fn write_dates(export: Option<&Export>, profiles: &[HingeProfile], output: impl Write) -> Result<(), Box<dyn Error>> {
+    let records = match export {
+        Some(export) => {
+            let mut met = export.matches().filter(|(_, interaction)| interaction.met()).collect::<Vec<_>>();
+            met.sort_by_key(|(matched_at, _)| *matched_at);
+            met.into_iter()
+                .enumerate()
+                .map(|(i, (matched_at, interaction))| DateRecord {
+                    name: format!("Match {}", i + 1),
+                    id: interaction.id.clone(),
+                    matched: Some(export.local(matched_at).date().to_string()),
+                    met: interaction.met_at().map(|met_at| export.local(met_at).date().to_string()),
+                    last_activity: interaction.timestamps().max().map(|timestamp| export.local(timestamp).date().to_string())
+                })
+                .collect::<Vec<_>>()
+        },
+        None => profiles
+            .iter()
+            .filter(|profile| profile.who_last_replied == WhoLastReplied::Met)
+            .map(|profile| DateRecord { name: profile.name.clone(), id: profile.id.clone(), matched: None, met: None, last_activity: None })
+            .collect()
+    };
+
+    let mut writer = csv::Writer::from_writer(output);
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use crate::{attribute, export::user::{self, User}, format, height, Ethnicities, EthnicityBits, HingeProfile, Race};
+
+const CENTIMETERS_PER_INCH: f64 = 2.54;
+
+// Hinge's ethnicity names, matched by the start of the lowercased name
+const ETHNICITY_NAMES: [(&str, EthnicityBits); 10] = [
+    ("american indian", Ethnicities::NATIVE_AMERICAN),
+    ("native american", Ethnicities::NATIVE_AMERICAN),
+    ("black", Ethnicities::BLACK_AFRICAN_DESCENT),
+    ("east asian", Ethnicities::EAST_ASIAN),
+    ("hispanic", Ethnicities::HISPANIC_LATINO),
+    ("middle eastern", Ethnicities::MIDDLE_EASTERN),
+    ("pacific islander", Ethnicities::PACIFIC_ISLANDER),
+    ("south asian", Ethnicities::SOUTH_ASIAN),
+    ("southeast asian", Ethnicities::SOUTHEAST_ASIAN),
+    ("white", Ethnicities::WHITE_CAUCASIAN)
+];
+
+// Races of a list of Hinge ethnicity names, each name on its own so a preference for East Asian and White is Asian or
+// White rather than Multiracial
+fn races(names: &[String]) -> Vec<Race> {
+    names
+        .iter()
+        .map(|name| {
+            let name = name.trim().to_lowercase();
+            ETHNICITY_NAMES.iter().find(|(prefix, _)| name.starts_with(prefix)).map_or(Ethnicities::OTHER, |(_, bits)| *bits)
+        })
+        .filter_map(|bits| Race::try_from(bits).ok())
+        .collect()
+}
+
+fn inside(inside: bool) -> &'static str {
+    if inside { "Inside" } else { "Outside" }
+}
+
+fn same(same: bool) -> &'static str {
+    if same { "Same" } else { "Different" }
+}
+
+// Whether each match falls inside the filters you set in Hinge with the conversion of either side, followed by
+// homophily, how matches that share your race or religion and are close to your age and height convert
+pub fn print_context(profiles: &[HingeProfile], user: &User) {
+    let profile = &user.profile;
+    let preferences = &user.preferences;
+    let my_races = races(&profile.ethnicities);
+    let my_height = profile.height_centimeters.map(|centimeters| (centimeters / CENTIMETERS_PER_INCH).round() as u8);
+
+    println!(
+        "\nYou: age {}, height {}, race {}, religion {}",
+        profile.age.map_or(String::from("unknown"), |age| age.to_string()),
+        my_height.map_or(String::from("unknown"), height::format_height),
+        if my_races.is_empty() { String::from("unknown") } else { my_races.iter().map(Race::to_string).collect::<Vec<_>>().join(", ") },
+        if profile.religions.is_empty() { String::from("unknown") } else { profile.religions.join(", ") }
+    );
+
+    if preferences.age_min.is_some() || preferences.age_max.is_some() {
+        let (min, max) = (preferences.age_min.unwrap_or(u8::MIN), preferences.age_max.unwrap_or(u8::MAX));
+        let rates = attribute::outcome_rates(profiles.iter(), |profile| profile.age.map(|age| inside((min..=max).contains(&age))));
+        attribute::print_outcome_rates(&format!("Age Filter {}-{}", min, max), &rates);
+    }
+
+    if preferences.height_min.is_some() || preferences.height_max.is_some() {
+        let min = preferences.height_min.map_or(0.0, |centimeters| centimeters / CENTIMETERS_PER_INCH);
+        let max = preferences.height_max.map_or(f64::INFINITY, |centimeters| centimeters / CENTIMETERS_PER_INCH);
+        let rates = attribute::outcome_rates(profiles.iter(), |profile| profile.height.map(|height| inside((min..=max).contains(&(height as f64)))));
+        attribute::print_outcome_rates("Height Filter", &rates);
+    }
+
+    if let Some(max) = preferences.distance_miles_max {
+        let rates = attribute::outcome_rates(profiles.iter(), |profile| profile.distance_miles.map(|distance| inside(distance <= max)));
+        attribute::print_outcome_rates(&format!("Distance Filter {} mi", max), &rates);
+    }
+
+    if !user::open_to_all(&preferences.ethnicity_preference) {
+        let preferred = races(&preferences.ethnicity_preference);
+        let rates = attribute::outcome_rates(profiles.iter(), |profile| profile.race.map(|race| inside(preferred.contains(&race))));
+        attribute::print_outcome_rates("Ethnicity Filter", &rates);
+    }
+
+    if !user::open_to_all(&preferences.religion_preference) {
+        let preferred = &preferences.religion_preference;
+        let rates = attribute::outcome_rates(profiles.iter(), |profile| profile.religion.as_deref().map(|religion| {
+            inside(preferred.iter().any(|preferred| preferred.eq_ignore_ascii_case(religion.trim())))
+        }));
+        attribute::print_outcome_rates("Religion Filter", &rates);
+    }
+
+    if !my_races.is_empty() {
+        let rates = attribute::outcome_rates(profiles.iter(), |profile| profile.race.map(|race| same(my_races.contains(&race))));
+        attribute::print_outcome_rates("Same Race", &rates);
+    }
+
+    if !profile.religions.is_empty() {
+        let rates = attribute::outcome_rates(profiles.iter(), |match_profile| match_profile.religion.as_deref().map(|religion| {
+            same(profile.religions.iter().any(|mine| mine.eq_ignore_ascii_case(religion.trim())))
+        }));
+        attribute::print_outcome_rates("Same Religion", &rates);
+    }
+
+    // gaps are signed as theirs minus yours
+    let gaps: [(&str, Option<Vec<f64>>, &str); 2] = [
+        ("Age Gap", profile.age.map(|age| profiles.iter().filter_map(|profile| Some(profile.age? as f64 - age as f64)).collect()), "years"),
+        ("Height Gap", my_height.map(|mine| profiles.iter().filter_map(|profile| Some(profile.height? as f64 - mine as f64)).collect()), "in")
+    ];
+    println!("\n\t         Age and Height Gaps");
+    for (gap, differences, unit) in gaps {
+        let Some(differences) = differences.filter(|differences| !differences.is_empty()) else {
+            continue;
+        };
+        let mean = differences.iter().sum::<f64>() / differences.len() as f64;
+        let mean_absolute = differences.iter().map(|difference| difference.abs()).sum::<f64>() / differences.len() as f64;
+        println!("\t{} (Theirs Minus Yours): mean {}{} {}, mean absolute {} {}, {} profiles", gap, if mean >= 0.0 { "+" } else { "" }, format::decimal(mean, 1), unit, format::decimal(mean_absolute, 1), unit, differences.len());
+    }
+}
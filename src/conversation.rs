@@ -0,0 +1,310 @@
+use chrono::{Duration, NaiveDateTime};
+
+use crate::{export::{Export, Message, Sender}, format, stats};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    YouReplied,
+    TheyReplied,
+    Stalled,
+    Met
+}
+
+const STATES: [State; 4] = [State::YouReplied, State::TheyReplied, State::Stalled, State::Met];
+
+impl State {
+    fn label(self) -> &'static str {
+        match self {
+            State::YouReplied => "You Replied",
+            State::TheyReplied => "They Replied",
+            State::Stalled => "Stalled",
+            State::Met => "Met"
+        }
+    }
+
+    fn index(self) -> usize {
+        STATES.iter().position(|state| *state == self).expect("Bad conversation state")
+    }
+}
+
+// States a conversation passed through in order, consecutive messages from one side are self transitions. A gap of
+// stall_days between messages, or before the end of the export, is a stall and meeting ends the conversation.
+fn states(messages: &[&Message], met_at: Option<NaiveDateTime>, export_end: NaiveDateTime, stall_days: i64) -> Vec<State> {
+    let stall = Duration::days(stall_days);
+    let mut states = Vec::new();
+    let mut last = None;
+    for message in messages.iter().filter(|message| met_at.is_none_or(|met_at| message.timestamp <= met_at)) {
+        if last.is_some_and(|last| message.timestamp - last >= stall) {
+            states.push(State::Stalled);
+        }
+        states.push(if message.sender == Sender::Them { State::TheyReplied } else { State::YouReplied });
+        last = Some(message.timestamp);
+    }
+
+    if met_at.is_some() {
+        states.push(State::Met);
+    } else if last.is_some_and(|last| export_end - last >= stall) {
+        states.push(State::Stalled);
+    }
+
+    states
+}
+
+// Transition probabilities between conversation states estimated from every transition in the export, along with how
+// often a stalled conversation picked back up. A stall at the end of a conversation is a stall that never recovered.
+pub fn print_transitions(export: &Export, stall_days: i64) {
+    if !export.has_received_messages() {
+        println!("\nNo received messages in the export, skipping conversation states since they need both sides");
+        return;
+    }
+
+    let Some(export_end) = export.interactions.iter().flat_map(|interaction| interaction.timestamps()).max() else {
+        println!("\nNo activity in the export, skipping conversation states");
+        return;
+    };
+
+    let mut transitions = [[0_u32; STATES.len()]; STATES.len()];
+    let mut conversations = 0;
+    for (_, interaction) in export.matches() {
+        let states = states(&interaction.messages(), interaction.met_at(), export_end, stall_days);
+        conversations += !states.is_empty() as u32;
+        for pair in states.windows(2) {
+            transitions[pair[0].index()][pair[1].index()] += 1;
+        }
+        if states.last() == Some(&State::Stalled) {
+            transitions[State::Stalled.index()][State::Stalled.index()] += 1;
+        }
+    }
+
+    println!("\n\t         Conversation State Transitions ({} Conversations, Stalled After {} Days, Row = From)", conversations, stall_days);
+    println!("\t{:<12}   {}   Total", "", STATES.map(|state| format!("{:>12}", state.label())).join("   "));
+    for state in STATES.iter().filter(|state| **state != State::Met) {
+        let row = transitions[state.index()];
+        let total = row.iter().sum::<u32>();
+        let probabilities = row.map(|count| format!("{:>12}", format::percent(count as f64 / total.max(1) as f64)));
+        println!("\t{:<12}   {}   {}", state.label(), probabilities.join("   "), total);
+    }
+
+    let stalled = transitions[State::Stalled.index()];
+    let stalls = stalled.iter().sum::<u32>();
+    let recovered = stalls - stalled[State::Stalled.index()];
+    println!(
+        "\tStalled conversations recovered {} of {} times ({}), {} by you and {} by them",
+        recovered,
+        stalls,
+        format::inline_percent(recovered as f64 / stalls.max(1) as f64, 3),
+        stalled[State::YouReplied.index()],
+        stalled[State::TheyReplied.index()]
+    );
+}
+
+// Upper bounds in hours of how long you waited before double texting, the last bucket is open ended
+const WAIT_BUCKETS: [(i64, &str); 4] = [(24, "< 1 day"), (72, "1-3 days"), (168, "3-7 days"), (i64::MAX, "7+ days")];
+
+// Double texts are messages you sent after your own unanswered message, at least gap_hours later so a thought split
+// over a few messages is not counted. A double text revived the conversation when they replied after it and preceded
+// a ghost when they never messaged again.
+pub fn print_double_texts(export: &Export, gap_hours: i64) {
+    if !export.has_received_messages() {
+        println!("\nNo received messages in the export, skipping double texts since they need both sides");
+        return;
+    }
+
+    // [double texts, revived] by wait bucket
+    let mut buckets = [[0_u32; 2]; WAIT_BUCKETS.len()];
+    let mut conversations = 0;
+    for interaction in &export.interactions {
+        let chat = interaction.messages();
+        let mut double_texted = false;
+        for (i, pair) in chat.windows(2).enumerate() {
+            let wait = (pair[1].timestamp - pair[0].timestamp).num_hours();
+            if pair[0].sender != Sender::Me || pair[1].sender != Sender::Me || wait < gap_hours {
+                continue;
+            }
+
+            let revived = chat[i + 2..].iter().any(|later| later.sender == Sender::Them);
+            let bucket = WAIT_BUCKETS.iter().position(|(hours, _)| wait < *hours).expect("Bad double text wait");
+            buckets[bucket][0] += 1;
+            buckets[bucket][1] += revived as u32;
+            double_texted = true;
+        }
+        conversations += double_texted as u32;
+    }
+
+    let [double_texts, revived] = buckets.iter().fold([0, 0], |[total, revived], bucket| [total + bucket[0], revived + bucket[1]]);
+    if double_texts == 0 {
+        println!("\nNo double texts in the export");
+        return;
+    }
+
+    println!("\n\t         Double Texts ({} in {} Conversations, At Least {} Hours After Your Last Message)", double_texts, conversations, gap_hours);
+    println!("\t{:<10}   Double Texts   Revived   Ghosted   Revival Rate", "Waited");
+    let rows = WAIT_BUCKETS.iter().map(|(_, label)| *label).zip(buckets).chain([("Total", [double_texts, revived])]);
+    for (label, [count, revived]) in rows.filter(|(_, [count, _])| *count > 0) {
+        println!("\t{:<10}   {:<12}   {:<7}   {:<7}   {}", label, count, revived, count - revived, format::percent(revived as f64 / count as f64));
+        format::explain(&[format::Rate::new("revival rate", revived as usize, "revived", count as usize, "double texts")]);
+    }
+}
+
+// Upper bounds of your share of the words in a conversation for each balance bucket
+const BALANCE_BUCKETS: [(f64, &str); 3] = [(0.4, "They Carried"), (0.6, "Balanced"), (f64::INFINITY, "You Carried")];
+
+type BalanceOutcome = fn(&Balance) -> bool;
+
+// How a conversation ended, a conversation you did not meet from is a ghost by whoever did not send the last message
+struct Balance {
+    message_share: f64,
+    word_share: f64,
+    they_ghosted: bool,
+    you_ghosted: bool,
+    met: bool
+}
+
+// Your share of the messages and words of every conversation with both sides, grouped into balance buckets, and the
+// correlation of your word share with each way a conversation ends
+pub fn print_balance(export: &Export) {
+    if !export.has_received_messages() {
+        println!("\nNo received messages in the export, skipping message balance since it needs both sides");
+        return;
+    }
+
+    let balances = export
+        .interactions
+        .iter()
+        .filter_map(|interaction| {
+            let chat = interaction.messages();
+            let words = |sender: Sender| chat
+                .iter()
+                .filter(|message| message.sender == sender)
+                .map(|message| message.body.split_whitespace().count())
+                .sum::<usize>() as f64;
+            let mine = chat.iter().filter(|message| message.sender == Sender::Me).count();
+            if mine == 0 || mine == chat.len() {
+                return None;
+            }
+
+            let met = interaction.met();
+            let last = chat.last()?.sender;
+            Some(Balance {
+                message_share: mine as f64 / chat.len() as f64,
+                word_share: words(Sender::Me) / (words(Sender::Me) + words(Sender::Them)).max(1.0),
+                they_ghosted: !met && last == Sender::Me,
+                you_ghosted: !met && last == Sender::Them,
+                met
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if balances.is_empty() {
+        println!("\nNo conversations with messages from both sides, skipping message balance");
+        return;
+    }
+
+    let rate = |group: &[&Balance], outcome: BalanceOutcome| group.iter().filter(|balance| outcome(balance)).count() as f64 / group.len() as f64;
+
+    println!("\n\t         Message Balance (Your Share of Words, {} Conversations with Both Sides)", balances.len());
+    println!("\t{:<12}   Conversations   Message Share   They Ghosted   You Ghosted   Date Rate", "Balance");
+    let mut lower = 0.0;
+    for (upper, label) in BALANCE_BUCKETS {
+        let group = balances.iter().filter(|balance| balance.word_share >= lower && balance.word_share < upper).collect::<Vec<_>>();
+        lower = upper;
+        if group.is_empty() {
+            continue;
+        }
+
+        println!(
+            "\t{:<12}   {:<13}   {}        {}       {}      {}",
+            label,
+            group.len(),
+            format::percent(group.iter().map(|balance| balance.message_share).sum::<f64>() / group.len() as f64),
+            format::percent(rate(&group, |balance| balance.they_ghosted)),
+            format::percent(rate(&group, |balance| balance.you_ghosted)),
+            format::percent(rate(&group, |balance| balance.met))
+        );
+    }
+
+    let word_shares = balances.iter().map(|balance| balance.word_share).collect::<Vec<_>>();
+    let outcomes: [(&str, BalanceOutcome); 3] = [
+        ("They Ghosted", |balance| balance.they_ghosted),
+        ("You Ghosted", |balance| balance.you_ghosted),
+        ("Met", |balance| balance.met)
+    ];
+    println!("\n\t         Point-Biserial Correlation of Your Word Share with Outcome");
+    for (outcome, flag) in outcomes {
+        let flags = balances.iter().map(flag).collect::<Vec<_>>();
+        match stats::point_biserial(&word_shares, &flags) {
+            Some(correlation) => println!("\t{:<12}   {}", outcome, correlation),
+            None => println!("\t{:<12}   not enough variation to test", outcome)
+        }
+    }
+}
+
+const SNIPPET_LENGTH: usize = 40;
+
+// Share of past silences that ended with another message, given who sent the message before the silence and that it
+// had already lasted `days`. Silences still going at the end of the export count as not revived.
+fn revival_probability(silences: &[(Sender, i64, bool)], sender: Sender, days: i64) -> f64 {
+    let (total, revived) = silences
+        .iter()
+        .filter(|(other, length, _)| *other == sender && *length >= days)
+        .fold((0, 0), |(total, revived), (_, _, was_revived)| (total + 1, revived + *was_revived as u32));
+    revived as f64 / total.max(1) as f64
+}
+
+// Conversations worth picking back up as of the end of the export: ones where they sent the last message, or that
+// stalled at least stall_days ago, as long as the last message is under max_days old. They are ranked by how often
+// past silences of the same length and last sender were broken.
+pub fn print_resurrectable(export: &Export, stall_days: i64, max_days: i64, top: usize) {
+    let Some(export_end) = export.interactions.iter().flat_map(|interaction| interaction.timestamps()).max() else {
+        println!("\nNo activity in the export, skipping resurrectable conversations");
+        return;
+    };
+
+    // (sender before the silence, days it lasted, whether another message ended it)
+    let mut silences = Vec::new();
+    for interaction in &export.interactions {
+        let chat = interaction.messages();
+        for pair in chat.windows(2) {
+            silences.push((pair[0].sender, (pair[1].timestamp - pair[0].timestamp).num_days(), true));
+        }
+        if let Some(last) = chat.last() {
+            silences.push((last.sender, (export_end - last.timestamp).num_days(), false));
+        }
+    }
+
+    let mut candidates = export
+        .matches()
+        // an unmatched conversation can't be picked back up
+        .filter(|(_, interaction)| !interaction.met() && interaction.removal().is_none())
+        .filter_map(|(matched_at, interaction)| {
+            let chat = interaction.messages();
+            let last = *chat.last()?;
+            let silent = (export_end - last.timestamp).num_days();
+            let waiting_on_you = export.has_received_messages() && last.sender == Sender::Them;
+            ((waiting_on_you || silent >= stall_days) && silent <= max_days)
+                .then(|| (matched_at, last, silent, revival_probability(&silences, last.sender, silent)))
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| b.3.total_cmp(&a.3).then_with(|| a.2.cmp(&b.2)));
+
+    if candidates.is_empty() {
+        println!("\nNo conversations to pick back up as of {}", export.local(export_end).date());
+        return;
+    }
+
+    println!("\n\t         Resurrectable Conversations as of {} ({} Found, Showing Up to {})", export.local(export_end).date(), candidates.len(), top);
+    println!("\t{:<10}   {:<10}   Days Silent   Last From   Revival     Last Message", "Matched", "Last");
+    for (matched_at, last, silent, probability) in candidates.into_iter().take(top) {
+        let body = last.body.trim();
+        let snippet = if body.chars().count() > SNIPPET_LENGTH { format!("{}…", body.chars().take(SNIPPET_LENGTH - 1).collect::<String>()) } else { body.to_string() };
+        println!(
+            "\t{:<10}   {:<10}   {:<11}   {:<9}   {}   {}",
+            export.local(matched_at).date(),
+            export.local(last.timestamp).date(),
+            silent,
+            if last.sender == Sender::Them { "Them" } else { "You" },
+            format::percent(probability),
+            snippet
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use std::{error::Error, io::Write};
+
+use crate::{
+    export::{Export, Interaction, Sender},
+    OPTIONAL_COLUMNS, REQUIRED_COLUMNS
+};
+
+// Who Last Replied of a match. Without received messages in the export every chat ends with your message.
+fn last_reply(interaction: &Interaction) -> &'static str {
+    if interaction.met() {
+        return "Met";
+    }
+
+    match interaction.messages().last() {
+        None => "None",
+        Some(message) if message.sender == Sender::Them => "Them",
+        Some(_) => "You"
+    }
+}
+
+// Every match of the export as a matches.csv row, numbered in the order you matched since the export has no names.
+// Ethnicity is left unspecified and the optional columns other than the id blank for you to annotate.
+pub fn write_matches(export: &Export, output: impl Write) -> Result<(), Box<dyn Error>> {
+    let mut matches = export.matches().collect::<Vec<_>>();
+    matches.sort_by_key(|(matched_at, _)| *matched_at);
+
+    let mut writer = csv::Writer::from_writer(output);
+    writer.write_record(REQUIRED_COLUMNS.iter().chain(&OPTIONAL_COLUMNS))?;
+    for (i, (_, interaction)) in matches.into_iter().enumerate() {
+        // telling Hinge you met is a conversation even when the chat moved off the app
+        let convo = export.conversation(interaction) || interaction.met();
+        let record = [format!("Match {}", i + 1), String::from("1"), (convo as u8).to_string(), last_reply(interaction).to_string()]
+            .into_iter()
+            .chain(REQUIRED_COLUMNS[4..].iter().map(|_| String::from("0")))
+            .chain(OPTIONAL_COLUMNS.iter().map(|column| if *column == "id" { interaction.id.clone().unwrap_or_default() } else { String::new() }));
+        writer.write_record(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use std::{error::Error, thread, time::{Duration, Instant}};
+
+use colored::Colorize;
+
+// Time between scheduled runs written like 7d, 12h or 30m, with w for weeks and s for seconds
+#[derive(Debug, Clone, Copy)]
+pub struct Interval {
+    pub duration: Duration
+}
+
+impl std::str::FromStr for Interval {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_lowercase();
+        let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+        let (number, unit) = value.split_at(split);
+        let seconds = match unit.trim() {
+            "w" => 7 * 24 * 60 * 60,
+            "d" => 24 * 60 * 60,
+            "h" => 60 * 60,
+            "m" => 60,
+            "s" => 1,
+            _ => return Err(format!("Expected an interval like 7d, 12h or 30m, found {}", value))
+        };
+        let number = match number.parse::<u64>() {
+            Ok(number) if number > 0 => number,
+            _ => return Err(format!("Expected an interval like 7d, 12h or 30m, found {}", value))
+        };
+        // the next run has to be a time the calendar can show
+        let duration = number
+            .checked_mul(seconds)
+            .map(Duration::from_secs)
+            .filter(|duration| chrono::Duration::from_std(*duration).is_ok_and(|duration| chrono::Local::now().checked_add_signed(duration).is_some()));
+        duration.map(|duration| Interval { duration }).ok_or_else(|| format!("The interval {} is too long", value))
+    }
+}
+
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let seconds = self.duration.as_secs();
+        let (number, unit) = [(7 * 24 * 60 * 60, "w"), (24 * 60 * 60, "d"), (60 * 60, "h"), (60, "m")]
+            .into_iter()
+            .find(|(unit, _)| seconds.is_multiple_of(*unit))
+            .map_or((seconds, "s"), |(unit, name)| (seconds / unit, name));
+        write!(f, "{}{}", number, unit)?;
+        Ok(())
+    }
+}
+
+// Calls `run` every interval, the first time right away, until it has run `runs` times or forever when there is no
+// limit. A failed run is reported and the schedule carries on so one bad export doesn't stop the trend.
+pub fn schedule(interval: Interval, runs: Option<u32>, mut run: impl FnMut() -> Result<String, Box<dyn Error>>) {
+    println!("Running every {}{}", interval, runs.map_or(String::new(), |runs| format!(", {} times", runs)));
+    let mut completed = 0;
+    loop {
+        let started = chrono::Local::now();
+        let timer = Instant::now();
+        match run() {
+            Ok(outcome) => println!("[{}] {}", started.format("%Y-%m-%d %H:%M"), outcome),
+            Err(err) => println!("[{}] {}", started.format("%Y-%m-%d %H:%M"), format!("Scheduled run failed: {}", err).red().bold())
+        }
+
+        completed += 1;
+        if runs.is_some_and(|runs| completed >= runs) {
+            return;
+        }
+        if let Some(next) = chrono::Duration::from_std(interval.duration).ok().and_then(|duration| started.checked_add_signed(duration)) {
+            println!("Next run at {}", next.format("%Y-%m-%d %H:%M"));
+        }
+        thread::sleep(interval.duration.saturating_sub(timer.elapsed()));
+    }
+}
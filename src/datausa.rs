@@ -0,0 +1,97 @@
+use std::{error::Error, path::Path};
+
+use crate::census::{self, County, RACES};
+
+// Population of each race by Hispanic origin, the measure behind the race and ethnicity section of Data USA's county
+// profiles. Its rows are the American Community Survey 5-year estimates, so counts match table B03002 of the same year.
+const MEASURE: &str = "Hispanic Population";
+
+#[derive(Debug, serde::Deserialize)]
+struct Response {
+    data: Vec<Row>
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Row {
+    #[serde(rename = "Race")]
+    race: String,
+    #[serde(rename = "Ethnicity")]
+    ethnicity: String,
+    #[serde(rename = "Hispanic Population")]
+    population: f64,
+    #[serde(rename = "Geography")]
+    geography: String,
+    #[serde(rename = "ID Geography")]
+    geography_id: String
+}
+
+// Data USA names races like the census with Alone appended, e.g. White Alone, and leaves it off Two or More Races
+fn race_index(race: &str) -> Option<usize> {
+    let race = race.trim();
+    let race = race.strip_suffix(" Alone").unwrap_or(race);
+    RACES.iter().position(|name| name.eq_ignore_ascii_case(race))
+}
+
+// Race counts of a county from Data USA, cached next to the census tables and read the same way
+pub fn race_counts(county: &County, vintage: u16, cache: &census::Cache) -> Result<census::RaceCounts, Box<dyn Error>> {
+    // Data USA identifies counties by their FIPS code after the 05000US summary level
+    let geography_id = format!("05000US{}", county);
+    let key = Path::new("datausa").join(vintage.to_string()).join("race").join(format!("{}.json", county));
+    let description = format!("Data USA race counts of {} for {}", county, vintage);
+    let (path, contents) = cache.get(key, &description, || {
+        let url = format!(
+            "https://datausa.io/api/data?Geography={}&drilldowns=Race,Ethnicity&measures={}&year={}",
+            geography_id,
+            MEASURE.replace(' ', "%20"),
+            vintage
+        );
+        census::curl(&url, &description)
+    })?;
+
+    parse(county, &geography_id, &contents)
+        .map_err(|err| format!("Could not read Data USA race counts of {} ({}): {}", county, path.display(), err).into())
+}
+
+// Every race must have a row in and out of the Hispanic population, other geographies in the response are an error
+fn parse(county: &County, geography_id: &str, contents: &str) -> Result<census::RaceCounts, Box<dyn Error>> {
+    let response = serde_json::from_str::<Response>(contents)?;
+    let Some(first) = response.data.first() else {
+        return Err(format!("No rows for county {}, Data USA may not have this year yet so try an earlier --vintage", county).into());
+    };
+
+    let mut counts = census::RaceCounts {
+        geography: format!("{} ({})", first.geography, county),
+        non_hispanic: [0; RACES.len()],
+        hispanic: [0; RACES.len()]
+    };
+    let mut found = [[false; RACES.len()]; 2];
+    for row in &response.data {
+        if row.geography_id != geography_id {
+            return Err(format!("Expected {} but a row is of {}", geography_id, row.geography_id).into());
+        }
+        let Some(index) = race_index(&row.race) else {
+            continue;
+        };
+
+        let hispanic = match row.ethnicity.as_str() {
+            "Hispanic or Latino" => true,
+            "Not Hispanic or Latino" => false,
+            other => return Err(format!("Unknown ethnicity {}", other).into())
+        };
+        let population = row.population.round() as u32;
+        if hispanic {
+            counts.hispanic[index] += population;
+        } else {
+            counts.non_hispanic[index] += population;
+        }
+        found[hispanic as usize][index] = true;
+    }
+
+    for (hispanic, races) in found.iter().enumerate() {
+        if let Some(index) = races.iter().position(|found| !found) {
+            let ethnicity = if hispanic == 1 { "Hispanic or Latino" } else { "Not Hispanic or Latino" };
+            return Err(format!("No {} {} row", ethnicity, RACES[index]).into());
+        }
+    }
+    Ok(counts)
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use crate::{HingeProfile, WhoLastReplied};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Strategy {
+    /// Keep every row
+    Off,
+    /// Keep only the last row of each person
+    KeepLatest,
+    /// Keep the last row with the furthest outcome any of the person's matches reached
+    Merge
+}
+
+// The latest row with the events of the earlier ones folded in: a conversation or date in any match counts, and the
+// latest answers win unless only an earlier match has them
+fn merge(earlier: HingeProfile, latest: &mut HingeProfile) {
+    if earlier.who_last_replied == WhoLastReplied::Met || (earlier.convo && !latest.convo) {
+        latest.who_last_replied = earlier.who_last_replied;
+    }
+    latest.matched |= earlier.matched;
+    latest.convo |= earlier.convo;
+    latest.stage = latest.stage.take().or(earlier.stage);
+    latest.date_rating = latest.date_rating.or(earlier.date_rating);
+}
+
+// Profiles with the rows of people matched more than once collapsed into one, rows later in the file being the later
+// matches, with the number of rows collapsed. Each person stays where their first row was.
+pub fn dedup(profiles: Vec<HingeProfile>, strategy: Strategy) -> (Vec<HingeProfile>, usize) {
+    if strategy == Strategy::Off {
+        return (profiles, 0);
+    }
+
+    let rows = profiles.len();
+    let mut positions = HashMap::new();
+    let mut deduplicated = Vec::new();
+    for profile in profiles {
+        match positions.get(&profile.key()) {
+            Some(&position) => {
+                let earlier = std::mem::replace(&mut deduplicated[position], profile);
+                if strategy == Strategy::Merge {
+                    merge(earlier, &mut deduplicated[position]);
+                }
+            },
+            None => {
+                positions.insert(profile.key(), deduplicated.len());
+                deduplicated.push(profile);
+            }
+        }
+    }
+
+    let collapsed = rows - deduplicated.len();
+    (deduplicated, collapsed)
+}
@@ -0,0 +1,65 @@
+use crate::{format, stats, HingeProfile, WhoLastReplied};
+
+// Upper bounds in miles of each distance bucket, the last bucket is open ended
+const BUCKETS: &[f64] = &[1.0, 2.0, 5.0, 10.0, 25.0, 50.0];
+
+fn bucket_label(i: usize) -> String {
+    match i {
+        0 => format!("< {} mi", BUCKETS[0]),
+        i if i == BUCKETS.len() => format!("{}+ mi", BUCKETS[i - 1]),
+        i => format!("{}-{} mi", BUCKETS[i - 1], BUCKETS[i])
+    }
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    match values.len() {
+        0 => f64::NAN,
+        len if len % 2 == 0 => (values[len / 2 - 1] + values[len / 2]) / 2.0,
+        len => values[len / 2]
+    }
+}
+
+// Matches, conversations and dates per distance bucket, the distance of dates against everyone else and the
+// point-biserial correlation between distance and meeting
+pub fn print_distance_distribution(profiles: &[HingeProfile]) {
+    let profiles = profiles
+        .iter()
+        .filter_map(|profile| Some((profile.distance_miles?, profile)))
+        .collect::<Vec<_>>();
+
+    println!("\n\t         Match Distance Distribution ({} Profiles with Distance)", profiles.len());
+    println!("\t{:^15}   Matches   Share        Convos    Dates     Date Rate", "Distance");
+    for i in 0..=BUCKETS.len() {
+        let bucket = profiles
+            .iter()
+            .filter(|(distance, _)| BUCKETS.partition_point(|&bound| bound <= *distance) == i)
+            .map(|(_, profile)| profile)
+            .collect::<Vec<_>>();
+        let dates = bucket.iter().filter(|profile| profile.who_last_replied == WhoLastReplied::Met).count();
+        println!(
+            "\t{:<15}   {:<7}   {}     {:<7}   {:<7}   {}",
+            bucket_label(i),
+            bucket.len(),
+            format::percent(bucket.len() as f64 / profiles.len().max(1) as f64),
+            bucket.iter().filter(|profile| profile.convo).count(),
+            dates,
+            format::percent(dates as f64 / bucket.len().max(1) as f64)
+        );
+        format::explain(&[
+            format::Rate::new("share", bucket.len(), "profiles", profiles.len(), "profiles with a distance"),
+            format::Rate::new("date rate", dates, "dates", bucket.len(), "profiles")
+        ]);
+    }
+
+    let distances = profiles.iter().map(|(distance, _)| *distance).collect::<Vec<_>>();
+    let met = profiles.iter().map(|(_, profile)| profile.who_last_replied == WhoLastReplied::Met).collect::<Vec<_>>();
+    let mut met_distances = distances.iter().zip(&met).filter(|(_, &met)| met).map(|(distance, _)| *distance).collect::<Vec<_>>();
+    let mut other_distances = distances.iter().zip(&met).filter(|(_, &met)| !met).map(|(distance, _)| *distance).collect::<Vec<_>>();
+
+    println!("\tMedian Distance: {} mi (Met), {} mi (Did Not Meet)", format::decimal(median(&mut met_distances), 1), format::decimal(median(&mut other_distances), 1));
+    match stats::point_biserial(&distances, &met) {
+        Some(correlation) => println!("\tDistance vs Met (Point-Biserial): {}", correlation),
+        None => println!("\tDistance vs Met (Point-Biserial): not enough variation to test")
+    }
+}
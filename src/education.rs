@@ -0,0 +1,38 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Education {
+    HighSchool,
+    Bachelors,
+    Graduate
+}
+
+impl Education {
+    // Labels match the categories of the education baseline
+    pub fn label(self) -> &'static str {
+        match self {
+            Education::HighSchool => "High School",
+            Education::Bachelors => "Bachelor's",
+            Education::Graduate => "Graduate"
+        }
+    }
+}
+
+impl std::str::FromStr for Education {
+    type Err = &'static str;
+
+    // Accepts the Hinge labels (High School, Undergrad, Postgrad) along with common spellings of each level
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().replace(['\'', '.'], "").as_str() {
+            "high school" | "highschool" | "hs" | "some college" | "associates" => Ok(Education::HighSchool),
+            "bachelors" | "bachelor" | "undergrad" | "undergraduate" | "college" | "ba" | "bs" => Ok(Education::Bachelors),
+            "graduate" | "grad" | "postgrad" | "postgraduate" | "masters" | "master" | "phd" | "doctorate" | "md" | "jd" | "mba" => Ok(Education::Graduate),
+            _ => Err("Invalid value for Education")
+        }
+    }
+}
+
+impl std::fmt::Display for Education {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())?;
+        Ok(())
+    }
+}
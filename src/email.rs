@@ -0,0 +1,114 @@
+use std::{error::Error, io::Write, process};
+
+// SMTP server and login of the sender, read from the environment so credentials stay out of shell history
+#[derive(Debug, Clone)]
+pub struct Smtp {
+    // e.g. smtps://smtp.gmail.com:465, or smtp://localhost:25 for a local relay
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String
+}
+
+impl Smtp {
+    // HINGE_ANALYZER_SMTP_URL is required, HINGE_ANALYZER_SMTP_USERNAME and HINGE_ANALYZER_SMTP_PASSWORD are needed
+    // by most servers, and HINGE_ANALYZER_SMTP_FROM defaults to the username
+    pub fn from_env() -> Result<Self, Box<dyn Error>> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.trim().is_empty());
+        let url = var("HINGE_ANALYZER_SMTP_URL")
+            .ok_or("Set HINGE_ANALYZER_SMTP_URL to the SMTP server to email the report, e.g. smtps://smtp.gmail.com:465")?;
+        let username = var("HINGE_ANALYZER_SMTP_USERNAME");
+        let from = var("HINGE_ANALYZER_SMTP_FROM")
+            .or_else(|| username.clone())
+            .ok_or("Set HINGE_ANALYZER_SMTP_FROM or HINGE_ANALYZER_SMTP_USERNAME to the address the report is sent from")?;
+        Ok(Smtp { url, username, password: var("HINGE_ANALYZER_SMTP_PASSWORD"), from })
+    }
+}
+
+// A single part message with the body as HTML or plain text, lines ending in CRLF as SMTP expects
+fn message(from: &str, to: &[String], subject: &str, body: &str, html: bool) -> String {
+    let content_type = if html { "text/html" } else { "text/plain" };
+    let headers = [
+        format!("From: {}", from),
+        format!("To: {}", to.join(", ")),
+        format!("Subject: {}", subject),
+        format!("Date: {}", chrono::Local::now().to_rfc2822()),
+        "MIME-Version: 1.0".to_string(),
+        format!("Content-Type: {}; charset=utf-8", content_type),
+        "Content-Transfer-Encoding: 8bit".to_string()
+    ];
+    let body = body.lines().collect::<Vec<_>>().join("\r\n");
+    format!("{}\r\n\r\n{}\r\n", headers.join("\r\n"), body)
+}
+
+// curl greets the server with the path of the URL, or the name of the uploaded file when it has none, so the domain of
+// the sender is used when the URL doesn't name one
+fn url_with_greeting(url: &str, from: &str) -> String {
+    let address = url.split_once("://").map_or(url, |(_, address)| address);
+    match address.split_once('/') {
+        Some((_, path)) if !path.is_empty() => url.to_string(),
+        _ => {
+            let domain = from.rsplit_once('@').map_or("localhost", |(_, domain)| domain.trim_end_matches('>'));
+            format!("{}/{}", url.trim_end_matches('/'), domain)
+        }
+    }
+}
+
+// Whether the URL is plain SMTP to this machine, comparing the host exactly so smtp://localhost.example.com is not
+fn local_relay(url: &str) -> bool {
+    let url = url.to_lowercase();
+    let Some(address) = url.strip_prefix("smtp://") else {
+        return false;
+    };
+    let authority = address.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        Some(host) => host.split_once(']').map_or(host, |(host, _)| host),
+        None => host.split(':').next().unwrap_or_default()
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+// Sends the report through curl's SMTP support. The login goes to curl on stdin rather than its arguments so it doesn't
+// show up in the process list, and the message through a temporary file only this user can read, removed once sent.
+pub fn send(smtp: &Smtp, to: &[String], subject: &str, body: &str, html: bool) -> Result<(), Box<dyn Error>> {
+    let mut file = tempfile::Builder::new().prefix("hinge-analyzer-report-").suffix(".eml").tempfile()?;
+    file.write_all(message(&smtp.from, to, subject, body, html).as_bytes())
+        .and_then(|()| file.flush())
+        .map_err(|err| format!("Could not write {}: {}", file.path().display(), err))?;
+
+    let mut command = process::Command::new("curl");
+    let url = url_with_greeting(&smtp.url, &smtp.from);
+    command.args(["--silent", "--show-error", "--config", "-", "--url", &url, "--mail-from", &smtp.from]);
+    // the login is never sent in the clear, only a relay on this machine may skip TLS
+    if !local_relay(&smtp.url) {
+        command.arg("--ssl-reqd");
+    }
+    command.arg("--upload-file").arg(file.path());
+    for recipient in to {
+        command.args(["--mail-rcpt", recipient]);
+    }
+    let mut child = command
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Could not run curl to email the report: {}", err))?;
+
+    // curl's config syntax, quoted with backslash escapes
+    let quote = |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+    let config = match &smtp.username {
+        Some(username) => format!("user = {}\n", quote(&format!("{}:{}", username, smtp.password.as_deref().unwrap_or_default()))),
+        None => String::new()
+    };
+    let written = child.stdin.take().map_or(Ok(()), |mut stdin| stdin.write_all(config.as_bytes()));
+    let output = child.wait_with_output();
+    drop(file);
+
+    written?;
+    let output = output?;
+    if !output.status.success() {
+        return Err(format!("Could not email the report through {}: {}", smtp.url, String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,264 @@
+use std::{error::Error, fs::File, io::Read, path::Path};
+
+use chrono::{DateTime, Duration, NaiveDateTime};
+
+use crate::progress;
+
+pub mod media;
+pub mod user;
+
+// Hinge writes naive UTC timestamps like 2023-01-14 20:31:07, sometimes with fractional seconds
+fn timestamp<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<NaiveDateTime, D::Error> {
+    let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+    parse_timestamp(&value).ok_or_else(|| serde::de::Error::custom(format!("invalid timestamp {}", value)))
+}
+
+fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| DateTime::parse_from_rfc3339(value).ok().map(|timestamp| timestamp.naive_utc()))
+}
+
+// The official export only contains your own messages, a sender field is read when present for exports that were
+// merged with the other side of the conversation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sender {
+    #[default]
+    #[serde(alias = "you", alias = "user")]
+    Me,
+    #[serde(alias = "match")]
+    Them
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Event {
+    #[serde(deserialize_with = "timestamp")]
+    pub timestamp: NaiveDateTime
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Message {
+    #[serde(default)]
+    pub body: String,
+    #[serde(deserialize_with = "timestamp")]
+    pub timestamp: NaiveDateTime,
+    #[serde(default)]
+    pub sender: Sender
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Meeting {
+    #[serde(deserialize_with = "timestamp")]
+    pub timestamp: NaiveDateTime,
+    #[serde(default)]
+    pub did_meet_subject: Option<String>,
+    #[serde(default)]
+    pub was_my_type: Option<bool>,
+    #[serde(default, alias = "see_again")]
+    pub would_meet_again: Option<bool>
+}
+
+// A like you received, the official export does not record these so they only show up in exports merged with the
+// likes you received list. The content is the prompt or photo they liked.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct ReceivedLike {
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(default, alias = "prompt", alias = "photo")]
+    pub content: Option<String>
+}
+
+// How a match left your list, the official export calls every one of them a block with a type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Removal {
+    #[default]
+    #[serde(alias = "remove", alias = "unmatch")]
+    Unmatched,
+    #[serde(alias = "block", alias = "report")]
+    Blocked,
+    #[serde(alias = "hide", alias = "hidden")]
+    Hidden
+}
+
+impl std::fmt::Display for Removal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Block {
+    #[serde(deserialize_with = "timestamp")]
+    pub timestamp: NaiveDateTime,
+    #[serde(default)]
+    pub block_type: Removal
+}
+
+// One entry of matches.json, every interaction with one person whether or not it became a match
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Interaction {
+    // only in exports merged with the profiles of the people, the official export has no way to tell who an entry is
+    #[serde(default, alias = "match_id", alias = "profile_id")]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub like: Vec<Event>,
+    #[serde(default, alias = "liked_me")]
+    pub received_like: Vec<ReceivedLike>,
+    #[serde(default, rename = "match")]
+    pub matched: Vec<Event>,
+    #[serde(default)]
+    pub chats: Vec<Message>,
+    #[serde(default)]
+    pub we_met: Vec<Meeting>,
+    #[serde(default)]
+    pub block: Vec<Block>
+}
+
+impl Interaction {
+    pub fn matched_at(&self) -> Option<NaiveDateTime> {
+        self.matched.iter().map(|event| event.timestamp).min()
+    }
+
+    // Messages in the order they were sent
+    pub fn messages(&self) -> Vec<&Message> {
+        let mut messages = self.chats.iter().collect::<Vec<_>>();
+        messages.sort_by_key(|message| message.timestamp);
+        messages
+    }
+
+    // Every timestamp of activity in the interaction
+    pub fn timestamps(&self) -> impl Iterator<Item = NaiveDateTime> + Clone + '_ {
+        self.like
+            .iter()
+            .chain(&self.matched)
+            .map(|event| event.timestamp)
+            .chain(self.chats.iter().map(|message| message.timestamp))
+            .chain(self.we_met.iter().map(|meeting| meeting.timestamp))
+            .chain(self.block.iter().map(|block| block.timestamp))
+    }
+
+    // How and when the match ended, the first removal wins when there are several
+    pub fn removal(&self) -> Option<(NaiveDateTime, Removal)> {
+        self.block.iter().min_by_key(|block| block.timestamp).map(|block| (block.timestamp, block.block_type))
+    }
+
+    // A match you never sent a like to must have started with their like
+    pub fn they_liked_first(&self) -> bool {
+        self.like.is_empty() || !self.received_like.is_empty()
+    }
+
+    pub fn met(&self) -> bool {
+        self.met_at().is_some()
+    }
+
+    // Answer to a We Met survey question for a match you met, the latest answer wins when Hinge asked more than once
+    pub fn we_met_answer(&self, answer: impl Fn(&Meeting) -> Option<bool>) -> Option<bool> {
+        self.met_at()?;
+        self.we_met.iter().filter_map(|meeting| Some((meeting.timestamp, answer(meeting)?))).max_by_key(|(timestamp, _)| *timestamp).map(|(_, answer)| answer)
+    }
+
+    // When you told Hinge you met, the export has no record of the date itself
+    pub fn met_at(&self) -> Option<NaiveDateTime> {
+        self.we_met
+            .iter()
+            .filter(|meeting| meeting.did_meet_subject.as_deref().is_some_and(|answer| answer.eq_ignore_ascii_case("yes")))
+            .map(|meeting| meeting.timestamp)
+            .min()
+    }
+}
+
+#[derive(Debug)]
+pub struct Export {
+    pub interactions: Vec<Interaction>,
+    pub media: Vec<media::Media>,
+    // your own profile and filters from user.json
+    pub user: Option<user::User>,
+    // hours added to the UTC timestamps of the export for anything reported by time of day
+    pub utc_offset: i32,
+    received_messages: bool
+}
+
+impl Export {
+    pub fn local(&self, timestamp: NaiveDateTime) -> NaiveDateTime {
+        timestamp + Duration::hours(self.utc_offset as i64)
+    }
+
+    // Interactions that became a match, with the time they matched
+    pub fn matches(&self) -> impl Iterator<Item = (NaiveDateTime, &Interaction)> + Clone {
+        self.interactions.iter().filter_map(|interaction| Some((interaction.matched_at()?, interaction)))
+    }
+
+    // A conversation needs a reply, which is a message from them when senders are known and otherwise a chat that kept
+    // going past the first message
+    pub fn conversation(&self, interaction: &Interaction) -> bool {
+        if self.has_received_messages() {
+            interaction.chats.iter().any(|message| message.sender == Sender::Them) && interaction.chats.iter().any(|message| message.sender == Sender::Me)
+        } else {
+            interaction.chats.len() >= 2
+        }
+    }
+
+    // True when any message carries the sender, otherwise only your side of every chat is known
+    pub fn has_received_messages(&self) -> bool {
+        self.received_messages
+    }
+}
+
+// Contents of one file of the export, read out of the ZIP or from the directory matches.json is in. None when the
+// file is not part of the export.
+fn read_file(path: &Path, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let mut contents = String::new();
+
+    if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("zip")) {
+        let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+        let Some(entry) = archive
+            .file_names()
+            .filter_map(Result::ok)
+            .find(|entry| entry.rsplit('/').next() == Some(name))
+            .map(|entry| entry.to_string())
+        else {
+            return Ok(None);
+        };
+        let entry = archive.by_name(&entry)?;
+        let bar = progress::bytes(entry.size(), "Extracting export");
+        bar.wrap_read(entry).read_to_string(&mut contents)?;
+        bar.finish_and_clear();
+    } else {
+        let path = if path.file_name().is_some_and(|file_name| file_name == name) { path.to_path_buf() } else { path.with_file_name(name) };
+        if !path.exists() {
+            return Ok(None);
+        }
+        File::open(path)?.read_to_string(&mut contents)?;
+    }
+
+    Ok(Some(contents))
+}
+
+// Reads matches.json on its own or out of the export ZIP, along with media.json and user.json when the export has them
+pub fn load(path: impl AsRef<Path>, utc_offset: i32) -> Result<Export, Box<dyn Error>> {
+    let path = path.as_ref();
+    let contents = if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("zip")) {
+        read_file(path, "matches.json")?.ok_or_else(|| format!("No matches.json in {}", path.display()))?
+    } else {
+        let mut contents = String::new();
+        File::open(path)?.read_to_string(&mut contents)?;
+        contents
+    };
+
+    let spinner = progress::spinner("Parsing matches.json");
+    let interactions = serde_json::from_str::<Vec<Interaction>>(&contents);
+    spinner.finish_and_clear();
+    let interactions = interactions?;
+    let received_messages = interactions.iter().flat_map(|interaction| &interaction.chats).any(|message| message.sender == Sender::Them);
+    let media = match read_file(path, "media.json")? {
+        Some(contents) => serde_json::from_str(&contents)?,
+        None => Vec::new()
+    };
+    let user = read_file(path, "user.json")?.map(|contents| serde_json::from_str(&contents)).transpose()?;
+
+    Ok(Export { interactions, media, user, utc_offset, received_messages })
+}
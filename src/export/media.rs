@@ -0,0 +1,43 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    Photo,
+    Video,
+    #[serde(other)]
+    Other
+}
+
+// One entry of media.json, the photos and videos on your profile in the order they are shown
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct Media {
+    #[serde(rename = "type")]
+    pub kind: MediaKind,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub caption: Option<String>,
+    #[serde(default)]
+    pub from_social_media: Option<bool>
+}
+
+impl Media {
+    // How a received like refers to this media, e.g. Photo 2 for the second photo
+    pub fn label(&self, position: usize) -> String {
+        format!("{} {}", if self.kind == MediaKind::Video { "Video" } else { "Photo" }, position)
+    }
+}
+
+// The position of the media a received like was on among the media of its kind, matched by URL or a label like Photo 3
+pub fn find(media: &[Media], content: &str) -> Option<usize> {
+    let content = content.trim();
+    media.iter().position(|item| item.url.as_deref() == Some(content)).or_else(|| {
+        let mut positions = [0; 2];
+        media.iter().position(|item| {
+            let position = &mut positions[(item.kind == MediaKind::Video) as usize];
+            *position += 1;
+            item.label(*position).eq_ignore_ascii_case(content)
+        })
+    })
+}
@@ -0,0 +1,44 @@
+// Your own profile as written in user.json, heights are in centimeters like the rest of the Hinge export
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub age: Option<u8>,
+    #[serde(default)]
+    pub height_centimeters: Option<f64>,
+    #[serde(default)]
+    pub ethnicities: Vec<String>,
+    #[serde(default)]
+    pub religions: Vec<String>
+}
+
+// The filters you set on who Hinge shows you, empty lists are open to everyone
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct Preferences {
+    #[serde(default)]
+    pub age_min: Option<u8>,
+    #[serde(default)]
+    pub age_max: Option<u8>,
+    #[serde(default)]
+    pub height_min: Option<f64>,
+    #[serde(default)]
+    pub height_max: Option<f64>,
+    #[serde(default)]
+    pub distance_miles_max: Option<f64>,
+    #[serde(default)]
+    pub ethnicity_preference: Vec<String>,
+    #[serde(default)]
+    pub religion_preference: Vec<String>
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct User {
+    #[serde(default)]
+    pub profile: Profile,
+    #[serde(default)]
+    pub preferences: Preferences
+}
+
+// Hinge writes "Open to All" instead of leaving a preference empty
+pub fn open_to_all(preference: &[String]) -> bool {
+    preference.is_empty() || preference.iter().any(|value| value.eq_ignore_ascii_case("open to all"))
+}
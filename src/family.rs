@@ -0,0 +1,41 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum FamilyPlans {
+    WantsKids,
+    DoesNotWantKids,
+    OpenToKids,
+    NotSure
+}
+
+impl FamilyPlans {
+    pub fn label(self) -> &'static str {
+        match self {
+            FamilyPlans::WantsKids => "Wants Children",
+            FamilyPlans::DoesNotWantKids => "Doesn't Want Children",
+            FamilyPlans::OpenToKids => "Open to Children",
+            FamilyPlans::NotSure => "Not Sure Yet"
+        }
+    }
+
+    // Accepts the Hinge labels and yes/no/open/unsure shorthands, Prefer Not to Say is treated as no answer
+    pub fn parse(value: Option<&str>) -> Result<Option<Self>, &'static str> {
+        let Some(value) = value.map(|value| value.trim().to_ascii_lowercase().replace('\'', "")).filter(|value| !value.is_empty()) else {
+            return Ok(None);
+        };
+
+        match value.as_str() {
+            "want children" | "wants children" | "wants kids" | "yes" => Ok(Some(FamilyPlans::WantsKids)),
+            "dont want children" | "doesnt want children" | "doesnt want kids" | "no" => Ok(Some(FamilyPlans::DoesNotWantKids)),
+            "open to children" | "open to kids" | "open" => Ok(Some(FamilyPlans::OpenToKids)),
+            "not sure yet" | "not sure" | "unsure" | "maybe" => Ok(Some(FamilyPlans::NotSure)),
+            "prefer not to say" => Ok(None),
+            _ => Err("Invalid value for Wants Kids")
+        }
+    }
+}
+
+impl std::fmt::Display for FamilyPlans {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())?;
+        Ok(())
+    }
+}
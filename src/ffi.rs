@@ -0,0 +1,96 @@
+use std::{
+    error::Error,
+    ffi::{c_char, CStr, CString},
+    panic, ptr, slice
+};
+
+use clap::Parser;
+
+use crate::{build_report_of, config, ingest, narrow_profiles, profile_from_record, progress, report, resolve_baselines, schema, vocabulary, warning, Args, HingeProfileCSVRecord};
+
+// What stands in for the path of the matches, which the library never reads from a file
+const IN_MEMORY: &str = "<memory>";
+
+// The report with the rows of the CSV that were rejected, which the command line would have quarantined to
+// rejected_rows.csv, and the warnings it would have printed
+#[derive(Debug, serde::Serialize)]
+struct Analysis {
+    #[serde(flatten)]
+    report: report::Report,
+    rejected_rows: Vec<ingest::Rejection>,
+    warnings: Vec<String>
+}
+
+// The report of the matches with the options applied like the config file's. The matches are read straight out of
+// the caller's buffer and the options only out of `options`, nothing is printed and nothing of one call is kept for
+// the next.
+fn analyze(csv: &[u8], options: &str) -> Result<String, Box<dyn Error>> {
+    let options: config::Config = toml::from_str(options).map_err(|err| format!("Could not parse the options: {}", err))?;
+    let mut arguments = vec![std::ffi::OsString::from("hinge-analyzer")];
+    arguments.extend(options.own_arguments()?);
+    let mut args = Args::try_parse_from(arguments)?;
+    if !args.counties.is_empty() || args.zip.is_some() {
+        return Err("The library doesn't download census baselines, write them with the census command and pass them as baseline and hispanic-baseline".into());
+    }
+    // the bundled baselines are read from files, written here and removed when the call returns
+    let cache = args.offline.then(|| tempfile::Builder::new().prefix("hinge-analyzer-").tempdir()).transpose()?;
+    if let Some(cache) = &cache {
+        args.cache_dir = Some(cache.path().to_path_buf());
+    }
+    // nothing of the analysis is printed, the caller gets everything in the report
+    args.summary = true;
+
+    let (analysis, warnings) = warning::collected(|| -> Result<_, Box<dyn Error>> {
+        let args = resolve_baselines(args)?;
+        let vocabulary = vocabulary::load(&args.last_reply_vocabulary)?;
+        let (profiles, rejected_rows) = ingest::ingest_bytes::<HingeProfileCSVRecord, _>(csv, &schema(&args), |record| profile_from_record(record, args.strictness, &vocabulary))?;
+        let report = progress::hidden(|| build_report_of(&args, narrow_profiles(profiles, &args)))?;
+        Ok((report, rejected_rows))
+    });
+    let (mut report, rejected_rows) = analysis?;
+    report.matches_file = IN_MEMORY.to_string();
+    report.provenance.inputs.insert(String::from("matches"), report::Input::of_bytes(IN_MEMORY, csv));
+    Ok(serde_json::to_string(&Analysis { report, rejected_rows, warnings })?)
+}
+
+/// Analyzes a matches.csv held in memory and returns the report as JSON, the same as the daemon's snapshots plus
+/// `rejected_rows` with the line, reason and text of every row that could not be read and `warnings` with what the
+/// command line would have printed about the baselines, or an object with an error when the analysis fails. Options
+/// are TOML in the format of hinge-analyzer.toml, e.g. `bayesian = true`, or NULL for the defaults. Relative baseline
+/// paths are resolved against the working directory of the process, so pass absolute ones or `offline = true` with
+/// the bundled baselines built in.
+///
+/// Nothing is printed and the HINGE_ANALYZER_* environment variables of the process are not read. Census baselines
+/// (`counties` or `zip`) are refused rather than downloaded. With `offline = true` the bundled baselines are
+/// decompressed into a temporary directory that is removed before the call returns.
+///
+/// # Safety
+///
+/// `csv` must point to `length` readable bytes and `options` must be NULL or a NUL-terminated UTF-8 string. The
+/// returned string belongs to the caller and must be released with hinge_analyzer_free.
+#[no_mangle]
+pub unsafe extern "C" fn hinge_analyzer_analyze(csv: *const u8, length: usize, options: *const c_char) -> *mut c_char {
+    let csv = if csv.is_null() { &[][..] } else { slice::from_raw_parts(csv, length) };
+    let options = if options.is_null() { Ok("") } else { CStr::from_ptr(options).to_str() };
+    let json = match options {
+        Ok(options) => match panic::catch_unwind(|| analyze(csv, options).map_err(|err| err.to_string())) {
+            Ok(Ok(json)) => json,
+            Ok(Err(err)) => serde_json::json!({ "error": err }).to_string(),
+            Err(_) => serde_json::json!({ "error": "The analysis panicked" }).to_string()
+        },
+        Err(err) => serde_json::json!({ "error": format!("The options are not UTF-8: {}", err) }).to_string()
+    };
+    CString::new(json).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Releases a string returned by hinge_analyzer_analyze, NULL is ignored.
+///
+/// # Safety
+///
+/// `json` must be NULL or a string returned by hinge_analyzer_analyze that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn hinge_analyzer_free(json: *mut c_char) {
+    if !json.is_null() {
+        drop(CString::from_raw(json));
+    }
+}
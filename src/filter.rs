@@ -0,0 +1,352 @@
+use crate::{attribute, height, HingeProfile};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual
+}
+
+impl Operator {
+    fn symbol(self) -> &'static str {
+        match self {
+            Operator::Equal => "=",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::LessEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterEqual => ">="
+        }
+    }
+}
+
+// Boolean expression over profile attributes, e.g. `convo and (race = Asian or age >= 30) and not drinking = No`.
+// A bare attribute is true when the profile has a value for it that is not false. A chain of ands or ors is one node
+// however long it is, so only parentheses and nots nest.
+#[derive(Debug, Clone)]
+pub enum Filter {
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+    Present(String),
+    Compare {
+        attribute: String,
+        operator: Operator,
+        value: String
+    }
+}
+
+// yes/no style literals compare equal to boolean attributes
+fn normalize_boolean(value: &str) -> &str {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" => "true",
+        "false" | "no" | "n" | "0" => "false",
+        _ => value
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, profile: &HingeProfile) -> bool {
+        match self {
+            Filter::And(filters) => filters.iter().all(|filter| filter.matches(profile)),
+            Filter::Or(filters) => filters.iter().any(|filter| filter.matches(profile)),
+            Filter::Not(filter) => !filter.matches(profile),
+            Filter::Present(attribute) => attribute::value(profile, attribute).is_some_and(|value| value != "false"),
+            Filter::Compare { attribute, operator, value: expected } => {
+                // profiles without a value never match a comparison, so `not` is needed to keep them
+                let Some(actual) = attribute::value(profile, attribute) else {
+                    return false;
+                };
+
+                let expected = if attribute == "height" {
+                    height::parse_height(expected).map_or(expected.clone(), |inches| inches.to_string())
+                } else if actual == "true" || actual == "false" {
+                    normalize_boolean(expected).to_string()
+                } else {
+                    expected.clone()
+                };
+
+                let ordering = match (actual.parse::<f64>(), expected.parse::<f64>()) {
+                    (Ok(actual), Ok(expected)) => actual.partial_cmp(&expected),
+                    _ => match operator {
+                        Operator::Equal | Operator::NotEqual => Some(actual.to_lowercase().cmp(&expected.to_lowercase())),
+                        // ordering text values is almost always a mistake
+                        _ => None
+                    }
+                };
+
+                ordering.is_some_and(|ordering| match operator {
+                    Operator::Equal => ordering.is_eq(),
+                    Operator::NotEqual => ordering.is_ne(),
+                    Operator::Less => ordering.is_lt(),
+                    Operator::LessEqual => ordering.is_le(),
+                    Operator::Greater => ordering.is_gt(),
+                    Operator::GreaterEqual => ordering.is_ge()
+                })
+            }
+        }
+    }
+}
+
+// Values the tokenizer would split or read as a keyword are written quoted so the filter parses back the same
+fn needs_quotes(value: &str) -> bool {
+    value.is_empty()
+        || value.contains(|c: char| c.is_whitespace() || SYMBOLS.contains(c))
+        || ["and", "or", "not"].contains(&value.to_ascii_lowercase().as_str())
+}
+
+impl std::fmt::Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Filter::And(filters) => write!(f, "({})", filters.iter().map(ToString::to_string).collect::<Vec<_>>().join(" and "))?,
+            Filter::Or(filters) => write!(f, "({})", filters.iter().map(ToString::to_string).collect::<Vec<_>>().join(" or "))?,
+            Filter::Not(filter) => write!(f, "not {}", filter)?,
+            Filter::Present(attribute) => write!(f, "{}", attribute)?,
+            Filter::Compare { attribute, operator, value } => if needs_quotes(value) {
+                write!(f, "{} {} \"{}\"", attribute, operator.symbol(), value)?
+            } else {
+                write!(f, "{} {} {}", attribute, operator.symbol(), value)?
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Operator(Operator),
+    And,
+    Or,
+    Not,
+    Open,
+    Close
+}
+
+// Characters that end a word
+const SYMBOLS: &str = "()\"=!<>&|";
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            },
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            },
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => value.push(c),
+                        None => return Err(String::from("Unterminated quote in filter"))
+                    }
+                }
+                tokens.push(Token::Quoted(value));
+            },
+            '=' | '!' | '<' | '>' | '&' | '|' => {
+                chars.next();
+                let doubled = chars.next_if_eq(&c).is_some();
+                let equals = !doubled && chars.next_if_eq(&'=').is_some();
+                tokens.push(match (c, doubled, equals) {
+                    ('=', _, _) => Token::Operator(Operator::Equal),
+                    ('!', false, true) => Token::Operator(Operator::NotEqual),
+                    ('!', false, false) => Token::Not,
+                    ('<', false, true) => Token::Operator(Operator::LessEqual),
+                    ('<', false, false) => Token::Operator(Operator::Less),
+                    ('>', false, true) => Token::Operator(Operator::GreaterEqual),
+                    ('>', false, false) => Token::Operator(Operator::Greater),
+                    ('&', true, _) => Token::And,
+                    ('|', true, _) => Token::Or,
+                    _ => return Err(format!("Unexpected {} in filter", c))
+                });
+            },
+            _ => {
+                let mut word = String::new();
+                while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && !SYMBOLS.contains(c)) {
+                    word.push(c);
+                }
+                tokens.push(match word.to_ascii_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Word(word)
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Parentheses and nots a filter may nest, the parser recurses for each so deeper ones would overflow the stack
+const MAX_DEPTH: usize = 64;
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    // The operands of a chain of `operator`, a chain of one is the operand itself
+    fn chain(&mut self, operator: Token, operand: fn(&mut Self) -> Result<Filter, String>, node: fn(Vec<Filter>) -> Filter) -> Result<Filter, String> {
+        let mut filters = vec![operand(self)?];
+        while self.peek() == Some(&operator) {
+            self.next();
+            filters.push(operand(self)?);
+        }
+        Ok(if filters.len() == 1 { filters.remove(0) } else { node(filters) })
+    }
+
+    fn or(&mut self) -> Result<Filter, String> {
+        self.chain(Token::Or, Self::and, Filter::Or)
+    }
+
+    fn and(&mut self) -> Result<Filter, String> {
+        self.chain(Token::And, Self::unary, Filter::And)
+    }
+
+    // Parses what follows a not or an opening parenthesis one level deeper
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("Filter nests deeper than {} levels", MAX_DEPTH));
+        }
+        self.depth += 1;
+        let parsed = parse(self);
+        self.depth -= 1;
+        parsed
+    }
+
+    fn unary(&mut self) -> Result<Filter, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Filter::Not(Box::new(self.nested(Self::unary)?))),
+            Some(Token::Open) => {
+                let filter = self.nested(Self::or)?;
+                match self.next() {
+                    Some(Token::Close) => Ok(filter),
+                    _ => Err(String::from("Missing ) in filter"))
+                }
+            },
+            Some(Token::Word(attribute)) => {
+                let attribute = attribute.to_ascii_lowercase();
+                if !attribute::FIELDS.contains(&attribute.as_str()) {
+                    return Err(format!("Unknown attribute {} in filter, expected one of {}", attribute, attribute::FIELDS.join(", ")));
+                }
+
+                let Some(&Token::Operator(operator)) = self.peek() else {
+                    return Ok(Filter::Present(attribute));
+                };
+                self.next();
+
+                match self.next() {
+                    Some(Token::Word(value) | Token::Quoted(value)) => Ok(Filter::Compare { attribute, operator, value }),
+                    _ => Err(format!("Missing value after {} {} in filter", attribute, operator.symbol()))
+                }
+            },
+            Some(token) => Err(format!("Unexpected {:?} in filter", token)),
+            None => Err(String::from("Filter ended early"))
+        }
+    }
+}
+
+impl std::str::FromStr for Filter {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { tokens: tokenize(input)?, position: 0, depth: 0 };
+        let filter = parser.or()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(format!("Unexpected {:?} in filter", token))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reparsed(input: &str) -> String {
+        let filter = input.parse::<Filter>().unwrap();
+        let written = filter.to_string();
+        assert_eq!(written.parse::<Filter>().unwrap().to_string(), written, "{} did not parse back", written);
+        written
+    }
+
+    #[test]
+    fn display_parses_back() {
+        assert_eq!(reparsed("convo and (race = Asian or age >= 30) and not drinking = No"), "(convo and (race = Asian or age >= 30) and not drinking = No)");
+        assert_eq!(reparsed("convo or matched and age > 30 or specified"), "(convo or (matched and age > 30) or specified)");
+        assert_eq!(reparsed("job != \"software engineer\" || !smoking"), "(job != \"software engineer\" or not smoking)");
+        assert_eq!(reparsed("height <= 5'10 && age < 40"), "(height <= 5'10 and age < 40)");
+        assert_eq!(reparsed("not not (matched)"), "not not matched");
+    }
+
+    #[test]
+    fn display_quotes_values_the_tokenizer_would_split() {
+        assert_eq!(reparsed("job = \"a<b\""), "job = \"a<b\"");
+        assert_eq!(reparsed("job = \"\""), "job = \"\"");
+        assert_eq!(reparsed("job = \"and\""), "job = \"and\"");
+        assert_eq!(reparsed("neighborhood = \"(north)\""), "neighborhood = \"(north)\"");
+    }
+
+    #[test]
+    fn attributes_and_keywords_ignore_case() {
+        assert_eq!(reparsed("AGE > 25 AND Convo"), "(age > 25 and convo)");
+    }
+
+    #[test]
+    fn nesting_is_capped() {
+        let nested = |depth: usize| format!("{}convo{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(nested(MAX_DEPTH).parse::<Filter>().is_ok());
+        assert_eq!(nested(MAX_DEPTH + 1).parse::<Filter>().unwrap_err(), format!("Filter nests deeper than {} levels", MAX_DEPTH));
+        assert!(format!("{}convo", "not ".repeat(MAX_DEPTH + 1)).parse::<Filter>().is_err());
+        assert!(format!("{}convo", "not ".repeat(MAX_DEPTH)).parse::<Filter>().is_ok());
+    }
+
+    #[test]
+    fn long_chains_stay_flat() {
+        let chain = vec!["convo"; 100_000].join(" and ");
+        let filter = chain.parse::<Filter>().unwrap();
+        assert!(matches!(&filter, Filter::And(filters) if filters.len() == 100_000));
+        assert_eq!(filter.to_string(), format!("({})", chain));
+
+        let alternating = vec!["convo and matched"; 100_000].join(" or ");
+        assert_eq!(reparsed(&alternating), format!("({})", vec!["(convo and matched)"; 100_000].join(" or ")));
+    }
+
+    #[test]
+    fn malformed_filters_are_errors() {
+        let error = |input: &str| input.parse::<Filter>().unwrap_err();
+        assert!(error("colour = red").starts_with("Unknown attribute colour in filter"));
+        assert_eq!(error("job = \"unterminated"), "Unterminated quote in filter");
+        assert_eq!(error("(convo"), "Missing ) in filter");
+        assert_eq!(error("age >"), "Missing value after age > in filter");
+        assert_eq!(error("convo and"), "Filter ended early");
+        assert_eq!(error(""), "Filter ended early");
+        assert_eq!(error("convo & matched"), "Unexpected & in filter");
+        assert_eq!(error("convo matched"), "Unexpected Word(\"matched\") in filter");
+    }
+}
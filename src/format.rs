@@ -0,0 +1,119 @@
+use std::sync::OnceLock;
+
+// How numbers are written by every table and summary, set once from the command line before anything is printed
+static FORMAT: OnceLock<Format> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum RateStyle {
+    /// Rates as percents, e.g. 12.500 %
+    #[default]
+    Percent,
+    /// Rates as ratios between 0 and 1, e.g. 0.12500
+    Ratio
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Format {
+    // decimal places of every rate and metric in place of the places each table picks for itself
+    pub decimals: Option<usize>,
+    pub thousands_separator: Option<char>,
+    pub rate_style: RateStyle,
+    // write the arithmetic behind every rate under the line reporting it
+    pub explain: bool
+}
+
+// A rate with the counts it is computed from, so the arithmetic can be shown next to it
+#[derive(Debug, Clone, Copy)]
+pub struct Rate {
+    pub name: &'static str,
+    pub count: usize,
+    pub count_label: &'static str,
+    pub total: usize,
+    pub total_label: &'static str
+}
+
+impl Rate {
+    pub fn new(name: &'static str, count: usize, count_label: &'static str, total: usize, total_label: &'static str) -> Rate {
+        Rate { name, count, count_label, total, total_label }
+    }
+
+    pub fn value(&self) -> f64 {
+        self.count as f64 / self.total.max(1) as f64
+    }
+}
+
+impl std::fmt::Display for Rate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {} {} / {} {} = {}", self.name, count(self.count), self.count_label, count(self.total), self.total_label, inline_percent(self.value(), 2))?;
+        Ok(())
+    }
+}
+
+pub fn set(format: Format) {
+    FORMAT.set(format).expect("Number format set twice");
+}
+
+fn format() -> Format {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+// Digits of the whole part of a number split into thousands
+fn group(digits: &str, separator: Option<char>) -> String {
+    let Some(separator) = separator else {
+        return digits.to_string();
+    };
+
+    let (sign, digits) = digits.split_at(digits.len() - digits.trim_start_matches(['-', '+']).len());
+    let mut grouped = String::from(sign);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+// A count like the number of matches or messages
+pub fn count(value: impl std::fmt::Display) -> String {
+    group(&value.to_string(), format().thousands_separator)
+}
+
+// A metric that is not a rate, like a mean, with the places of the table unless overridden
+pub fn decimal(value: f64, places: usize) -> String {
+    let places = format().decimals.unwrap_or(places);
+    let value = format!("{:.*}", places, value);
+    match value.split_once('.') {
+        Some((whole, fraction)) => format!("{}.{}", group(whole, format().thousands_separator), fraction),
+        None => group(&value, format().thousands_separator)
+    }
+}
+
+// A rate between 0 and 1 in a table column, a zero padded percent like 05.250 % or a ratio of the same width
+pub fn percent(rate: f64) -> String {
+    let places = format().decimals.unwrap_or(3);
+    // two whole digits and the decimal point when there are places
+    let width = places + 2 + (places > 0) as usize;
+    match format().rate_style {
+        RateStyle::Percent => format!("{:0width$.places$} %", rate * 100.0),
+        RateStyle::Ratio => format!("{:<ratio_width$.ratio_places$}", rate, ratio_width = width + 2, ratio_places = places + 2)
+    }
+}
+
+// A rate between 0 and 1 within a sentence, with the places of the sentence unless overridden
+pub fn inline_percent(rate: f64, places: usize) -> String {
+    let places = format().decimals.unwrap_or(places);
+    match format().rate_style {
+        RateStyle::Percent => format!("{:.*}%", places, rate * 100.0),
+        RateStyle::Ratio => format!("{:.*}", places + 2, rate)
+    }
+}
+
+// The arithmetic of each rate under the table row or sentence reporting it, only with --explain
+pub fn explain(rates: &[Rate]) {
+    if format().explain {
+        for rate in rates {
+            println!("\t    {}", rate);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{filter::Filter, format, stats, HingeProfile, WhoLastReplied};
+
+// Stages a conversation can reach in order, one of them is Met which the last_reply column already records. The
+// stage column of a profile names the furthest stage it reached.
+#[derive(Debug, Clone)]
+pub struct Funnel {
+    stages: Vec<String>
+}
+
+impl Funnel {
+    pub fn new(stages: &[String]) -> Result<Self, String> {
+        let stages = stages.iter().map(|stage| stage.trim().to_string()).filter(|stage| !stage.is_empty()).collect::<Vec<_>>();
+        if !stages.iter().any(|stage| stage.eq_ignore_ascii_case("met")) {
+            return Err(format!("Funnel stages {} need to include Met", stages.join(", ")));
+        }
+        if let Some(stage) = stages.iter().enumerate().find(|(i, stage)| stages[..*i].iter().any(|other| other.eq_ignore_ascii_case(stage))) {
+            return Err(format!("Funnel stage {} is listed twice", stage.1));
+        }
+        Ok(Funnel { stages })
+    }
+
+    // True when there are stages beyond the built in Met
+    pub fn is_custom(&self) -> bool {
+        self.stages.len() > 1
+    }
+
+    fn position(&self, stage: &str) -> Option<usize> {
+        self.stages.iter().position(|other| other.eq_ignore_ascii_case(stage.trim()))
+    }
+
+    // Number of stages a profile reached. A profile that met without a stage reached every stage up to Met.
+    fn reached(&self, profile: &HingeProfile) -> usize {
+        if !profile.convo {
+            return 0;
+        }
+
+        let met = if profile.who_last_replied == WhoLastReplied::Met { self.position("met").map_or(0, |met| met + 1) } else { 0 };
+        let stage = profile.stage.as_deref().and_then(|stage| self.position(stage)).map_or(0, |stage| stage + 1);
+        met.max(stage)
+    }
+}
+
+// Profiles that reached each stage of the funnel starting from matches and conversations
+pub fn stage_counts<'a>(profiles: impl Iterator<Item = &'a HingeProfile> + Clone, funnel: &Funnel) -> Vec<(String, usize)> {
+    let reached = profiles.clone().map(|profile| funnel.reached(profile)).collect::<Vec<_>>();
+    let counts = [profiles.clone().count(), profiles.filter(|profile| profile.convo).count()]
+        .into_iter()
+        .chain((1..=funnel.stages.len()).map(|stage| reached.iter().filter(|reached| **reached >= stage).count()));
+    ["Matches", "Conversations"].into_iter().chain(funnel.stages.iter().map(String::as_str)).map(str::to_string).zip(counts).collect()
+}
+
+// How many profiles reached every stage of the funnel, as a share of the stage before and of all matches
+pub fn print_funnel<'a>(profiles: impl Iterator<Item = &'a HingeProfile> + Clone, funnel: &Funnel) {
+    let unknown = profiles
+        .clone()
+        .filter(|profile| profile.stage.as_deref().is_some_and(|stage| funnel.position(stage).is_none()))
+        .count();
+
+    let matches = profiles.clone().count();
+    let stages = stage_counts(profiles, funnel);
+    let counts = stages.iter().map(|(_, count)| *count).collect::<Vec<_>>();
+
+    println!("\n\t         Conversation Funnel ({} Matches)", matches);
+    println!("\t{:<25}   Profiles   Of Previous   Of Matches", "Stage");
+    let labels = stages.iter().map(|(label, _)| label);
+    for (i, (label, count)) in labels.zip(&counts).enumerate() {
+        let previous = if i == 0 { matches } else { counts[i - 1] };
+        println!(
+            "\t{:<25}   {:<8}   {}     {}",
+            label,
+            format::count(count),
+            format::percent(*count as f64 / previous.max(1) as f64),
+            format::percent(*count as f64 / matches.max(1) as f64)
+        );
+        format::explain(&[
+            format::Rate::new("of previous", *count, "profiles", previous, "at the previous stage"),
+            format::Rate::new("of matches", *count, "profiles", matches, "matches")
+        ]);
+    }
+
+    if unknown > 0 {
+        println!("\t{} profiles have a stage that is not one of {}, only their last_reply is counted", unknown, funnel.stages.join(", "));
+    }
+}
+
+// The funnel of the matches the scenario keeps next to the funnel of every match, each stage as a share of matches.
+// How much the behavior behind the scenario mattered is put as the final stage count had every match gone like the
+// scenario's, with a chi-square test of the scenario's matches against the rest reaching that stage.
+pub fn print_what_if<'a>(profiles: impl Iterator<Item = &'a HingeProfile> + Clone, scenario: &Filter, funnel: &Funnel) {
+    let kept = profiles.clone().filter(|profile| scenario.matches(profile));
+    let (actual, what_if) = (stage_counts(profiles.clone(), funnel), stage_counts(kept, funnel));
+    let (matches, kept_matches) = (actual[0].1, what_if[0].1);
+
+    println!("\n\t         What If: {}", scenario);
+    if kept_matches == 0 {
+        println!("\tNo matches fit the scenario, so there is nothing to compare");
+        return;
+    }
+    println!("\t{:<25}   {:<18}   {:<18}   Change", "Stage", "Actual", "What If");
+    for ((label, count), (_, kept_count)) in actual.iter().zip(&what_if) {
+        let (rate, kept_rate) = (*count as f64 / matches.max(1) as f64, *kept_count as f64 / kept_matches.max(1) as f64);
+        println!(
+            "\t{:<25}   {:<18}   {:<18}   {:+.1} points",
+            label,
+            format!("{} ({})", format::count(count), format::inline_percent(rate, 1)),
+            format!("{} ({})", format::count(kept_count), format::inline_percent(kept_rate, 1)),
+            (kept_rate - rate) * 100.0
+        );
+    }
+    let ((last, reached), (_, kept_reached)) = (actual[actual.len() - 1].clone(), what_if[what_if.len() - 1].clone());
+    let counterfactual = kept_reached as f64 / kept_matches as f64 * matches as f64;
+    println!(
+        "\n\tHad all {} matches gone like the {} that fit the scenario: {} {} instead of {} ({:+.1})",
+        format::count(matches),
+        format::count(kept_matches),
+        format::decimal(counterfactual, 1),
+        last,
+        format::count(reached),
+        counterfactual - reached as f64
+    );
+
+    let rest = (matches - kept_matches) as u32;
+    let rest_reached = (reached - kept_reached) as u32;
+    let table = [
+        vec![kept_reached as u32, (kept_matches - kept_reached) as u32],
+        vec![rest_reached, rest - rest_reached]
+    ];
+    match stats::independence_test(&table) {
+        Some(test) => println!("\tScenario against the other matches reaching {}: {}", last, test),
+        None => println!("\tThe scenario can't be tested against the other matches, one side has no matches or every match ended the same")
+    }
+}
@@ -0,0 +1,271 @@
+use std::{error::Error, io::Write, path::{Path, PathBuf}};
+
+use chrono::{Duration, NaiveDate};
+use rand::{Rng, RngExt};
+
+use crate::{baseline, height::HeightBaseline};
+
+const ETHNICITY_COLUMNS: usize = 10;
+// Columns of the race baseline in the order of the groups drawn by ethnicity()
+const RACE_COLUMNS: [&str; 8] = [
+    "white_alone", "black_african_american_alone", "american_indian_alaska_native_alone", "asian_alone",
+    "native_hawaiian_pacific_islander_alone", "some_other_race_alone", "two_or_more_races", "hispanic_latino"
+];
+const ASIAN_COLUMNS: [usize; 3] = [1, 3, 7];
+
+// Column of each ethnicity in the ethnicity flags of GeneratedRecord
+const NATIVE_AMERICAN: usize = 0;
+const BLACK_AFRICAN_DESCENT: usize = 2;
+const HISPANIC_LATINO: usize = 4;
+const PACIFIC_ISLANDER: usize = 6;
+const WHITE_CAUCASIAN: usize = 8;
+const OTHER: usize = 9;
+
+const RELIGIONS: [&str; 3] = ["Agnostic", "Atheist", "Spiritual"];
+const JOBS: [&str; 12] = [
+    "Software Engineer", "Nurse", "Teacher", "Financial Analyst", "Attorney", "Graphic Designer", "Marketing Manager",
+    "Bartender", "Research Scientist", "Physician", "Consultant", "Student"
+];
+const POLITICS: [&str; 4] = ["Liberal", "Moderate", "Conservative", "Not Political"];
+const HABITS: [&str; 3] = ["Yes", "Sometimes", "No"];
+const FAMILY_PLANS: [&str; 4] = ["Want children", "Don't want children", "Open to children", "Not sure yet"];
+const NEIGHBORHOODS: [&str; 8] = ["Lincoln Park", "Wicker Park", "Logan Square", "Lakeview", "West Loop", "Hyde Park", "Pilsen", "Evanston"];
+const WORDS: [&str; 16] = [
+    "hey", "how", "was", "your", "weekend", "haha", "that", "sounds", "fun", "coffee", "hiking", "favorite", "place", "drinks",
+    "tacos", "music"
+];
+
+// Chance and shape settings of the synthetic matches
+#[derive(Debug, Clone)]
+pub struct Distributions {
+    pub conversation_rate: f64,
+    pub date_rate: f64,
+    pub missing_rate: f64,
+    pub mean_age: f64,
+    pub mean_distance_miles: f64,
+    pub height_baseline: HeightBaseline,
+    // county race counts in the layout of demographics.csv
+    pub race_baseline: PathBuf,
+    pub baseline_units: baseline::Units
+}
+
+#[derive(Debug, serde::Serialize)]
+struct GeneratedRecord {
+    name: String,
+    matched: u8,
+    convo: u8,
+    last_reply: &'static str,
+    specified: u8,
+    native_american: u8,
+    southeast_asian: u8,
+    black_african_descent: u8,
+    east_asian: u8,
+    hispanic_latino: u8,
+    middle_eastern: u8,
+    pacific_islander: u8,
+    south_asian: u8,
+    white_caucasian: u8,
+    other: u8,
+    age: Option<u8>,
+    height: Option<u8>,
+    religion: Option<String>,
+    education: Option<String>,
+    job: Option<&'static str>,
+    politics: Option<&'static str>,
+    drinking: Option<&'static str>,
+    smoking: Option<&'static str>,
+    marijuana: Option<&'static str>,
+    drugs: Option<&'static str>,
+    wants_kids: Option<&'static str>,
+    distance_miles: Option<f64>,
+    neighborhood: Option<&'static str>
+}
+
+// Index drawn with probability proportional to its weight
+fn weighted(rng: &mut impl Rng, weights: &[f64]) -> usize {
+    let mut draw = rng.random::<f64>() * weights.iter().sum::<f64>();
+    for (i, weight) in weights.iter().enumerate() {
+        if draw < *weight {
+            return i;
+        }
+        draw -= weight;
+    }
+    weights.len() - 1
+}
+
+// Standard normal draw by the Box-Muller transform
+fn normal(rng: &mut impl Rng) -> f64 {
+    let u = 1.0 - rng.random::<f64>();
+    let v = rng.random::<f64>();
+    (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+}
+
+fn pick<T: Copy>(rng: &mut impl Rng, values: &[T]) -> T {
+    values[rng.random_range(0..values.len())]
+}
+
+// Ethnicity flags drawn from the census race counts of the race baseline, two or more races sets two flags
+fn ethnicity_weights(path: impl AsRef<Path>, units: baseline::Units) -> Result<[f64; 8], Box<dyn Error>> {
+    let path = path.as_ref();
+    let populations = baseline::read_columns(path, units)?;
+    let mut weights = [0.0; 8];
+    for (weight, name) in weights.iter_mut().zip(RACE_COLUMNS) {
+        *weight = populations
+            .iter()
+            .find(|(column, _)| column == name)
+            .map(|(_, population)| *population)
+            .ok_or_else(|| format!("No {} column in {}", name, path.display()))?;
+    }
+    Ok(weights)
+}
+
+fn ethnicity(rng: &mut impl Rng, weights: &[f64; 8]) -> [u8; ETHNICITY_COLUMNS] {
+    let single = |rng: &mut _, group: usize| match group {
+        0 => WHITE_CAUCASIAN,
+        1 => BLACK_AFRICAN_DESCENT,
+        2 => NATIVE_AMERICAN,
+        3 => pick(rng, &ASIAN_COLUMNS),
+        4 => PACIFIC_ISLANDER,
+        5 => OTHER,
+        _ => HISPANIC_LATINO
+    };
+
+    let mut flags = [0; ETHNICITY_COLUMNS];
+    match weighted(rng, weights) {
+        6 => {
+            // two or more races, two different single race groups
+            let first = weighted(rng, &weights[..6]);
+            let second = (first + 1 + rng.random_range(0..5)) % 6;
+            flags[single(rng, first)] = 1;
+            flags[single(rng, second)] = 1;
+        },
+        group => flags[single(rng, group)] = 1
+    }
+    flags
+}
+
+// Whether an optional column gets a value, blanks show up at the missing rate
+fn present(rng: &mut impl Rng, distributions: &Distributions) -> bool {
+    rng.random::<f64>() >= distributions.missing_rate
+}
+
+fn shares(baseline: &[(String, f64)]) -> Vec<f64> {
+    baseline.iter().map(|(_, share)| *share).collect()
+}
+
+// A made up Hinge export entry whose chats and We Met answer agree with the outcome of the generated match
+fn interaction(rng: &mut impl Rng, record: &GeneratedRecord, start: NaiveDate) -> serde_json::Value {
+    let timestamp = |at: chrono::NaiveDateTime| at.format("%Y-%m-%d %H:%M:%S").to_string();
+    let matched_at = start.and_hms_opt(0, 0, 0).expect("Bad start time") + Duration::minutes(rng.random_range(0..365 * 24 * 60));
+
+    let messages = match (record.convo, record.last_reply) {
+        (1, _) => rng.random_range(4..30),
+        (_, "None") => 0,
+        _ => 1
+    };
+    // the last message is from whoever replied last, so count back from it
+    let last_sender = if record.last_reply == "Them" { "them" } else { "me" };
+    let mut at = matched_at + Duration::minutes(rng.random_range(10..48 * 60));
+    let chats = (0..messages)
+        .map(|i| {
+            let sender = if (messages - 1 - i) % 2 == 0 { last_sender } else if last_sender == "me" { "them" } else { "me" };
+            let body = (0..rng.random_range(1..20)).map(|_| pick(rng, &WORDS)).collect::<Vec<_>>().join(" ");
+            let message = serde_json::json!({ "body": body, "timestamp": timestamp(at), "sender": sender });
+            at += Duration::minutes(rng.random_range(5..24 * 60));
+            message
+        })
+        .collect::<Vec<_>>();
+
+    let mut interaction = serde_json::json!({
+        "like": [{ "timestamp": timestamp(matched_at - Duration::hours(rng.random_range(1..72))) }],
+        "match": [{ "timestamp": timestamp(matched_at) }],
+        "chats": chats
+    });
+    if record.last_reply == "Met" {
+        interaction["we_met"] = serde_json::json!([{
+            "timestamp": timestamp(at + Duration::days(rng.random_range(1..10))),
+            "did_meet_subject": "Yes",
+            "was_my_type": rng.random::<f64>() < 0.5
+        }]);
+    }
+    interaction
+}
+
+// Synthetic matches.csv rows, and optionally a matching matches.json export. Races follow demographics.csv,
+// religion and education follow their baselines for the geography and heights follow the height baseline, everything
+// else is drawn uniformly. Matches closer by are a little more likely to become a date.
+pub fn generate(
+    profiles: usize,
+    distributions: &Distributions,
+    religion_baseline: &[(String, f64)],
+    education_baseline: &[(String, f64)],
+    rng: &mut impl Rng,
+    output: impl Write,
+    chats: Option<impl Write>
+) -> Result<(), Box<dyn Error>> {
+    let ethnicity_weights = ethnicity_weights(&distributions.race_baseline, distributions.baseline_units)?;
+    let (height_mean, height_standard_deviation) = distributions.height_baseline.parameters();
+    let religions = religion_baseline.iter().map(|(religion, _)| religion.as_str()).chain(RELIGIONS).collect::<Vec<_>>();
+    let religion_shares = shares(religion_baseline).into_iter().chain(RELIGIONS.map(|_| 0.01)).collect::<Vec<_>>();
+    let start = NaiveDate::from_ymd_opt(2023, 1, 1).expect("Bad start date");
+
+    let mut writer = csv::Writer::from_writer(output);
+    let mut interactions = Vec::new();
+    for i in 0..profiles {
+        let flags = ethnicity(rng, &ethnicity_weights);
+        let distance_miles = (-distributions.mean_distance_miles * (1.0 - rng.random::<f64>()).ln() * 10.0).round() / 10.0;
+
+        let convo = rng.random::<f64>() < distributions.conversation_rate;
+        // dates fall off with distance, at the mean distance the date rate is as configured
+        let date_chance = distributions.date_rate * (1.0 - distance_miles / distributions.mean_distance_miles).exp().min(2.0);
+        let last_reply = match (convo, rng.random::<f64>()) {
+            (true, draw) if draw < date_chance => "Met",
+            (false, draw) if draw < 0.05 => "None",
+            (_, _) => pick(rng, &["You", "Them"])
+        };
+
+        let record = GeneratedRecord {
+            name: format!("Synthetic {}", i + 1),
+            matched: 1,
+            convo: convo as u8,
+            last_reply,
+            specified: (rng.random::<f64>() >= distributions.missing_rate) as u8,
+            native_american: flags[0],
+            southeast_asian: flags[1],
+            black_african_descent: flags[2],
+            east_asian: flags[3],
+            hispanic_latino: flags[4],
+            middle_eastern: flags[5],
+            pacific_islander: flags[6],
+            south_asian: flags[7],
+            white_caucasian: flags[8],
+            other: flags[9],
+            age: present(rng, distributions).then(|| (distributions.mean_age + normal(rng) * 4.0).clamp(18.0, 60.0).round() as u8),
+            height: present(rng, distributions).then(|| (height_mean + normal(rng) * height_standard_deviation).round() as u8),
+            religion: present(rng, distributions).then(|| religions[weighted(rng, &religion_shares)].to_string()),
+            education: (present(rng, distributions) && !education_baseline.is_empty())
+                .then(|| education_baseline[weighted(rng, &shares(education_baseline))].0.clone()),
+            job: present(rng, distributions).then(|| pick(rng, &JOBS)),
+            politics: present(rng, distributions).then(|| pick(rng, &POLITICS)),
+            drinking: present(rng, distributions).then(|| pick(rng, &HABITS)),
+            smoking: present(rng, distributions).then(|| pick(rng, &HABITS)),
+            marijuana: present(rng, distributions).then(|| pick(rng, &HABITS)),
+            drugs: present(rng, distributions).then(|| pick(rng, &HABITS)),
+            wants_kids: present(rng, distributions).then(|| pick(rng, &FAMILY_PLANS)),
+            distance_miles: present(rng, distributions).then_some(distance_miles),
+            neighborhood: present(rng, distributions).then(|| pick(rng, &NEIGHBORHOODS))
+        };
+
+        if chats.is_some() {
+            interactions.push(interaction(rng, &record, start));
+        }
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+
+    if let Some(mut chats) = chats {
+        serde_json::to_writer_pretty(&mut chats, &interactions)?;
+        writeln!(chats)?;
+    }
+    Ok(())
+}
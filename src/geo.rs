@@ -0,0 +1,206 @@
+use std::{error::Error, fmt::Write, path::Path};
+
+use crate::{format, zipcode, HingeProfile, WhoLastReplied};
+
+// Chicago neighborhoods and suburbs with their county and roughly where their center is. A file with the same columns
+// passed to --neighborhoods places neighborhoods anywhere else.
+const BUNDLED_NEIGHBORHOODS: &str = include_str!("../data/neighborhoods.csv");
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 600.0;
+const MARGIN: f64 = 60.0;
+// width of the county totals to the right of the map
+const SIDEBAR: f64 = 220.0;
+const MAX_RADIUS: f64 = 28.0;
+
+// Shading from the fewest matches to the most
+const LIGHT: (u8, u8, u8) = (0xfd, 0xe0, 0xdd);
+const DARK: (u8, u8, u8) = (0xae, 0x01, 0x7e);
+
+#[derive(Debug, serde::Deserialize)]
+struct NeighborhoodRecord {
+    name: String,
+    county: String,
+    county_name: String,
+    latitude: f64,
+    longitude: f64
+}
+
+// Where a neighborhood value is, found by name or as a ZIP code
+#[derive(Debug, Clone)]
+struct Place {
+    county: String,
+    latitude: f64,
+    longitude: f64
+}
+
+// Matches and dates from one place
+#[derive(Debug, Clone)]
+struct Location {
+    label: String,
+    place: Option<Place>,
+    matches: u32,
+    dates: u32
+}
+
+fn read_neighborhoods(path: Option<&Path>) -> Result<Vec<NeighborhoodRecord>, Box<dyn Error>> {
+    let records = match path {
+        Some(path) => csv::Reader::from_path(path)?.deserialize().collect::<Result<Vec<_>, _>>(),
+        None => csv::Reader::from_reader(BUNDLED_NEIGHBORHOODS.as_bytes()).deserialize().collect::<Result<Vec<_>, _>>()
+    };
+    Ok(records?)
+}
+
+fn locations(profiles: &[HingeProfile], neighborhoods: &[NeighborhoodRecord], zip_codes: &[zipcode::ZipCode]) -> Vec<Location> {
+    let place = |label: &str| {
+        neighborhoods
+            .iter()
+            .find(|neighborhood| neighborhood.name.eq_ignore_ascii_case(label))
+            .map(|neighborhood| Place { county: neighborhood.county.clone(), latitude: neighborhood.latitude, longitude: neighborhood.longitude })
+            .or_else(|| zip_codes
+                .iter()
+                .find(|zip_code| zip_code.zip == label)
+                .map(|zip_code| Place { county: zip_code.county.clone(), latitude: zip_code.latitude, longitude: zip_code.longitude }))
+    };
+
+    let mut locations: Vec<Location> = Vec::new();
+    for profile in profiles {
+        let Some(label) = &profile.neighborhood else {
+            continue;
+        };
+
+        let index = match locations.iter().position(|location| location.label.eq_ignore_ascii_case(label)) {
+            Some(index) => index,
+            None => {
+                locations.push(Location { label: label.clone(), place: place(label), matches: 0, dates: 0 });
+                locations.len() - 1
+            }
+        };
+        locations[index].matches += 1;
+        locations[index].dates += (profile.who_last_replied == WhoLastReplied::Met) as u32;
+    }
+
+    locations.sort_by(|a, b| b.matches.cmp(&a.matches).then_with(|| a.label.cmp(&b.label)));
+    locations
+}
+
+// Matches and dates of every county with a placed neighborhood, named from the neighborhoods file when it has the county
+fn county_totals(locations: &[Location], neighborhoods: &[NeighborhoodRecord]) -> Vec<(String, u32, u32)> {
+    let mut counties: Vec<(String, u32, u32)> = Vec::new();
+    for location in locations {
+        let Some(place) = &location.place else {
+            continue;
+        };
+
+        let name = neighborhoods
+            .iter()
+            .find(|neighborhood| neighborhood.county == place.county)
+            .map_or(place.county.clone(), |neighborhood| format!("{} ({})", neighborhood.county_name, place.county));
+        match counties.iter_mut().find(|(county, _, _)| *county == name) {
+            Some((_, matches, dates)) => {
+                *matches += location.matches;
+                *dates += location.dates;
+            },
+            None => counties.push((name, location.matches, location.dates))
+        }
+    }
+
+    counties.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counties
+}
+
+fn shade(share: f64) -> String {
+    let mix = |light: u8, dark: u8| (light as f64 + (dark as f64 - light as f64) * share).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", mix(LIGHT.0, DARK.0), mix(LIGHT.1, DARK.1), mix(LIGHT.2, DARK.2))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// The placed locations as circles sized and shaded by their matches over an equirectangular projection squeezed by the
+// latitude, with the county totals and the neighborhoods that couldn't be placed to the right
+fn render(locations: &[Location], counties: &[(String, u32, u32)]) -> Result<String, std::fmt::Error> {
+    let placed = locations.iter().filter_map(|location| Some((location, location.place.as_ref()?))).collect::<Vec<_>>();
+    let max_matches = locations.iter().map(|location| location.matches).max().unwrap_or(1).max(1) as f64;
+
+    let latitudes = placed.iter().map(|(_, place)| place.latitude);
+    let longitudes = placed.iter().map(|(_, place)| place.longitude);
+    let (south, north) = (latitudes.clone().fold(f64::INFINITY, f64::min), latitudes.fold(f64::NEG_INFINITY, f64::max));
+    let (west, east) = (longitudes.clone().fold(f64::INFINITY, f64::min), longitudes.fold(f64::NEG_INFINITY, f64::max));
+    let squeeze = ((south + north) / 2.0).to_radians().cos();
+    let span = ((east - west) * squeeze).max(north - south).max(1e-6);
+    let scale = (WIDTH - 2.0 * MARGIN).min(HEIGHT - 2.0 * MARGIN) / span;
+    let project = |place: &Place| (
+        MARGIN + ((place.longitude - west) * squeeze * scale),
+        MARGIN + ((north - place.latitude) * scale)
+    );
+
+    let mut svg = String::new();
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="sans-serif" font-size="11">"#, WIDTH + SIDEBAR, HEIGHT)?;
+    writeln!(svg, r##"<rect width="100%" height="100%" fill="#ffffff"/>"##)?;
+    writeln!(svg, r#"<text x="{}" y="24" font-size="16">Where Matches Come From</text>"#, MARGIN)?;
+    // biggest first so smaller circles stay on top
+    for (location, place) in &placed {
+        let (x, y) = project(place);
+        let share = location.matches as f64 / max_matches;
+        let radius = MAX_RADIUS * share.sqrt().max(0.15);
+        writeln!(
+            svg,
+            r##"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="{}" fill-opacity="0.85" stroke="#555555"><title>{}: {} matches, {} dates</title></circle>"##,
+            x,
+            y,
+            radius,
+            shade(share),
+            escape(&location.label),
+            location.matches,
+            location.dates
+        )?;
+        writeln!(svg, r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{} ({})</text>"#, x, y + radius + 12.0, escape(&location.label), location.matches)?;
+    }
+
+    let mut y = 50.0;
+    writeln!(svg, r#"<text x="{}" y="{}" font-size="13">Matches by County</text>"#, WIDTH, y)?;
+    for (county, matches, dates) in counties {
+        y += 16.0;
+        writeln!(svg, r#"<text x="{}" y="{}">{}: {} matches, {} dates</text>"#, WIDTH, y, escape(county), matches, dates)?;
+    }
+
+    let unplaced = locations.iter().filter(|location| location.place.is_none()).collect::<Vec<_>>();
+    if !unplaced.is_empty() {
+        y += 32.0;
+        writeln!(svg, r#"<text x="{}" y="{}" font-size="13">Not on the Map</text>"#, WIDTH, y)?;
+        for location in unplaced {
+            y += 16.0;
+            writeln!(svg, r#"<text x="{}" y="{}">{}: {} matches</text>"#, WIDTH, y, escape(&location.label), location.matches)?;
+        }
+    }
+    writeln!(svg, "</svg>")?;
+    Ok(svg)
+}
+
+// Prints the matches and dates of each neighborhood and county and returns an SVG map of where matches come from by the
+// neighborhood column, which can hold a neighborhood name or a ZIP code
+pub fn map(profiles: &[HingeProfile], neighborhoods: Option<&Path>, zip_crosswalk: Option<&Path>) -> Result<String, Box<dyn Error>> {
+    if !profiles.iter().any(|profile| profile.neighborhood.is_some()) {
+        return Err("No profiles have a neighborhood to map".into());
+    }
+
+    let neighborhoods = read_neighborhoods(neighborhoods)?;
+    let locations = locations(profiles, &neighborhoods, &zipcode::read_crosswalk(zip_crosswalk)?);
+    let counties = county_totals(&locations, &neighborhoods);
+
+    println!("\n\t         Matches by Neighborhood");
+    println!("\t{:<25}   Matches   Dates     County", "");
+    for location in &locations {
+        let county = location.place.as_ref().map_or("not on the map", |place| place.county.as_str());
+        println!("\t{:<25}   {:<7}   {:<7}   {}", location.label, format::count(location.matches), format::count(location.dates), county);
+    }
+
+    println!("\n\t         Matches by County");
+    for (county, matches, dates) in &counties {
+        println!("\t{:<25}   {:<7}   {:<7}", county, format::count(matches), format::count(dates));
+    }
+
+    Ok(render(&locations, &counties)?)
+}
@@ -0,0 +1,172 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_graphql::{EmptyMutation, EmptySubscription, Object, Result, Schema, SimpleObject};
+
+use crate::{api, attribute, filter::Filter, funnel, report, server, HingeProfile, WhoLastReplied};
+
+pub type AnalysisSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+// Every field of the query reruns the analysis, so each counts for as much as a hundred plain fields and a query may
+// ask for a handful of them, under an alias or not, since the server answers one request at a time
+const ANALYSIS_COMPLEXITY: usize = 100;
+const MAX_COMPLEXITY: usize = 1000;
+
+// Nesting allowed in a query, deep enough for the introspection query of GraphiQL and other tools
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, SimpleObject)]
+struct Summary {
+    matches: usize,
+    conversations: usize,
+    dates: usize,
+    conversation_rate: f64,
+    date_rate: f64
+}
+
+// The funnel of the profiles sharing a value of the grouped attribute, the group is missing for profiles without one
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, SimpleObject)]
+struct FunnelGroup {
+    group: Option<String>,
+    matches: usize,
+    stages: Vec<api::FunnelRow>
+}
+
+pub struct Query {
+    analysis: Arc<api::Analysis>
+}
+
+fn parse(filter: Option<String>) -> Result<Option<Filter>> {
+    api::parse_filter(filter.as_deref()).map_err(async_graphql::Error::new)
+}
+
+impl Query {
+    fn load(&self, filter: Option<String>) -> Result<Vec<HingeProfile>> {
+        (self.analysis.profiles)(parse(filter)?.as_ref()).map_err(|err| async_graphql::Error::new(err.to_string()))
+    }
+
+    fn group(&self, group: Option<String>, profiles: &[&HingeProfile]) -> FunnelGroup {
+        let stages = funnel::stage_counts(profiles.iter().copied(), &self.analysis.funnel);
+        FunnelGroup { group, matches: profiles.len(), stages: api::funnel_rows(stages, profiles.len()) }
+    }
+}
+
+#[Object]
+impl Query {
+    /// Profiles of the matches file, only those matching the filter when given, in the syntax of --filter
+    #[graphql(complexity = "ANALYSIS_COMPLEXITY + child_complexity")]
+    async fn profiles(&self, filter: Option<String>) -> Result<Vec<api::ProfileRow>> {
+        Ok(self.load(filter)?.iter().map(api::ProfileRow::from).collect())
+    }
+
+    /// Matches, conversations and dates with their rates
+    #[graphql(complexity = "ANALYSIS_COMPLEXITY + child_complexity")]
+    async fn summary(&self, filter: Option<String>) -> Result<Summary> {
+        let profiles = self.load(filter)?;
+        let conversations = profiles.iter().filter(|profile| profile.convo).count();
+        let dates = profiles.iter().filter(|profile| profile.who_last_replied == WhoLastReplied::Met).count();
+        Ok(Summary {
+            matches: profiles.len(),
+            conversations,
+            dates,
+            conversation_rate: conversations as f64 / profiles.len().max(1) as f64,
+            date_rate: dates as f64 / profiles.len().max(1) as f64
+        })
+    }
+
+    /// Profiles reaching each stage of the conversation funnel, for every value of groupBy when given, e.g.
+    /// funnel(filter: "convo", groupBy: "race")
+    #[graphql(complexity = "ANALYSIS_COMPLEXITY + child_complexity")]
+    async fn funnel(&self, filter: Option<String>, group_by: Option<String>) -> Result<Vec<FunnelGroup>> {
+        let profiles = self.load(filter)?;
+        let Some(attribute) = group_by else {
+            return Ok(vec![self.group(None, &profiles.iter().collect::<Vec<_>>())]);
+        };
+        if !attribute::FIELDS.contains(&attribute.as_str()) {
+            return Err(format!("Unknown attribute {} to group by, expected one of {}", attribute, attribute::FIELDS.join(", ")).into());
+        }
+
+        let mut groups: BTreeMap<Option<String>, Vec<&HingeProfile>> = BTreeMap::new();
+        for profile in &profiles {
+            groups.entry(attribute::value(profile, &attribute)).or_default().push(profile);
+        }
+        Ok(groups.into_iter().map(|(group, profiles)| self.group(group, &profiles)).collect())
+    }
+
+    /// Preferences and outcomes of every attribute with a baseline, or only the given one of race, age, height,
+    /// religion and education
+    #[graphql(complexity = "ANALYSIS_COMPLEXITY + child_complexity")]
+    async fn preferences(&self, filter: Option<String>, attribute: Option<String>) -> Result<Vec<report::Section>> {
+        let report = (self.analysis.report)(parse(filter)?.as_ref()).map_err(|err| async_graphql::Error::new(err.to_string()))?;
+        Ok(report
+            .sections
+            .into_iter()
+            .filter(|(name, _)| attribute.as_ref().is_none_or(|attribute| attribute == name))
+            .map(|(_, section)| section)
+            .collect())
+    }
+}
+
+pub fn schema(analysis: Arc<api::Analysis>) -> AnalysisSchema {
+    Schema::build(Query { analysis }, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+// POST /graphql with a JSON body of query, variables and operationName, or GET /graphql?query=... for trying queries
+// out from a browser or curl. Errors in the query come back in the errors of a 200 response like GraphQL servers do.
+pub fn respond(request: &server::Request, schema: &AnalysisSchema) -> server::Response {
+    let query = match request.method.as_str() {
+        "POST" => match serde_json::from_str::<async_graphql::Request>(&request.body) {
+            Ok(query) => query,
+            Err(err) => return server::Response::text(400, format!("Expected a JSON body with a query: {}\n", err))
+        },
+        "GET" => match request.parameter("query") {
+            Some(query) => async_graphql::Request::new(query),
+            None => return server::Response::text(400, "Expected a query parameter\n")
+        },
+        _ => return server::Response::text(405, "Only GET and POST are supported\n")
+    };
+    server::Response::json(200, &pollster::block_on(schema.execute(query)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_schema() -> AnalysisSchema {
+        schema(Arc::new(api::Analysis {
+            profiles: Box::new(|_| Ok(Vec::new())),
+            report: Box::new(|_| Err("No report in tests".into())),
+            funnel: funnel::Funnel::new(&[String::from("Matches"), String::from("Met")]).unwrap()
+        }))
+    }
+
+    fn errors(query: &str) -> Vec<String> {
+        pollster::block_on(test_schema().execute(query)).errors.into_iter().map(|error| error.message).collect()
+    }
+
+    #[test]
+    fn answers_queries_within_the_limits() {
+        assert_eq!(errors("{ summary { matches dateRate } funnel(groupBy: \"race\") { group stages { name } } profiles(filter: \"convo\") { name } }"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn rejects_queries_rerunning_the_analysis_too_often() {
+        let aliases = (0..MAX_COMPLEXITY / ANALYSIS_COMPLEXITY).map(|i| format!("s{}: summary {{ matches }}", i)).collect::<Vec<_>>().join(" ");
+        assert_eq!(errors(&format!("{{ {} }}", aliases)), vec![String::from("Query is too complex.")]);
+    }
+
+    #[test]
+    fn rejects_queries_nested_too_deep() {
+        let nested = format!("{{ __schema {{ types {}{{ name }}{} }} }}", "{ fields { type { ofType ".repeat(MAX_DEPTH / 3), "} } }".repeat(MAX_DEPTH / 3));
+        assert_eq!(errors(&nested), vec![String::from("Query is nested too deep.")]);
+    }
+
+    #[test]
+    fn allows_the_introspection_query_of_graphiql() {
+        let query = "query { __schema { queryType { name } types { kind name fields(includeDeprecated: true) { name args { name type { ...TypeRef } } type { ...TypeRef } } } } }
+            fragment TypeRef on __Type { kind name ofType { kind name ofType { kind name ofType { kind name ofType { kind name ofType { kind name ofType { kind name ofType { kind name } } } } } } } }";
+        assert_eq!(errors(query), Vec::<String>::new());
+    }
+}
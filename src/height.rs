@@ -0,0 +1,123 @@
+use statrs::distribution::{ContinuousCDF, Normal};
+
+use crate::{format, preference, HingeProfile, WhoLastReplied};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum HeightBaseline {
+    /// CDC adult women, mean 63.5 in
+    Women,
+    /// CDC adult men, mean 69.0 in
+    Men
+}
+
+impl HeightBaseline {
+    // Source: https://www.cdc.gov/nchs/data/series/sr_03/sr03-046-508.pdf (NHANES 2015-2018, adults 20 and over)
+    // Heights are close enough to normal that the mean and standard deviation stand in for the full distribution
+    // Mean and standard deviation of adult height in inches
+    pub fn parameters(self) -> (f64, f64) {
+        match self {
+            HeightBaseline::Women => (63.5, 2.8),
+            HeightBaseline::Men => (69.0, 3.0)
+        }
+    }
+
+    fn distribution(self) -> Normal {
+        let (mean, standard_deviation) = self.parameters();
+        Normal::new(mean, standard_deviation).expect("Bad height distribution")
+    }
+}
+
+// Heights at or above 9 feet are typos, like 30'0 for 3'0 or 6'0
+const MAX_HEIGHT: u16 = 108;
+
+// Height in whole inches from 70, 5'10", 5' 10 or 178cm, None for anything that isn't a plausible height like 5'13"
+pub fn parse_height(value: &str) -> Option<u8> {
+    let value = value.trim();
+    let inches = if let Some(centimeters) = value.strip_suffix("cm") {
+        let inches = (centimeters.trim().parse::<f64>().ok()? / 2.54).round();
+        (0.0..MAX_HEIGHT as f64).contains(&inches).then_some(inches as u16)?
+    } else {
+        match value.split_once('\'') {
+            Some((feet, inches)) => {
+                let inches = inches.trim().trim_end_matches('"').trim();
+                let inches = if inches.is_empty() { 0 } else { inches.parse::<u16>().ok().filter(|&inches| inches < 12)? };
+                feet.trim().parse::<u16>().ok()?.checked_mul(12)?.checked_add(inches)?
+            },
+            None => value.parse().ok()?
+        }
+    };
+    u8::try_from(inches).ok().filter(|&inches| u16::from(inches) < MAX_HEIGHT)
+}
+
+pub fn format_height(inches: u8) -> String {
+    format!("{}'{}\"", inches / 12, inches % 12)
+}
+
+// Buckets of `width` inches covering every observed height, each with its share of the baseline population. The
+// outermost buckets also take the tails of the baseline so the shares add up to 1.
+pub fn height_buckets(heights: impl Iterator<Item = u8> + Clone, width: u8, baseline: HeightBaseline) -> Vec<(u8, u8, f64)> {
+    let width = width.max(1);
+    let (Some(min), Some(max)) = (heights.clone().min(), heights.max()) else {
+        return Vec::new();
+    };
+
+    let distribution = baseline.distribution();
+    let starts = (min / width * width..=max).step_by(width as usize).collect::<Vec<_>>();
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| {
+            let end = start.saturating_add(width - 1);
+            // heights are reported to the nearest inch
+            let lower = if i == 0 { 0.0 } else { distribution.cdf(start as f64 - 0.5) };
+            let upper = if i == starts.len() - 1 { 1.0 } else { distribution.cdf(end as f64 + 0.5) };
+            (start, end, upper - lower)
+        })
+        .collect()
+}
+
+fn bucket_label(start: u8, end: u8) -> String {
+    if start == end {
+        format_height(start)
+    } else {
+        format!("{}-{}", format_height(start), format_height(end))
+    }
+}
+
+pub fn height_categories<'a>(profiles: impl Iterator<Item = &'a HingeProfile>, buckets: &[(u8, u8, f64)]) -> Vec<preference::Category> {
+    let mut categories = buckets
+        .iter()
+        .map(|&(start, end, population)| preference::Category { label: bucket_label(start, end), count: 0, population })
+        .collect::<Vec<_>>();
+
+    for height in profiles.filter_map(|profile| profile.height) {
+        if let Some(i) = buckets.iter().position(|&(start, end, _)| (start..=end).contains(&height)) {
+            categories[i].count += 1;
+        }
+    }
+
+    categories
+}
+
+// Share of matches, conversations and dates falling in each bucket next to the baseline share
+pub fn print_height_distribution(profiles: &[HingeProfile], buckets: &[(u8, u8, f64)], baseline: HeightBaseline) {
+    let profiles = profiles.iter().filter(|profile| profile.height.is_some()).collect::<Vec<_>>();
+    let groups: [Vec<&HingeProfile>; 3] = [
+        profiles.clone(),
+        profiles.iter().copied().filter(|profile| profile.convo).collect(),
+        profiles.iter().copied().filter(|profile| profile.who_last_replied == WhoLastReplied::Met).collect()
+    ];
+
+    println!("\n\t         Height Distribution (CDC {:?} Baseline, {} Profiles with Height)", baseline, profiles.len());
+    println!("\t{:^15}   Matches    Convos     Dates      Population", "Height");
+    for &(start, end, population) in buckets {
+        let shares = groups
+            .iter()
+            .map(|group| {
+                let count = group.iter().filter(|profile| profile.height.is_some_and(|height| (start..=end).contains(&height))).count();
+                format::percent(count as f64 / group.len().max(1) as f64)
+            })
+            .collect::<Vec<_>>();
+        println!("\t{:<15}   {}   {}   {}   {}", bucket_label(start, end), shares[0], shares[1], shares[2], format::percent(population));
+    }
+}
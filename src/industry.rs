@@ -0,0 +1,100 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Industry {
+    Healthcare,
+    Tech,
+    Finance,
+    Education,
+    Law,
+    Engineering,
+    Science,
+    Business,
+    Arts,
+    Government,
+    Hospitality,
+    Student,
+    Other
+}
+
+// Checked in order so the more specific buckets win, e.g. software engineer is Tech before Engineering. A keyword with
+// spaces matches that phrase anywhere in the job, a keyword of four letters or fewer matches a whole word and any other
+// keyword matches the start of a word.
+const KEYWORDS: &[(Industry, &[&str])] = &[
+    (Industry::Student, &["student", "phd candidate", "grad school", "med school", "law school"]),
+    (Industry::Healthcare, &[
+        "nurse", "nursing", "rn", "doctor", "physician", "md", "dentist", "dental", "pharmac", "therap", "medical", "medicine", "health",
+        "hospital", "clinic", "surgeon", "surgical", "psychiatr", "psycholog", "veterinar", "paramedic", "emt", "optometr", "pa-c",
+        "physician assistant", "resident physician", "counselor"
+    ]),
+    (Industry::Tech, &[
+        "software", "developer", "programmer", "swe", "data", "database", "devops", "it", "tech", "product manager", "ux", "ui",
+        "web", "cyber", "machine learning", "ml", "ai", "cloud", "frontend", "backend", "full stack", "qa"
+    ]),
+    (Industry::Finance, &[
+        "financ", "bank", "banker", "banking", "accountant", "accounting", "cpa", "analyst", "investment", "trader", "trading", "actuar", "audit",
+        "tax", "insurance", "underwrit", "wealth", "equity", "credit", "loan", "mortgage"
+    ]),
+    (Industry::Education, &["teacher", "teaching", "professor", "educat", "tutor", "school", "lecturer", "librarian", "instructor"]),
+    (Industry::Law, &["lawyer", "attorney", "paralegal", "legal", "law", "judge", "jd", "counsel"]),
+    (Industry::Engineering, &["engineer", "architect", "mechanic", "electrician", "construction", "manufactur", "technician", "drafts"]),
+    (Industry::Science, &["scien", "research", "chemist", "biolog", "physic", "lab", "geolog", "ecolog"]),
+    (Industry::Business, &[
+        "marketing", "sales", "consult", "business", "operations", "manager", "recruit", "hr", "human resources", "entrepreneur",
+        "founder", "ceo", "executive", "real estate", "realtor", "account manager", "project manager", "supply chain", "logistics"
+    ]),
+    (Industry::Arts, &[
+        "artist", "design", "music", "writer", "editor", "journalis", "photograph", "actor", "actress", "film",
+        "media", "content", "creative", "dancer", "fashion", "stylist", "producer", "influencer", "tattoo"
+    ]),
+    (Industry::Government, &["government", "police", "officer", "military", "army", "navy", "firefight", "nonprofit", "non-profit", "social work", "policy", "public"]),
+    (Industry::Hospitality, &[
+        "server", "bartender", "barista", "chef", "cook", "restaurant", "hospitality", "hotel", "retail", "cashier", "flight attendant",
+        "waitress", "waiter", "hostess", "esthetician", "hairstylist", "salon", "fitness", "trainer", "coach"
+    ])
+];
+
+impl Industry {
+    pub fn classify(job: &str) -> Self {
+        let job = job.to_lowercase();
+        let words = job
+            .split(|c: char| !c.is_alphanumeric() && c != '-')
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>();
+        let phrase = format!(" {} ", words.join(" "));
+
+        KEYWORDS
+            .iter()
+            .find(|(_, keywords)| keywords.iter().any(|keyword| if keyword.contains(' ') {
+                phrase.contains(&format!(" {} ", keyword))
+            } else if keyword.len() <= 4 {
+                words.contains(keyword)
+            } else {
+                words.iter().any(|word| word.starts_with(keyword))
+            }))
+            .map_or(Industry::Other, |(industry, _)| *industry)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Industry::Healthcare => "Healthcare",
+            Industry::Tech => "Tech",
+            Industry::Finance => "Finance",
+            Industry::Education => "Education",
+            Industry::Law => "Law",
+            Industry::Engineering => "Engineering",
+            Industry::Science => "Science & Research",
+            Industry::Business => "Business & Sales",
+            Industry::Arts => "Arts & Media",
+            Industry::Government => "Government & Nonprofit",
+            Industry::Hospitality => "Hospitality & Service",
+            Industry::Student => "Student",
+            Industry::Other => "Other"
+        }
+    }
+}
+
+impl std::fmt::Display for Industry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,435 @@
+use std::{error::Error, fs, path::{Path, PathBuf}, sync::Mutex};
+
+use colored::Colorize;
+use rayon::prelude::*;
+use serde::Deserialize;
+
+use crate::format;
+
+// Files at least this big are mapped into memory instead of read into it, copying them costs more than mapping them
+const MAP_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+// Rejection counts of every CSV read during the run, reported once the output is done so they are not lost in it
+static SUMMARIES: Mutex<Vec<Summary>> = Mutex::new(Vec::new());
+
+#[derive(Debug, serde::Serialize)]
+pub struct Rejection {
+    pub line: u64,
+    pub reason: String,
+    pub original: String
+}
+
+#[derive(Debug, Clone)]
+struct Summary {
+    path: PathBuf,
+    rows: usize,
+    rejected: usize,
+    quarantine: PathBuf
+}
+
+// rejected_rows.csv next to matches.csv, other files get their name in front so they don't overwrite each other
+fn quarantine_path(path: &Path) -> PathBuf {
+    match path.file_stem().and_then(|stem| stem.to_str()) {
+        Some("matches") | None => path.with_file_name("rejected_rows.csv"),
+        Some(stem) => path.with_file_name(format!("{}_rejected_rows.csv", stem))
+    }
+}
+
+// The column and problem of a row that could not be read, without the position csv already puts in the rejection
+fn reason(err: &csv::Error, headers: &csv::ByteRecord) -> String {
+    match err.kind() {
+        csv::ErrorKind::Deserialize { err, .. } => match err.field().and_then(|field| headers.get(field as usize)) {
+            Some(column) => format!("{}: {}", String::from_utf8_lossy(column), err.kind()),
+            None => err.kind().to_string()
+        },
+        _ => err.to_string()
+    }
+}
+
+// The type a row of a CSV is deserialized into, with its text fields borrowed from the record the row was read into
+// rather than copied out of it, e.g. `type Fields<'r> = MyRecord<'r>` for a struct of `&'r str` fields
+pub trait Record {
+    type Fields<'r>: Deserialize<'r>;
+}
+
+// Columns a CSV is read with, whether near miss headers are read as the column they look like, and whether a file
+// over MAP_THRESHOLD may be mapped into memory. A mapped file truncated while it's read crashes the process with
+// SIGBUS, so only runs that read the file once and exit map it, not the server and daemon rereading a file the user
+// edits in place.
+#[derive(Debug, Clone, Copy)]
+pub struct Schema<'a> {
+    pub required: &'a [&'a str],
+    pub optional: &'a [&'a str],
+    pub fuzzy_headers: bool,
+    pub map: bool
+}
+
+// A header found in the file that looks like a column of the schema the file is missing
+#[derive(Debug, Clone)]
+struct Suggestion<'a> {
+    index: usize,
+    found: String,
+    expected: &'a str
+}
+
+fn normalize_header(header: &str) -> String {
+    header.trim().to_lowercase().replace([' ', '-'], "_")
+}
+
+// Levenshtein distance between two headers
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b) in b.iter().enumerate() {
+            current[j + 1] = (previous[j] + (a != *b) as usize).min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// Unknown headers within a couple of typos of a schema column missing from the file, the closest header wins when
+// several look like the same column
+fn suggestions<'a>(headers: &csv::ByteRecord, schema: &Schema<'a>) -> Vec<Suggestion<'a>> {
+    let headers = headers.iter().map(|header| String::from_utf8_lossy(header).to_string()).collect::<Vec<_>>();
+    let columns = schema.required.iter().chain(schema.optional);
+
+    columns
+        .clone()
+        .filter(|column| !headers.iter().any(|header| header == *column))
+        .filter_map(|expected| {
+            headers
+                .iter()
+                .enumerate()
+                .filter(|(_, header)| !columns.clone().any(|column| column == header))
+                .map(|(index, header)| (index, header, edit_distance(&normalize_header(header), expected)))
+                .filter(|(_, _, distance)| *distance <= (expected.len() / 4).max(1))
+                .min_by_key(|(_, _, distance)| *distance)
+                .map(|(index, header, _)| Suggestion { index, found: header.clone(), expected })
+        })
+        .collect()
+}
+
+// A row of the CSV with the line it was read from, and the value or the reason it was rejected. The line is borrowed
+// from the contents of the file and only copied out for a rejected row.
+struct Row<'c, T> {
+    line: u64,
+    original: &'c [u8],
+    result: Result<T, String>
+}
+
+struct Rows<'a, 'c, T> {
+    headers: csv::ByteRecord,
+    suggestions: Vec<Suggestion<'a>>,
+    rows: Vec<Row<'c, T>>
+}
+
+// A line of the file as written to the quarantine, without its line break
+fn line_text(line: &[u8]) -> String {
+    String::from_utf8_lossy(line).trim_end_matches(['\r', '\n']).to_string()
+}
+
+// Headers of the file, with near misses read as the column they look like when the schema allows it
+fn read_headers<'a>(reader: &mut csv::Reader<impl std::io::Read>, schema: &Schema<'a>) -> Result<(csv::ByteRecord, Vec<Suggestion<'a>>), csv::Error> {
+    let mut headers = reader.byte_headers()?.clone();
+    let suggestions = suggestions(&headers, schema);
+    if schema.fuzzy_headers {
+        headers = headers
+            .iter()
+            .enumerate()
+            .map(|(index, header)| suggestions.iter().find(|suggestion| suggestion.index == index).map_or(header, |suggestion| suggestion.expected.as_bytes()))
+            .collect();
+    }
+    Ok((headers, suggestions))
+}
+
+fn read_row<R: Record, T>(
+    record: &csv::ByteRecord,
+    headers: &csv::ByteRecord,
+    convert: impl for<'r> Fn(R::Fields<'r>) -> Result<T, String>
+) -> Result<T, String> {
+    if record.len() != headers.len() {
+        Err(format!("Expected {} fields but found {}", headers.len(), record.len()))
+    } else {
+        record.deserialize::<R::Fields<'_>>(Some(headers)).map_err(|err| reason(&err, headers)).and_then(convert)
+    }
+}
+
+// A file read more than once is reported once, for its last read
+fn record_summary(summary: Summary) {
+    let mut summaries = SUMMARIES.lock().expect("Poisoned ingest summaries");
+    summaries.retain(|recorded| recorded.path != summary.path);
+    summaries.push(summary);
+}
+
+// Rows are split out of the file in order and then read and converted in parallel
+fn read_rows<'a, 'c, R: Record, T: Send>(
+    contents: &'c [u8],
+    schema: &Schema<'a>,
+    convert: impl for<'r> Fn(R::Fields<'r>) -> Result<T, String> + Sync
+) -> Result<Rows<'a, 'c, T>, Box<dyn Error>> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(contents);
+    let (headers, suggestions) = read_headers(&mut reader, schema)?;
+
+    let mut records = Vec::new();
+    loop {
+        let start = reader.position().byte() as usize;
+        let mut record = csv::ByteRecord::new();
+        if !reader.read_byte_record(&mut record)? {
+            break;
+        }
+
+        let end = (reader.position().byte() as usize).min(contents.len());
+        records.push((record, &contents[start..end]));
+    }
+
+    let rows = records
+        .into_par_iter()
+        .map(|(record, original)| Row {
+            line: record.position().map_or(0, |position| position.line()),
+            result: read_row::<R, _>(&record, &headers, &convert),
+            original
+        })
+        .collect();
+
+    Ok(Rows { headers, suggestions, rows })
+}
+
+fn print_suggestions(path: &Path, schema: &Schema, suggestions: &[Suggestion]) {
+    for suggestion in suggestions {
+        if schema.fuzzy_headers {
+            println!("Reading header `{}` of {} as `{}`", suggestion.found, path.display(), suggestion.expected);
+        } else {
+            println!(
+                "{}",
+                format!("Found `{}` in {}; expected `{}`, rename it or pass --fuzzy-headers", suggestion.found, path.display(), suggestion.expected)
+                    .yellow()
+                    .bold()
+            );
+        }
+    }
+}
+
+// The bytes of a file, read into memory or mapped into it
+enum Contents {
+    Read(Vec<u8>),
+    Mapped(memmap2::Mmap)
+}
+
+impl std::ops::Deref for Contents {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Contents::Read(bytes) => bytes,
+            Contents::Mapped(map) => map
+        }
+    }
+}
+
+// Files over MAP_THRESHOLD are mapped when the schema allows it so rows are parsed straight out of the page cache
+fn read(path: &Path, schema: &Schema) -> Result<Contents, Box<dyn Error>> {
+    let error = |err: std::io::Error| format!("Could not read {}: {}", path.display(), err);
+    let file = fs::File::open(path).map_err(error)?;
+    if !schema.map || file.metadata().map_err(error)?.len() < MAP_THRESHOLD {
+        return Ok(Contents::Read(fs::read(path).map_err(error)?));
+    }
+
+    // SAFETY: the map is only read. If another process truncates the file while it is mapped, reading the lost pages
+    // raises SIGBUS, which the schema only allows to happen to one-shot runs.
+    let map = unsafe { memmap2::Mmap::map(&file) }.map_err(error)?;
+    Ok(Contents::Mapped(map))
+}
+
+// Values of the rows that were read, and the rejection of every row that wasn't
+fn split<T>(rows: Vec<Row<T>>) -> (Vec<T>, Vec<Rejection>) {
+    let mut values = Vec::new();
+    let mut rejections = Vec::new();
+    for row in rows {
+        match row.result {
+            Ok(value) => values.push(value),
+            Err(reason) => rejections.push(Rejection { line: row.line, reason, original: line_text(row.original) })
+        }
+    }
+    (values, rejections)
+}
+
+// Like ingest but of a CSV already in memory, the rejected rows are returned instead of quarantined and nothing is
+// printed or recorded for the summary
+pub fn ingest_bytes<R: Record, T: Send>(
+    contents: &[u8],
+    schema: &Schema,
+    convert: impl for<'r> Fn(R::Fields<'r>) -> Result<T, String> + Sync
+) -> Result<(Vec<T>, Vec<Rejection>), Box<dyn Error>> {
+    let Rows { rows, .. } = read_rows::<R, _>(contents, schema, convert)?;
+    Ok(split(rows))
+}
+
+// Reads every row of a CSV into a record and converts it, rows that fail either step are written to the quarantine file
+// with their line number, the reason and the line as it was in the file. Only a file that can't be read at all is an
+// error.
+pub fn ingest<R: Record, T: Send>(
+    path: impl AsRef<Path>,
+    schema: &Schema,
+    convert: impl for<'r> Fn(R::Fields<'r>) -> Result<T, String> + Sync
+) -> Result<Vec<T>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let contents = read(path, schema)?;
+    let Rows { suggestions, rows, .. } = read_rows::<R, _>(&contents, schema, convert)?;
+    print_suggestions(path, schema, &suggestions);
+    let (values, rejections) = split(rows);
+
+    let quarantine = quarantine_path(path);
+    if !rejections.is_empty() {
+        let mut writer = csv::Writer::from_path(&quarantine)?;
+        for rejection in &rejections {
+            writer.serialize(rejection)?;
+        }
+        writer.flush()?;
+    } else if quarantine.exists() {
+        // a quarantine left over from an earlier run would describe rows that have since been fixed
+        fs::remove_file(&quarantine)?;
+    }
+
+    record_summary(Summary { path: path.to_path_buf(), rows: values.len() + rejections.len(), rejected: rejections.len(), quarantine });
+
+    Ok(values)
+}
+
+// Like ingest but a row at a time, every value is handed to `each` as it is read and rejected rows are quarantined as
+// they are found, so a file of any size is read in constant memory. The line of a rejected row is written back out of
+// its fields rather than copied from the file.
+pub fn stream<R: Record, T>(
+    path: impl AsRef<Path>,
+    schema: &Schema,
+    convert: impl for<'r> Fn(R::Fields<'r>) -> Result<T, String>,
+    mut each: impl FnMut(T)
+) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+    let (headers, suggestions) = read_headers(&mut reader, schema)?;
+    print_suggestions(path, schema, &suggestions);
+
+    let quarantine = quarantine_path(path);
+    let mut quarantine_writer = None;
+    let (mut rows, mut rejected) = (0, 0);
+    let mut record = csv::ByteRecord::new();
+    while reader.read_byte_record(&mut record)? {
+        rows += 1;
+        match read_row::<R, _>(&record, &headers, &convert) {
+            Ok(value) => each(value),
+            Err(reason) => {
+                rejected += 1;
+                let mut line = csv::Writer::from_writer(Vec::new());
+                line.write_byte_record(&record)?;
+                let original = line_text(&line.into_inner()?);
+
+                let writer = match &mut quarantine_writer {
+                    Some(writer) => writer,
+                    None => quarantine_writer.insert(csv::Writer::from_path(&quarantine)?)
+                };
+                writer.serialize(Rejection { line: record.position().map_or(0, |position| position.line()), reason, original })?;
+            }
+        }
+    }
+
+    match quarantine_writer {
+        Some(mut writer) => writer.flush()?,
+        None if quarantine.exists() => fs::remove_file(&quarantine)?,
+        None => {}
+    }
+
+    record_summary(Summary { path: path.to_path_buf(), rows, rejected, quarantine });
+    Ok(())
+}
+
+// Every problem of a CSV without reading it into the analysis: near miss and missing required columns in the header,
+// rows that can't be read or converted, and rows repeating the key of an earlier row. A row is keyed on the first of
+// the key columns it has a value for. Problems with the header are on line 1.
+pub fn validate<R: Record, T: Send>(
+    path: impl AsRef<Path>,
+    schema: &Schema,
+    keys: &[&str],
+    convert: impl for<'r> Fn(R::Fields<'r>) -> Result<T, String> + Sync
+) -> Result<Vec<Rejection>, Box<dyn Error>> {
+    let contents = read(path.as_ref(), schema)?;
+    let Rows { headers, suggestions, rows } = read_rows::<R, _>(&contents, schema, convert)?;
+    let header_line = String::from_utf8_lossy(contents.split(|&byte| byte == b'\n').next().unwrap_or_default()).trim_end().to_string();
+    let header = |name: &str| headers.iter().position(|column| column == name.as_bytes());
+
+    let mut problems = Vec::new();
+    if !schema.fuzzy_headers {
+        problems.extend(suggestions.iter().map(|suggestion| Rejection {
+            line: 1,
+            reason: format!("Found `{}`; expected `{}`", suggestion.found, suggestion.expected),
+            original: header_line.clone()
+        }));
+    }
+    problems.extend(schema.required.iter().filter(|column| header(column).is_none()).map(|column| Rejection {
+        line: 1,
+        reason: format!("Missing required column {}", column),
+        original: header_line.clone()
+    }));
+
+    let key_indices = keys.iter().filter_map(|key| Some((*key, header(key)?))).collect::<Vec<_>>();
+    let mut first_lines = std::collections::HashMap::new();
+    for row in rows {
+        if let Err(reason) = row.result {
+            problems.push(Rejection { line: row.line, reason, original: line_text(row.original) });
+        }
+
+        // the key is read from the raw line so rows with other problems are still checked for duplicates
+        let record = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(row.original)
+            .records()
+            .next()
+            .and_then(Result::ok);
+        let Some((key, value)) = key_indices.iter().find_map(|(key, index)| {
+            let value = record.as_ref()?.get(*index)?.trim().to_lowercase();
+            (!value.is_empty()).then_some((*key, value))
+        }) else {
+            continue;
+        };
+
+        match first_lines.get(&(key, value.clone())) {
+            Some(first_line) => problems.push(Rejection {
+                line: row.line,
+                reason: format!("Duplicate {}, first seen on line {}", key, first_line),
+                original: line_text(row.original)
+            }),
+            None => {
+                first_lines.insert((key, value), row.line);
+            }
+        }
+    }
+
+    Ok(problems)
+}
+
+// Share of rows rejected from each CSV read during the run, nothing when no CSV was read
+pub fn print_summary() {
+    let summaries = SUMMARIES.lock().expect("Poisoned ingest summaries");
+    for summary in summaries.iter() {
+        let rate = summary.rejected as f64 / summary.rows.max(1) as f64;
+        let message = format!(
+            "{}: {} of {} rows rejected ({})",
+            summary.path.display(),
+            format::count(summary.rejected),
+            format::count(summary.rows),
+            format::inline_percent(rate, 2)
+        );
+
+        if summary.rejected == 0 {
+            println!("\n{}", message.green().bold());
+        } else {
+            println!("\n{}", format!("{}, see {} for each row and the reason", message, summary.quarantine.display()).red().bold());
+        }
+    }
+}
@@ -0,0 +1,2676 @@
+mod activity;
+mod alert;
+mod api;
+mod age;
+mod association;
+mod attribute;
+mod baseline;
+#[doc(hidden)]
+pub mod bench;
+mod benchmark;
+mod bundled;
+mod burnout;
+mod census;
+mod datausa;
+mod chat;
+mod config;
+mod contacts;
+mod dedup;
+mod convert;
+mod context;
+mod daemon;
+mod conversation;
+mod distance;
+mod education;
+mod email;
+mod export;
+mod family;
+mod ffi;
+mod filter;
+mod format;
+mod funnel;
+mod generate;
+mod geo;
+mod graphql;
+mod height;
+mod ingest;
+mod industry;
+mod lifestyle;
+mod likes;
+pub mod metric;
+mod metrics;
+mod models;
+mod multiuser;
+mod plugin;
+mod politics;
+mod rating;
+mod repl;
+mod preference;
+mod progress;
+mod report;
+mod script;
+mod server;
+mod stats;
+mod survival;
+mod timeline;
+mod vocabulary;
+mod warning;
+mod webhook;
+mod zipcode;
+mod zodiac;
+
+use std::{collections::BTreeMap, error::Error, fs::File, hash::Hash, path::PathBuf, process};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use colored::Colorize;
+use rand::{rngs::StdRng, SeedableRng};
+
+pub use api::ProfileRow;
+
+type EthnicityBits = u16;
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+struct Ethnicities(EthnicityBits);
+
+impl Ethnicities {
+    const NATIVE_AMERICAN      : EthnicityBits = (0x0001);
+    const SOUTHEAST_ASIAN      : EthnicityBits = (0x0002);
+    const BLACK_AFRICAN_DESCENT: EthnicityBits = (0x0004);
+    const EAST_ASIAN           : EthnicityBits = (0x0008);
+    const HISPANIC_LATINO      : EthnicityBits = (0x0010);
+    const MIDDLE_EASTERN       : EthnicityBits = (0x0020);
+    const PACIFIC_ISLANDER     : EthnicityBits = (0x0040);
+    const SOUTH_ASIAN          : EthnicityBits = (0x0080);
+    const WHITE_CAUCASIAN      : EthnicityBits = (0x0100);
+    const OTHER                : EthnicityBits = (0x8000);
+
+    fn bits(&self) -> EthnicityBits {
+        self.0
+    }
+}
+
+impl From<EthnicityBits> for Ethnicities {
+    fn from(bits: EthnicityBits) -> Self {
+        Self(bits)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+enum WhoLastReplied {
+    You,
+    Them,
+    Met,
+    None
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, serde::Serialize, serde::Deserialize)]
+enum Race {
+    WhiteCaucasian,
+    BlackAfrican,
+    NativeAmerican,
+    Asian,
+    PacificIslander,
+    Multiracial,
+    Hispanic,
+    Other
+}
+
+impl Race {
+    fn entries() -> impl Iterator<Item = Self> {
+        [
+            Race::WhiteCaucasian, 
+            Race::BlackAfrican, 
+            Race::NativeAmerican, 
+            Race::Asian, 
+            Race::PacificIslander, 
+            Race::Multiracial, 
+            Race::Hispanic, 
+            Race::Other
+        ].iter().copied()
+    }
+
+    // counted in an array indexed by the order races are declared in, the map is built once at the end instead of
+    // looked up for every profile
+    fn aggregate(races: impl Iterator<Item = Self>) -> BTreeMap<Self, u32> {
+        let mut counts = [0; 8];
+        for race in races {
+            counts[race as usize] += 1;
+        }
+
+        Race::entries().map(|race| (race, counts[race as usize])).collect()
+    }
+}
+
+impl std::fmt::Display for Race {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match *self {
+            Race::WhiteCaucasian => "White",
+            Race::BlackAfrican => "Black or African American",
+            Race::NativeAmerican => "American Indian or Alaska Native",
+            Race::Asian => "Asian",
+            Race::PacificIslander => "Native Hawaiian & Other Pacific Island",
+            Race::Multiracial => "Multiracial",
+            Race::Hispanic => "Hispanic or Latino",
+            Race::Other => "Other"
+        })?;
+
+        Ok(())
+    }
+}
+
+impl TryFrom<EthnicityBits> for Race {
+    type Error = <Race as TryFrom<Ethnicities>>::Error;
+    fn try_from(value: EthnicityBits) -> Result<Self, Self::Error> {
+        Race::try_from(Ethnicities(value))
+    }
+}
+
+impl TryFrom<Ethnicities> for Race {
+    type Error = &'static str;
+    fn try_from(value: Ethnicities) -> Result<Self, Self::Error> {
+        const ASIAN_RACE_ETHNICITIES: EthnicityBits = Ethnicities::SOUTHEAST_ASIAN | Ethnicities::SOUTH_ASIAN | Ethnicities::EAST_ASIAN;
+        const UNSUPPORTED_ETHNICITIES: EthnicityBits = Ethnicities::MIDDLE_EASTERN;
+
+        let bits = value.bits();
+        if bits == 0 {
+            return Err("Ethnicity is empty");
+        }
+
+        let bits_ignore_not_supported = value.bits() & !UNSUPPORTED_ETHNICITIES;
+
+        match bits_ignore_not_supported {
+            0 => Err("Ethnicity only contains values that are not supported to be converted to a race distinction"),
+            Ethnicities::NATIVE_AMERICAN => Ok(Self::NativeAmerican),
+            Ethnicities::SOUTHEAST_ASIAN => Ok(Self::Asian),
+            Ethnicities::BLACK_AFRICAN_DESCENT => Ok(Self::BlackAfrican),
+            Ethnicities::EAST_ASIAN => Ok(Self::Asian),
+            Ethnicities::PACIFIC_ISLANDER => Ok(Self::PacificIslander),
+            Ethnicities::SOUTH_ASIAN => Ok(Self::Asian),
+            Ethnicities::WHITE_CAUCASIAN => Ok(Self::WhiteCaucasian),
+            Ethnicities::HISPANIC_LATINO => Ok(Self::Hispanic),
+            Ethnicities::OTHER => Ok(Self::Other),
+            _ => {
+                // accept combinations of asian ethnicities
+                if bits_ignore_not_supported & ASIAN_RACE_ETHNICITIES == bits_ignore_not_supported {
+                    return Ok(Self::Asian);
+                }
+
+                if bits_ignore_not_supported & Ethnicities::HISPANIC_LATINO != 0 {
+                    return Ok(Self::Hispanic);
+                }
+
+                // at this point bits must be part of at least two different disjoint sets of ethnicity groupings
+                Ok(Self::Multiracial)
+            }
+        }
+    }
+}
+
+// Value of a yes/no column as it was written, so how forgiving parsing is can be decided after reading the row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flag {
+    Number(u8),
+    Word(bool),
+    Blank
+}
+
+impl Flag {
+    fn is_set(self) -> bool {
+        match self {
+            Flag::Number(number) => number != 0,
+            Flag::Word(set) => set,
+            Flag::Blank => false
+        }
+    }
+
+    fn allowed(self, strictness: Strictness) -> bool {
+        match (self, strictness) {
+            (Flag::Number(0 | 1), _) => true,
+            (Flag::Number(_) | Flag::Word(_), Strictness::Strict) => false,
+            (Flag::Blank, Strictness::Strict | Strictness::Standard) => false,
+            (_, _) => true
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Flag {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(Flag::Blank);
+        }
+
+        match value.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "y" => Ok(Flag::Word(true)),
+            "false" | "no" | "n" => Ok(Flag::Word(false)),
+            _ => value.parse().map(Flag::Number).map_err(|_| serde::de::Error::custom(format!("invalid flag {}, expected 0/1, true/false, yes/no or y/n", value)))
+        }
+    }
+}
+
+// How forgiving parsing of the yes/no columns of matches.csv is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Strictness {
+    /// Only 0 or 1
+    Strict,
+    /// Any number, nonzero being yes, or TRUE/FALSE, Yes/No and Y/N in any case
+    Standard,
+    /// Like standard with blank read as 0
+    Lenient
+}
+
+// A row of matches.csv with its text borrowed from the record it was read into, copied only into the profile it
+// becomes
+#[derive(Debug, serde::Deserialize)]
+struct HingeProfileCSVRecord<'a> {
+    name: &'a str,
+    matched: Flag,
+    convo: Flag,
+    last_reply: &'a str,
+    specified: Flag,
+    native_american: Flag,
+    southeast_asian: Flag,
+    black_african_descent: Flag,
+    east_asian: Flag,
+    hispanic_latino: Flag,
+    middle_eastern: Flag,
+    pacific_islander: Flag,
+    south_asian: Flag,
+    white_caucasian: Flag,
+    other: Flag,
+    #[serde(default)]
+    age: Option<u8>,
+    #[serde(default)]
+    height: Option<&'a str>,
+    #[serde(default)]
+    religion: Option<&'a str>,
+    #[serde(default)]
+    education: Option<&'a str>,
+    #[serde(default)]
+    job: Option<&'a str>,
+    #[serde(default)]
+    politics: Option<&'a str>,
+    #[serde(default)]
+    drinking: Option<&'a str>,
+    #[serde(default)]
+    smoking: Option<&'a str>,
+    #[serde(default)]
+    marijuana: Option<&'a str>,
+    #[serde(default)]
+    drugs: Option<&'a str>,
+    #[serde(default)]
+    wants_kids: Option<&'a str>,
+    #[serde(default)]
+    distance_miles: Option<f64>,
+    #[serde(default)]
+    neighborhood: Option<&'a str>,
+    #[serde(default)]
+    zodiac: Option<&'a str>,
+    #[serde(default)]
+    birthday: Option<&'a str>,
+    #[serde(default)]
+    stage: Option<&'a str>,
+    #[serde(default)]
+    date_rating: Option<u8>,
+    #[serde(default, alias = "profile_id")]
+    id: Option<&'a str>
+}
+
+impl ingest::Record for HingeProfileCSVRecord<'_> {
+    type Fields<'r> = HingeProfileCSVRecord<'r>;
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[allow(dead_code)]
+struct HingeProfile {
+    name: String,
+    matched: bool,
+    convo: bool,
+    who_last_replied: WhoLastReplied,
+    ethnicity_specified: bool,
+    ethnicity: Ethnicities,
+    race: Option<Race>,
+    age: Option<u8>,
+    // in inches
+    height: Option<u8>,
+    religion: Option<String>,
+    education: Option<education::Education>,
+    job: Option<String>,
+    politics: Option<politics::Politics>,
+    drinking: Option<lifestyle::Habit>,
+    smoking: Option<lifestyle::Habit>,
+    marijuana: Option<lifestyle::Habit>,
+    drugs: Option<lifestyle::Habit>,
+    wants_kids: Option<family::FamilyPlans>,
+    distance_miles: Option<f64>,
+    neighborhood: Option<String>,
+    zodiac: Option<zodiac::Sign>,
+    // furthest custom funnel stage reached
+    stage: Option<String>,
+    // 1 to 5
+    date_rating: Option<u8>,
+    // stable identifier of the person, names are not unique and the same person can be matched more than once
+    id: Option<String>
+}
+
+// Columns of matches.csv every row needs, the rest are optional
+const REQUIRED_COLUMNS: [&str; 15] = [
+    "name", "matched", "convo", "last_reply", "specified", "native_american", "southeast_asian", "black_african_descent",
+    "east_asian", "hispanic_latino", "middle_eastern", "pacific_islander", "south_asian", "white_caucasian", "other"
+];
+
+// Columns of matches.csv a row can leave blank or the file can leave out
+const OPTIONAL_COLUMNS: [&str; 18] = [
+    "age", "height", "religion", "education", "job", "politics", "drinking", "smoking", "marijuana", "drugs", "wants_kids",
+    "distance_miles", "neighborhood", "zodiac", "birthday", "stage", "date_rating", "id"
+];
+
+impl<'a> HingeProfileCSVRecord<'a> {
+    // The record with last_reply in the words TryFrom expects
+    fn translate_last_reply(mut self, vocabulary: &vocabulary::Vocabulary) -> Self {
+        self.last_reply = vocabulary.canonical(self.last_reply);
+        self
+    }
+
+    // The record when every yes/no column is written the way the strictness allows
+    fn check_flags(self, strictness: Strictness) -> Result<Self, String> {
+        let flags = [
+            ("matched", self.matched), ("convo", self.convo), ("specified", self.specified), ("native_american", self.native_american),
+            ("southeast_asian", self.southeast_asian), ("black_african_descent", self.black_african_descent), ("east_asian", self.east_asian),
+            ("hispanic_latino", self.hispanic_latino), ("middle_eastern", self.middle_eastern), ("pacific_islander", self.pacific_islander),
+            ("south_asian", self.south_asian), ("white_caucasian", self.white_caucasian), ("other", self.other)
+        ];
+
+        let expected = match strictness {
+            Strictness::Strict => "0 or 1",
+            Strictness::Standard => "a number or yes/no, not blank",
+            Strictness::Lenient => "a number, yes/no or blank"
+        };
+        match flags.into_iter().find(|(_, flag)| !flag.allowed(strictness)) {
+            Some((column, _)) => Err(format!("Invalid value for {}, {:?} parsing expects {}", column, strictness, expected)),
+            None => Ok(self)
+        }
+    }
+}
+
+impl TryFrom<HingeProfileCSVRecord<'_>> for HingeProfile {
+    type Error = &'static str;
+    fn try_from(value: HingeProfileCSVRecord) -> Result<Self, Self::Error> {
+        let who_last_replied = match value.last_reply {
+            "You" => WhoLastReplied::You,
+            "Them" => WhoLastReplied::Them,
+            "None" => WhoLastReplied::None,
+            "Met" => WhoLastReplied::Met,
+            _ => return Err("Invalid value for Who Last Replied")
+        };
+
+        if who_last_replied == WhoLastReplied::Met && !value.convo.is_set() {
+            return Err("Who Last Replied is Met but Conversation is False");
+        }
+
+        if who_last_replied == WhoLastReplied::None && value.convo.is_set() {
+            return Err("Who Last Replied is None but Conversation is True");
+        }
+
+        let ethnicity = Ethnicities(
+            (if value.native_american.is_set()        { Ethnicities::NATIVE_AMERICAN       } else { 0 }) |
+            (if value.southeast_asian.is_set()        { Ethnicities::SOUTHEAST_ASIAN       } else { 0 }) |
+            (if value.black_african_descent.is_set()  { Ethnicities::BLACK_AFRICAN_DESCENT } else { 0 }) |
+            (if value.east_asian.is_set()             { Ethnicities::EAST_ASIAN            } else { 0 }) |
+            (if value.hispanic_latino.is_set()        { Ethnicities::HISPANIC_LATINO       } else { 0 }) |
+            (if value.middle_eastern.is_set()         { Ethnicities::MIDDLE_EASTERN        } else { 0 }) |
+            (if value.pacific_islander.is_set()       { Ethnicities::PACIFIC_ISLANDER      } else { 0 }) |
+            (if value.south_asian.is_set()            { Ethnicities::SOUTH_ASIAN           } else { 0 }) |
+            (if value.white_caucasian.is_set()        { Ethnicities::WHITE_CAUCASIAN       } else { 0 }) |
+            (if value.other.is_set()                  { Ethnicities::OTHER                 } else { 0 })
+        );
+
+        let height = match value.height.map(height::parse_height) {
+            Some(None) => return Err("Invalid value for Height"),
+            height => height.flatten()
+        };
+
+        let education = match value.education.map(str::trim).filter(|education| !education.is_empty()) {
+            Some(education) => Some(education.parse()?),
+            None => None
+        };
+
+        let politics = match value.politics.map(str::trim).filter(|politics| !politics.is_empty()) {
+            Some(politics) => Some(politics.parse()?),
+            None => None
+        };
+
+        if value.distance_miles.is_some_and(|distance| distance.is_nan() || distance < 0.0) {
+            return Err("Invalid value for Distance Miles");
+        }
+        let distance_miles = value.distance_miles;
+
+        if value.date_rating.is_some_and(|rating| !(1..=5).contains(&rating)) {
+            return Err("Invalid value for Date Rating");
+        }
+
+        // an explicit sign wins over the one derived from the birthday
+        let zodiac = match (value.zodiac.map(str::trim).filter(|zodiac| !zodiac.is_empty()), value.birthday.map(str::trim).filter(|birthday| !birthday.is_empty())) {
+            (Some(zodiac), _) => Some(zodiac.parse()?),
+            (None, Some(birthday)) => Some(zodiac::Sign::from_birthday(birthday).ok_or("Invalid value for Birthday")?),
+            (None, None) => None
+        };
+
+        Ok(HingeProfile {
+            name: value.name.to_string(),
+            matched: value.matched.is_set(),
+            convo: value.convo.is_set(),
+            who_last_replied,
+            ethnicity_specified: value.specified.is_set(),
+            ethnicity,
+            race: ethnicity.try_into().ok(),
+            age: value.age,
+            height,
+            religion: value.religion.map(str::trim).filter(|religion| !religion.is_empty()).map(str::to_string),
+            education,
+            job: value.job.map(str::trim).filter(|job| !job.is_empty()).map(str::to_string),
+            politics,
+            drinking: lifestyle::Habit::parse(value.drinking)?,
+            smoking: lifestyle::Habit::parse(value.smoking)?,
+            marijuana: lifestyle::Habit::parse(value.marijuana)?,
+            drugs: lifestyle::Habit::parse(value.drugs)?,
+            wants_kids: family::FamilyPlans::parse(value.wants_kids)?,
+            distance_miles,
+            neighborhood: value.neighborhood.map(str::trim).filter(|neighborhood| !neighborhood.is_empty()).map(str::to_string),
+            zodiac,
+            stage: value.stage.map(str::trim).filter(|stage| !stage.is_empty()).map(str::to_string),
+            date_rating: value.date_rating,
+            id: value.id.map(str::trim).filter(|id| !id.is_empty()).map(str::to_string)
+        })
+    }
+}
+
+impl HingeProfile {
+    // What tells people apart, the id when the row has one and otherwise the name
+    fn key(&self) -> String {
+        match &self.id {
+            Some(id) => format!("id:{}", id),
+            None => format!("name:{}", self.name.trim().to_lowercase())
+        }
+    }
+
+    // Race within the Hispanic population, Hispanic alone is counted as some other race
+    fn hispanic_race(&self) -> Option<Race> {
+        let bits = self.ethnicity.bits();
+        if bits & Ethnicities::HISPANIC_LATINO == 0 {
+            return None;
+        }
+
+        let bits = if bits == Ethnicities::HISPANIC_LATINO {
+            Ethnicities::OTHER
+        } else {
+            bits & !Ethnicities::HISPANIC_LATINO
+        };
+
+        Race::try_from(bits).ok()
+    }
+
+    fn industry(&self) -> Option<industry::Industry> {
+        self.job.as_deref().map(industry::Industry::classify)
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct CountyDemographicsCSVRecord {
+    county: String,
+    white_alone: u32,
+    black_african_american_alone: u32,
+    american_indian_alaska_native_alone: u32,
+    asian_alone: u32,
+    native_hawaiian_pacific_islander_alone: u32,
+    some_other_race_alone: u32,
+    two_or_more_races: u32,
+    hispanic_latino: u32
+}
+
+#[derive(Debug, serde::Serialize)]
+struct CountyHispanicDemographicsCSVRecord {
+    county: String,
+    white_hispanic: u32,
+    black_african_american_hispanic: u32,
+    american_indian_alaska_native_hispanic: u32,
+    asian_hispanic: u32,
+    native_hawaiian_pacific_islander_hispanic: u32,
+    some_other_race_hispanic: u32,
+    two_or_more_races_hispanic: u32
+}
+
+fn racial_categories<'a>(
+    profiles: impl Iterator<Item = &'a HingeProfile> + Clone,
+    race_weights: &BTreeMap<Race, f64>,
+    hispanic_race_weights: &BTreeMap<Race, f64>
+) -> Vec<preference::Category> {
+    let mut race_counts = Race::aggregate(profiles.clone().filter_map(|profile| profile.race));
+    let mut hispanic_race_counts = Race::aggregate(profiles.filter_map(HingeProfile::hispanic_race));
+
+    race_counts.remove(&Race::Hispanic);
+    hispanic_race_counts.remove(&Race::Hispanic);
+
+    // built in Race::entries() order so seeded simulations are reproducible, races with no population are left out
+    Race::entries()
+        .filter_map(|race| Some(preference::Category {
+            label: format!("{} (Non-Hispanic)", race),
+            count: *race_counts.get(&race)?,
+            population: race_weights[&race]
+        }))
+        .chain(Race::entries()
+            .filter_map(|race| Some(preference::Category {
+                label: format!("{} (Hispanic)", race),
+                count: *hispanic_race_counts.get(&race)?,
+                population: race_weights[&Race::Hispanic] * hispanic_race_weights[&race]
+            })))
+        .filter(|category| category.population > 0.0)
+        .collect()
+}
+
+// Source: https://www.census.gov/library/stories/2021/08/improved-race-ethnicity-measures-reveal-united-states-population-much-more-multiracial.html
+// (2020 Census), races alone are non-Hispanic
+const NATIONAL_RACE_WEIGHTS: [(Race, f64); 8] = [
+    (Race::WhiteCaucasian, 0.578),
+    (Race::BlackAfrican, 0.121),
+    (Race::NativeAmerican, 0.007),
+    (Race::Asian, 0.059),
+    (Race::PacificIslander, 0.002),
+    (Race::Multiracial, 0.041),
+    (Race::Hispanic, 0.187),
+    (Race::Other, 0.005)
+];
+
+// Race within the Hispanic population, same source
+const NATIONAL_HISPANIC_RACE_WEIGHTS: [(Race, f64); 7] = [
+    (Race::WhiteCaucasian, 0.203),
+    (Race::BlackAfrican, 0.019),
+    (Race::NativeAmerican, 0.023),
+    (Race::Asian, 0.004),
+    (Race::PacificIslander, 0.001),
+    (Race::Multiracial, 0.327),
+    (Race::Other, 0.423)
+];
+
+// National shares for filling zero population cells, empty when they should be left out
+fn national_race_weights(weights: &[(Race, f64)], zero_population: preference::ZeroPopulation) -> Vec<(String, f64)> {
+    match zero_population {
+        preference::ZeroPopulation::National => weights.iter().map(|(race, weight)| (race.to_string(), *weight)).collect(),
+        preference::ZeroPopulation::Exclude => Vec::new()
+    }
+}
+
+fn national_baseline(args: &Args, path: &PathBuf) -> Result<Vec<(String, f64)>, Box<dyn Error>> {
+    match args.zero_population {
+        preference::ZeroPopulation::National => attribute::load_baseline(path, &args.national_geography, args.baseline_units),
+        preference::ZeroPopulation::Exclude => Ok(Vec::new())
+    }
+}
+
+// options repeat with the last one winning, which is how the command line overrides the config file
+#[derive(Debug, Clone, Parser)]
+#[command(
+    version,
+    about = "Analyzes Hinge match outcomes and racial preferences",
+    args_override_self = true,
+    after_help = "Options are taken from the command line first, then HINGE_ANALYZER_<OPTION> environment variables like \
+                  HINGE_ANALYZER_MATCHES or HINGE_ANALYZER_SAMPLE_CUTOFF, then the config file and its preset"
+)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Config file of default options and presets, hinge-analyzer.toml when present
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Preset of options from the config file, e.g. "serious" for a [preset.serious] table
+    #[arg(long, global = true, value_name = "NAME")]
+    preset: Option<String>,
+
+    /// Matches file analyzed by every command that reads matches
+    #[arg(long, global = true, value_name = "FILE", default_value = "matches.csv")]
+    matches: PathBuf,
+
+    /// Hinge data export, either the ZIP or the matches.json inside it, used by the timeline commands
+    #[arg(long, global = true, value_name = "FILE")]
+    export: Option<PathBuf>,
+
+    /// Hours to add to the UTC timestamps of the export to get your local time, e.g. -6 for Chicago in winter
+    #[arg(long, global = true, default_value_t = 0, allow_negative_numbers = true)]
+    utc_offset: i32,
+
+    /// Only analyze profiles matching this expression, e.g. "convo and (race = Asian or age >= 30) and drinking != No"
+    #[arg(long, global = true, value_name = "EXPR")]
+    filter: Option<filter::Filter>,
+
+    /// Read matches.csv headers that are a typo or two away from a known column as that column, e.g. "last reply"
+    #[arg(long, global = true)]
+    fuzzy_headers: bool,
+
+    /// How forgiving reading the yes/no columns of matches.csv is
+    #[arg(long, global = true, value_enum, default_value_t = Strictness::Lenient)]
+    strictness: Strictness,
+
+    /// Synonyms for the You/Them/Met/None values of last_reply, with synonym and last_reply columns, used when present
+    #[arg(long, global = true, value_name = "FILE", default_value = "last_reply_vocabulary.csv")]
+    last_reply_vocabulary: PathBuf,
+
+    /// How rows of the same person matched more than once are combined, keyed on the id column or otherwise the name
+    #[arg(long, global = true, value_enum, default_value_t = dedup::Strategy::Off)]
+    dedup: dedup::Strategy,
+
+    /// Decimal places of every rate and metric, in place of the places each table uses
+    #[arg(long, global = true)]
+    decimals: Option<usize>,
+
+    /// Character written between thousands of counts and metrics, e.g. ","
+    #[arg(long, global = true, value_name = "CHAR")]
+    thousands_separator: Option<char>,
+
+    /// Whether rates are written as percents or as ratios between 0 and 1
+    #[arg(long, global = true, value_enum, default_value_t = format::RateStyle::Percent)]
+    rate_style: format::RateStyle,
+
+    /// Follow every rate with the counts it is computed from, e.g. "date rate = 4 dates / 23 convos = 17.39%"
+    #[arg(long, global = true)]
+    explain: bool,
+
+    /// Estimate preferences with a Dirichlet-multinomial model and credible intervals instead of raw ratios
+    #[arg(long)]
+    bayesian: bool,
+
+    /// Total pseudo-matches in the Bayesian prior, spread across categories by population share
+    #[arg(long, default_value_t = 14.0)]
+    prior_strength: f64,
+
+    /// Test whether the racial skew of matches could arise by chance by drawing match sets of the same size from the
+    /// baseline
+    #[arg(long)]
+    bootstrap_test: bool,
+
+    /// Minimum matches for a category to be scored, 2 trims sparse samples and 0 includes all samples
+    #[arg(long, default_value_t = 2)]
+    sample_cutoff: u32,
+
+    /// Recompute the preference ranking for every cutoff from 0 to 5 and report whether it is stable
+    #[arg(long)]
+    cutoff_sensitivity: bool,
+
+    /// Formula used to score each category in the preference index
+    #[arg(long, value_enum, default_value_t = stats::IndexFormula::Normalized)]
+    index_formula: stats::IndexFormula,
+
+    /// Show how many matches each category would have under the population baseline next to the observed count
+    #[arg(long)]
+    expected: bool,
+
+    /// Report Simpson and Shannon diversity of the race distribution of matches, conversations and dates
+    #[arg(long)]
+    diversity: bool,
+
+    /// Print a single line of key=value pairs with the match count, rates and top racial preference instead of the report
+    #[arg(long)]
+    summary: bool,
+
+    /// Multiple-comparison correction applied to per-category p-values
+    #[arg(long, value_enum, default_value_t = stats::Correction::Holm)]
+    correction: stats::Correction,
+
+    /// Significance level that corrected p-values are compared against
+    #[arg(long, default_value_t = 0.05)]
+    alpha: f64,
+
+    /// Simulate match sets drawn from the population baseline and report where observed counts fall among them
+    #[arg(long)]
+    monte_carlo: bool,
+
+    /// Fit logistic regressions of conversation, ghosting and date outcomes on profile attributes
+    #[arg(long)]
+    regression: bool,
+
+    /// Ridge penalty on regression coefficients, keeps estimates finite when a group always or never has an outcome
+    #[arg(long, default_value_t = 0.1)]
+    ridge: f64,
+
+    /// Fit a decision tree and a random forest predicting dates and report feature importances
+    #[arg(long)]
+    feature_importance: bool,
+
+    /// Number of trees in the random forest
+    #[arg(long, default_value_t = 500)]
+    trees: usize,
+
+    /// Maximum depth of each decision tree
+    #[arg(long, default_value_t = 4)]
+    max_depth: usize,
+
+    /// Rank open conversations by the predicted probability that they die without a date
+    #[arg(long)]
+    ghosting_risk: bool,
+
+    /// Write the coefficients and splits of every model fit during the run to this JSON file
+    #[arg(long, value_name = "FILE")]
+    export_model: Option<PathBuf>,
+
+    /// Number of folds used to cross-validate predictive models, 0 or 1 skips cross-validation
+    #[arg(long, default_value_t = 5)]
+    cv_folds: usize,
+
+    /// CDC height distribution that match heights are compared against
+    #[arg(long, value_enum, default_value_t = height::HeightBaseline::Women)]
+    height_baseline: height::HeightBaseline,
+
+    /// Width in inches of each height bucket
+    #[arg(long, default_value_t = 2)]
+    height_bucket: u8,
+
+    /// Geography of the bundled or supplied attribute baselines
+    #[arg(long, default_value = "Chicago Metro Area")]
+    geography: String,
+
+    /// County race and ethnicity counts the racial preferences are measured against
+    #[arg(long, value_name = "FILE", default_value = "demographics.csv")]
+    baseline: PathBuf,
+
+    /// County race counts within the Hispanic population
+    #[arg(long, value_name = "FILE", default_value = "hispanic_demographics.csv")]
+    hispanic_baseline: PathBuf,
+
+    /// Counties of the race baselines by 5 digit FIPS code, e.g. 17031,17043, downloaded from the census in place of
+    /// --baseline and --hispanic-baseline
+    #[arg(long, global = true, value_delimiter = ',', value_name = "FIPS")]
+    counties: Vec<census::County>,
+
+    /// ZIP code whose surrounding counties make up the race baselines, in place of --counties
+    #[arg(long, global = true, conflicts_with = "counties")]
+    zip: Option<String>,
+
+    /// Distance from --zip within which counties with a ZIP code are included, e.g. 25mi or 40km
+    #[arg(long, global = true, default_value = "25mi")]
+    radius: zipcode::Radius,
+
+    /// ZIP code to county crosswalk with zip, county, latitude and longitude columns, the bundled Chicago area one when
+    /// left out
+    #[arg(long, global = true, value_name = "FILE")]
+    zip_crosswalk: Option<PathBuf>,
+
+    /// Where the race counts of --counties come from
+    #[arg(long, global = true, value_enum, default_value_t = census::Source::Census)]
+    baseline_source: census::Source,
+
+    /// Year of the 5-year census estimates
+    #[arg(long, global = true, default_value_t = 2022)]
+    vintage: u16,
+
+    /// Download census tables again even when they are cached
+    #[arg(long, global = true)]
+    refresh: bool,
+
+    /// Directory of cached downloads and bundled baselines, $XDG_CACHE_HOME/hinge-analyzer or ~/.cache/hinge-analyzer
+    /// when left out
+    #[arg(long, global = true, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+
+    /// Population by age bracket the age preferences are measured against
+    #[arg(long, value_name = "FILE", default_value = "age_demographics.csv")]
+    age_baseline: PathBuf,
+
+    /// Religious affiliation baseline with geography, category and population columns
+    #[arg(long, value_name = "FILE", default_value = "religion_demographics.csv")]
+    religion_baseline: PathBuf,
+
+    /// Educational attainment baseline with geography, category and population columns
+    #[arg(long, value_name = "FILE", default_value = "education_demographics.csv")]
+    education_baseline: PathBuf,
+
+    /// Run without the network using the baselines bundled into the binary in place of the baseline files and
+    /// --geography, and only census tables already cached. Needs the vendored-baselines feature
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Bundled baselines used with --offline
+    #[arg(long, global = true, value_enum, default_value_t = bundled::BaselineSet::Chicago)]
+    bundled_baselines: bundled::BaselineSet,
+
+    /// Slack, Discord or other webhook URL to post the headline numbers to after each analysis, e.g. a group chat's
+    #[arg(long, value_name = "URL")]
+    webhook: Option<String>,
+
+    /// Chart to attach to the webhook post, like the SVG of the map command. Only Discord webhooks take uploads
+    #[arg(long, value_name = "FILE", requires = "webhook")]
+    webhook_image: Option<PathBuf>,
+
+    /// Whether the values of the baseline files are population counts or percentages of each geography's population
+    #[arg(long, value_enum, default_value_t = baseline::Units::Auto)]
+    baseline_units: baseline::Units,
+
+    /// What to do with baseline categories that have no population on record
+    #[arg(long, value_enum, default_value_t = preference::ZeroPopulation::Exclude)]
+    zero_population: preference::ZeroPopulation,
+
+    /// Geography of the baselines used for national shares with --zero-population national
+    #[arg(long, default_value = "United States")]
+    national_geography: String,
+
+    /// Your own sign, splits the zodiac table into astrologically compatible and incompatible matches
+    #[arg(long, value_enum)]
+    my_sign: Option<zodiac::Sign>,
+
+    /// Funnel stages after a conversation starts, in order and including Met, e.g. "Number Exchanged,Met,Second Date".
+    /// The optional stage column of matches.csv names the furthest stage each match reached
+    #[arg(long, value_delimiter = ',', default_value = "Met")]
+    stages: Vec<String>,
+
+    /// Thresholds on the headline numbers checked after the analysis and every daemon run, e.g. "date_rate < 10%" or
+    /// "date_rate drops 5 points" against the previous snapshot. A tripped alert is highlighted and the run exits with
+    /// code 3
+    #[arg(long, value_delimiter = ',', value_name = "ALERT")]
+    alert: Vec<alert::Alert>,
+
+    /// Directory of the daemon's snapshots the alerts of an analysis compare against, the daemon uses its own
+    #[arg(long, value_name = "DIR")]
+    snapshots: Option<PathBuf>,
+
+    /// Also report the outcome metrics separately for every value of this attribute, e.g. race
+    #[arg(long, value_parser = attribute::parse_name)]
+    by: Option<String>,
+
+    /// Rhai script adding to the analysis and report, its derive(profile) returns fields derived from a profile whose
+    /// outcomes are reported like the built in attributes and its metrics(profiles) returns custom metrics
+    #[arg(long, value_name = "FILE")]
+    script: Option<PathBuf>,
+
+    /// WebAssembly plugins whose metrics are reported with the script's, given the profiles and the conversations of
+    /// --export. See src/plugin.rs for the interface and plugins/dates.wat for an example
+    #[arg(long, value_delimiter = ',', value_name = "FILE")]
+    plugin: Vec<PathBuf>,
+
+    /// Only print and report these metric computers, of ghosting, conversations, dates, funnel, preference_index and
+    /// any added by a program using the crate as a library
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    metrics: Vec<String>,
+
+    /// Metric computers to leave out of the analysis and report, e.g. dates
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    skip_metrics: Vec<String>,
+
+    /// Number of random resamples used by the simulation based tests
+    #[arg(long, default_value_t = 10_000, value_parser = clap::value_parser!(u32).range(1..))]
+    iterations: u32,
+
+    /// Seed for the random number generator, for reproducible simulation results
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+}
+
+// fn aggregate_racial_preferences(
+//     profiles: &[HingeProfile], 
+//     race_distribution: HashMap<Race, f64>,
+//     race_distribution_hispanic: HashMap<Race, f64>
+// ) -> Vec<RacialPreference> {
+
+//     let mut race_counts = HashMap::from_iter(Race::entries().map(|race| (race, 0)));
+
+//     let races = profiles.iter().filter_map(|profile| profile.race);
+//     let hispanic_races = profiles
+//         .iter()
+//         .filter(|profile| profile.ethnicity.bits() & Ethnicities::HISPANIC_LATINO != 0)
+//         .map(|profile| if profile.ethnicity.bits() == Ethnicities::HISPANIC_LATINO 
+//             { Ethnicities::OTHER } else 
+//             { profile.ethnicity.bits() & !Ethnicities::HISPANIC_LATINO })
+//         .filter_map(|ethnicity_bits| Race::try_from(ethnicity_bits).ok());
+
+    
+    
+    
+
+//     let total_profiles_with_bkg_info = profiles
+//         .iter()
+//         .filter(|profile| profile.race.is_some() || profile.ethnicity.bits() & Ethnicities::HISPANIC_LATINO != 0)
+//         .count();
+//     let total_profiles = profiles.len();
+
+//     hispanic_race_counts.remove(&Race::Hispanic);
+
+//     println!("Total Profiles: {}", total_profiles);
+//     println!("Total Profiles with Background Info: {}", total_profiles_with_bkg_info);
+//     println!("Race Counts: {:?}", race_counts);
+//     println!("Race (Hispanic) Breakdown: {:?}", hispanic_race_counts);
+
+//     race_counts.remove(&Race::Hispanic);
+
+//     println!("Race Index");
+//     for (race, count) in race_counts.iter() {
+//         if *count < SAMPLE_CUTOFF {
+//             println!("\t{:?}: {}", race, "NOT ENOUGH SAMPLES");
+//         } else {
+//             println!("\t{:?}: {}", race, *count as f64 / race_weights[race]);
+//         }
+//     }
+    
+//     println!("Hispanic Race Index");
+//     for (race, count) in hispanic_race_counts.iter() {
+//         if *count < SAMPLE_CUTOFF {
+//             println!("\t{:?}Hispanic: {}", race, "NOT ENOUGH SAMPLES");
+//         } else {
+//             println!("\t{:?}Hispanic: {}", race, *count as f64 / (race_weights[&Race::Hispanic] * hispanic_race_weights[race]));
+//         }
+//     }
+// }
+
+#[derive(Debug, Clone, Subcommand)]
+enum Command {
+    /// Contingency table of any two attributes with row and column percentages and a chi-square independence test
+    Crosstab {
+        /// Attribute down the rows, e.g. race
+        #[arg(value_parser = attribute::parse_name)]
+        row: String,
+
+        /// Attribute across the columns, e.g. convo
+        #[arg(value_parser = attribute::parse_name)]
+        column: String
+    },
+
+    /// Matrix of pairwise associations between every attribute in the data and the outcome flags
+    Correlations,
+
+    /// SVG map of where matches come from by their neighborhood, a name or ZIP code, with matches and dates per
+    /// neighborhood and county
+    Map {
+        /// File to write the map to
+        #[arg(long, default_value = "matches_map.svg")]
+        output: PathBuf,
+
+        /// Neighborhoods with name, county, county_name, latitude and longitude columns, the bundled Chicago ones when
+        /// left out
+        #[arg(long, value_name = "FILE")]
+        neighborhoods: Option<PathBuf>
+    },
+
+    /// Day of week by hour of day heatmap of messages from the export and the hours with the best reply rates
+    Heatmap,
+
+    /// Conversation and date conversion of matches grouped by the month they matched, with seasonality and trend
+    Cohorts,
+
+    /// Likes, matches, messages, conversations and dates per active week of the export
+    Weekly,
+
+    /// Active streaks and pauses in your app activity and how matches fare right after returning from a pause
+    Streaks {
+        /// Days without sending a like or message that count as a pause
+        #[arg(long, default_value_t = 14)]
+        pause_days: i64,
+
+        /// Days after returning from a pause that matches count as returning matches
+        #[arg(long, default_value_t = 14)]
+        return_days: i64
+    },
+
+    /// Trends your reply latency, message length and conversation initiation and flags significant declines
+    Burnout,
+
+    /// Days from match to first message, first message to plans and match to date for matches you met
+    Timing,
+
+    /// Kaplan-Meier survival curves of conversations, overall and by who sent the first message
+    Survival {
+        /// What a conversation's lifetime is measured in
+        #[arg(long, value_enum, default_value_t = survival::SurvivalTime::Messages)]
+        time: survival::SurvivalTime,
+
+        /// Days without a message before the end of the export after which a conversation counts as dead
+        #[arg(long, default_value_t = 14)]
+        alive_days: i64
+    },
+
+    /// Markov transition probabilities between you replying, them replying, stalling and meeting
+    Transitions {
+        /// Days without a message after which a conversation counts as stalled
+        #[arg(long, default_value_t = 3)]
+        stall_days: i64
+    },
+
+    /// How often messaging again without a reply revived a conversation versus preceded a ghost
+    DoubleTexts {
+        /// Hours after your last message before another one counts as a double text
+        #[arg(long, default_value_t = 1)]
+        gap_hours: i64
+    },
+
+    /// Your share of each conversation's messages and words against ghosting and dates
+    Balance,
+
+    /// Emoji used by each side and whether emoji early in a conversation go with it surviving
+    Emoji,
+
+    /// Most distinctive words and bigrams of conversations that led to a date versus ones that died, by TF-IDF
+    Topics {
+        /// Conversations a term has to appear in to be ranked
+        #[arg(long, default_value_t = 3)]
+        min_conversations: usize,
+
+        /// Terms listed for each group
+        #[arg(long, default_value_t = 15)]
+        top: usize
+    },
+
+    /// Which prompts and photos received likes were on and how the matches they brought converted
+    Prompts,
+
+    /// Statistics about your photos and videos and the received likes on each
+    Media,
+
+    /// Conversion of matches that engaged with voice prompts or voice notes against those that did not
+    Voice,
+
+    /// Whether matches fall inside the filters in your user.json and how matches similar to you convert
+    Context,
+
+    /// Monthly share of the likes you sent that became a match, and its trend
+    Conversion {
+        /// Date you changed your profile (YYYY-MM-DD), conversion is compared between changes, can be repeated
+        #[arg(long = "change")]
+        changes: Vec<chrono::NaiveDate>
+    },
+
+    /// Separate conversation and date funnels for matches you liked first and matches they liked first
+    Funnels,
+
+    /// Match volume, match quality and date conversion while subscribed to Hinge+ or HingeX against the rest
+    Subscription {
+        /// Dates you were subscribed as START..END (YYYY-MM-DD, inclusive), can be repeated
+        #[arg(long = "period", required = true)]
+        periods: Vec<timeline::Period>
+    },
+
+    /// Conversations worth picking back up, ranked by how often similar silences were broken before
+    Resurrect {
+        /// Days without a message after which a conversation counts as stalled
+        #[arg(long, default_value_t = 3)]
+        stall_days: i64,
+
+        /// Conversations silent for longer than this many days are left out
+        #[arg(long, default_value_t = 30)]
+        max_days: i64,
+
+        /// Conversations listed
+        #[arg(long, default_value_t = 20)]
+        top: usize
+    },
+
+    /// Combined report over several people's matches files with a column per person and pooled results
+    Compare {
+        /// A person's matches file as NAME=PATH, or NAME=PATH@GEOGRAPHY for baselines of another geography, repeated
+        /// for every person
+        #[arg(long = "user", required = true)]
+        users: Vec<multiuser::UserFile>,
+
+        /// Read each matches file a row at a time keeping only counts and a sample of ages, for files too large to
+        /// hold in memory. Can't be combined with --dedup
+        #[arg(long)]
+        streaming: bool
+    },
+
+    /// Write your outcome rates as an anonymized bundle for sharing, rates only and none from too few matches
+    Bundle {
+        /// File to write instead of standard output
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Fewest matches or conversations a rate can be computed from
+        #[arg(long, default_value_t = 20)]
+        floor: u32
+    },
+
+    /// Percentile of your outcome rates among anonymized benchmark bundles
+    Benchmark {
+        /// Benchmark file holding one bundle or a list of them, can be repeated
+        #[arg(long = "benchmarks", default_value = "benchmarks.json")]
+        benchmarks: Vec<PathBuf>,
+
+        /// Fewest matches or conversations a rate can be computed from
+        #[arg(long, default_value_t = 20)]
+        floor: u32
+    },
+
+    /// Write records from the analysis as CSV
+    Export {
+        #[command(subcommand)]
+        export: ExportCommand
+    },
+
+    /// Write the matches of the Hinge export as a matches.csv to annotate, numbered in the order you matched with
+    /// ethnicity unspecified and the profile columns blank
+    Convert {
+        /// File to write instead of standard output
+        #[arg(long)]
+        output: Option<PathBuf>
+    },
+
+    /// Download the race counts of the --counties, or those around --zip, from the American Community Survey through
+    /// --baseline-source as files to pass to --baseline and --hispanic-baseline. Downloads are cached so later runs
+    /// don't need the network
+    Census {
+        /// File to write the race counts to, in the format of demographics.csv
+        #[arg(long, default_value = "census_demographics.csv")]
+        output: PathBuf,
+
+        /// File to write the race counts within the Hispanic population to, in the format of hispanic_demographics.csv
+        #[arg(long, default_value = "census_hispanic_demographics.csv")]
+        hispanic_output: PathBuf
+    },
+
+    /// Write a report of the analysis through a Tera template, with the match outcomes and the preferences of every
+    /// attribute as its context. The bundled Markdown report is used when no template is given
+    Report {
+        /// Template controlling the layout, wording and sections of the report
+        #[arg(long)]
+        template: Option<PathBuf>,
+
+        /// File to write instead of standard output
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Addresses to email the report to, as HTML when the template is a .html file and as plain text otherwise,
+        /// there is no PDF output. The server and login are read from HINGE_ANALYZER_SMTP_URL,
+        /// HINGE_ANALYZER_SMTP_USERNAME, HINGE_ANALYZER_SMTP_PASSWORD and HINGE_ANALYZER_SMTP_FROM
+        #[arg(long, value_delimiter = ',', value_name = "ADDRESS")]
+        email_to: Vec<String>,
+
+        /// Subject of the emailed report
+        #[arg(long, default_value = "Hinge Report")]
+        email_subject: String
+    },
+
+    /// Write the JSON Schema of the report format used by the daemon's snapshots, the API and the C API, whose
+    /// report_version says which version of the format a report follows
+    Schema {
+        /// File to write instead of standard output
+        #[arg(long)]
+        output: Option<PathBuf>
+    },
+
+    /// Rerun the analysis on a schedule against the matches file of a data directory, saving every run as a JSON
+    /// snapshot under its snapshots directory and posting to --webhook and emailing --email-to after each
+    Daemon {
+        /// Time between runs, e.g. 7d, 12h or 30m
+        #[arg(long, default_value = "7d")]
+        interval: daemon::Interval,
+
+        /// Directory the --matches file is read from and snapshots are saved in
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        data_dir: PathBuf,
+
+        /// Runs before exiting, forever when left out
+        #[arg(long)]
+        runs: Option<u32>,
+
+        /// Template of the emailed report, the bundled Markdown report when left out
+        #[arg(long)]
+        template: Option<PathBuf>,
+
+        /// Addresses to email the report to after each run, see the report command
+        #[arg(long, value_delimiter = ',', value_name = "ADDRESS")]
+        email_to: Vec<String>,
+
+        /// Subject of the emailed report
+        #[arg(long, default_value = "Hinge Report")]
+        email_subject: String,
+
+        /// Address to serve the latest numbers on at /metrics for Prometheus to scrape, e.g. 127.0.0.1:9898
+        #[arg(long, value_name = "ADDRESS")]
+        metrics_address: Option<String>
+    },
+
+    /// Serve the analysis as JSON at /api/profiles, /api/preferences and /api/funnel and through GraphQL at /graphql for
+    /// frontends and scripts to query, each taking a filter like --filter and rerunning against the matches file on
+    /// every request, and its numbers at /metrics for Prometheus to scrape
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        address: String
+    },
+
+    /// Slice the matches interactively, narrowing them with filter, splitting them with by and printing outcomes and
+    /// funnels with show, without reading the matches file again between commands
+    Repl,
+
+    /// Compare the conversation funnel of the matches fitting a hypothetical behavior, e.g. "convo and last_reply !=
+    /// You", with the funnel of every match, to see how much behaving that way with every match might have mattered
+    WhatIf {
+        /// Matches to keep, in the syntax of --filter
+        #[arg(value_name = "EXPR")]
+        scenario: filter::Filter
+    },
+
+    /// Check a matches file for missing columns, invalid values, inconsistent outcomes and duplicate names, exiting
+    /// with an error when any are found
+    Validate {
+        /// Matches file to check, the --matches file when left out
+        path: Option<PathBuf>
+    },
+
+    /// Write a synthetic matches.csv for demos and testing, with races, religion, education and heights drawn from the
+    /// baselines
+    Generate {
+        /// Number of matches to generate
+        #[arg(long, default_value_t = 500)]
+        profiles: usize,
+
+        /// File to write instead of standard output
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Also write a Hinge export matches.json with chats and We Met answers agreeing with the generated matches
+        #[arg(long, value_name = "FILE")]
+        chats: Option<PathBuf>,
+
+        /// Share of matches that become a conversation
+        #[arg(long, default_value_t = 0.5)]
+        convo_rate: f64,
+
+        /// Share of conversations that become a date, for matches at the mean distance
+        #[arg(long, default_value_t = 0.15)]
+        date_rate: f64,
+
+        /// Share of optional profile fields left blank
+        #[arg(long, default_value_t = 0.1)]
+        missing_rate: f64,
+
+        /// Average age of matches
+        #[arg(long, default_value_t = 29.0)]
+        mean_age: f64,
+
+        /// Average distance of matches in miles
+        #[arg(long, default_value_t = 8.0)]
+        mean_distance: f64
+    }
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum ExportCommand {
+    /// Everyone you met with the dates you matched, met and last talked, from the Hinge export when --export is given
+    /// and otherwise the names in matches.csv
+    Dates {
+        /// File to write instead of standard output
+        #[arg(long)]
+        output: Option<PathBuf>
+    }
+}
+
+fn load_export(args: &Args) -> Result<export::Export, Box<dyn Error>> {
+    let path = args.export.as_ref().ok_or("--export is required for commands that use the Hinge data export")?;
+    export::load(path, args.utc_offset)
+}
+
+fn schema(args: &Args) -> ingest::Schema<'static> {
+    // the server and daemon read the matches again and again while the user may be editing them
+    let long_running = matches!(args.command, Some(Command::Serve { .. } | Command::Daemon { .. }));
+    ingest::Schema { required: &REQUIRED_COLUMNS, optional: &OPTIONAL_COLUMNS, fuzzy_headers: args.fuzzy_headers, map: !long_running }
+}
+
+fn profile_from_record(record: HingeProfileCSVRecord<'_>, strictness: Strictness, vocabulary: &vocabulary::Vocabulary) -> Result<HingeProfile, String> {
+    HingeProfile::try_from(record.check_flags(strictness)?.translate_last_reply(vocabulary)).map_err(String::from)
+}
+
+fn load_profiles(args: &Args) -> Result<Vec<HingeProfile>, Box<dyn Error>> {
+    load_profiles_from(&args.matches, args)
+}
+
+fn load_profiles_from(path: impl AsRef<std::path::Path>, args: &Args) -> Result<Vec<HingeProfile>, Box<dyn Error>> {
+    let vocabulary = vocabulary::load(&args.last_reply_vocabulary)?;
+    let profiles = ingest::ingest::<HingeProfileCSVRecord, _>(path, &schema(args), |record| profile_from_record(record, args.strictness, &vocabulary))?;
+    Ok(narrow_profiles(profiles, args))
+}
+
+// The profiles read with duplicates collapsed per --dedup and only those matching --filter kept
+fn narrow_profiles(profiles: Vec<HingeProfile>, args: &Args) -> Vec<HingeProfile> {
+    let filter = args.filter.as_ref();
+    let (profiles, collapsed) = dedup::dedup(profiles, args.dedup);
+    // --summary prints nothing but its line
+    if args.dedup != dedup::Strategy::Off && !args.summary {
+        println!("\nCollapsed {} duplicate rows of people matched more than once ({:?})", collapsed, args.dedup);
+    }
+
+    let profiles = profiles
+        .into_iter()
+        .filter(|profile| filter.is_none_or(|filter| filter.matches(profile)))
+        .collect::<Vec<_>>();
+
+    if let Some(filter) = filter.filter(|_| !args.summary) {
+        println!("\nFilter: {}", filter);
+    }
+
+    profiles
+}
+
+// Like load_profiles_from but hands each profile to `each` as it is read instead of collecting them. Duplicates can
+// only be found with every row at hand, so this refuses --dedup.
+fn stream_profiles_from(path: impl AsRef<std::path::Path>, args: &Args, mut each: impl FnMut(HingeProfile)) -> Result<(), Box<dyn Error>> {
+    if args.dedup != dedup::Strategy::Off {
+        return Err("--dedup needs every row at once and can't be used with --streaming".into());
+    }
+
+    let filter = args.filter.as_ref();
+    let vocabulary = vocabulary::load(&args.last_reply_vocabulary)?;
+    ingest::stream::<HingeProfileCSVRecord, _>(path, &schema(args), |record| profile_from_record(record, args.strictness, &vocabulary), |profile| {
+        if filter.is_none_or(|filter| filter.matches(&profile)) {
+            each(profile);
+        }
+    })?;
+
+    if let Some(filter) = filter.filter(|_| !args.summary) {
+        println!("\nFilter: {}", filter);
+    }
+
+    Ok(())
+}
+
+// Every user's matches tallied on their own and pooled, streamed a row at a time with --streaming
+fn compare(args: &Args, users: &[multiuser::UserFile], streaming: bool) -> Result<(), Box<dyn Error>> {
+    // each tally samples ages with its own generator so the sample of one user doesn't depend on the others
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut pooled = multiuser::Tally::new(seed);
+    let mut tallies = Vec::new();
+    for (index, user) in users.iter().enumerate() {
+        let mut tally = multiuser::Tally::new(seed.wrapping_add(index as u64 + 1));
+        let add = |profile: HingeProfile| {
+            tally.add(&profile);
+            pooled.add(&profile);
+        };
+        match streaming {
+            true => stream_profiles_from(&user.path, args, add)?,
+            false => load_profiles_from(&user.path, args)?.into_iter().for_each(add)
+        }
+        tallies.push((user.clone(), tally));
+    }
+
+    multiuser::print_comparison(&tallies, &pooled, &args.religion_baseline, args.baseline_units, &args.geography, args.index_formula, args.sample_cutoff)
+}
+
+type RaceWeights = BTreeMap<Race, f64>;
+
+// Population shares of each race and of each race within the Hispanic population, from the county demographics with
+// zero population cells handled per --zero-population
+fn load_race_weights(args: &Args) -> Result<(RaceWeights, RaceWeights), Box<dyn Error>> {
+    // Source: https://datausa.io/profile/geo/cook-county-il#race_and_ethnicity
+    // Source: https://datausa.io/profile/geo/dupage-county-il#race_and_ethnicity
+    // Source: https://www.census.gov/library/visualizations/interactive/exploring-age-groups-in-the-2020-census.html
+    let races = baseline::read_columns(&args.baseline, args.baseline_units)?;
+    let column = |columns: &[(String, f64)], path: &PathBuf, name: &str| {
+        columns
+            .iter()
+            .find(|(column, _)| column == name)
+            .map(|(_, population)| *population)
+            .ok_or_else(|| format!("No {} column in {}", name, path.display()))
+    };
+    let mut race_weights: BTreeMap<Race, f64> = BTreeMap::from([
+        (Race::WhiteCaucasian , column(&races, &args.baseline, "white_alone")?),
+        (Race::BlackAfrican   , column(&races, &args.baseline, "black_african_american_alone")?),
+        (Race::NativeAmerican , column(&races, &args.baseline, "american_indian_alaska_native_alone")?),
+        (Race::Asian          , column(&races, &args.baseline, "asian_alone")?),
+        (Race::PacificIslander, column(&races, &args.baseline, "native_hawaiian_pacific_islander_alone")?),
+        (Race::Multiracial    , column(&races, &args.baseline, "two_or_more_races")?),
+        (Race::Hispanic       , column(&races, &args.baseline, "hispanic_latino")?),
+        (Race::Other          , column(&races, &args.baseline, "some_other_race_alone")?)
+    ]);
+
+    let race_total_population = race_weights.values().sum::<f64>();
+    race_weights.values_mut().for_each(|weight| *weight /= race_total_population);
+    preference::fill_zero_population(
+        "Race",
+        race_weights.iter_mut().map(|(race, weight)| (race.to_string(), weight)).collect(),
+        &national_race_weights(&NATIONAL_RACE_WEIGHTS, args.zero_population)
+    );
+
+    let hispanic_races = baseline::read_columns(&args.hispanic_baseline, args.baseline_units)?;
+    let mut hispanic_race_weights: BTreeMap<Race, f64> = BTreeMap::from([
+        (Race::WhiteCaucasian , column(&hispanic_races, &args.hispanic_baseline, "white_hispanic")?),
+        (Race::BlackAfrican   , column(&hispanic_races, &args.hispanic_baseline, "black_african_american_hispanic")?),
+        (Race::NativeAmerican , column(&hispanic_races, &args.hispanic_baseline, "american_indian_alaska_native_hispanic")?),
+        (Race::Asian          , column(&hispanic_races, &args.hispanic_baseline, "asian_hispanic")?),
+        (Race::PacificIslander, column(&hispanic_races, &args.hispanic_baseline, "native_hawaiian_pacific_islander_hispanic")?),
+        (Race::Multiracial    , column(&hispanic_races, &args.hispanic_baseline, "two_or_more_races_hispanic")?),
+        (Race::Other          , column(&hispanic_races, &args.hispanic_baseline, "some_other_race_hispanic")?)
+    ]);
+
+    let hispanic_race_total_population = hispanic_race_weights.values().sum::<f64>();
+    hispanic_race_weights.values_mut().for_each(|weight| *weight /= hispanic_race_total_population);
+    preference::fill_zero_population(
+        "Hispanic Race",
+        hispanic_race_weights.iter_mut().filter(|(race, _)| **race != Race::Hispanic).map(|(race, weight)| (race.to_string(), weight)).collect(),
+        &national_race_weights(&NATIONAL_HISPANIC_RACE_WEIGHTS, args.zero_population)
+    );
+
+    Ok((race_weights, hispanic_race_weights))
+}
+
+fn run_analysis(args: &Args) -> Result<(), Box<dyn Error>> {
+    let funnel = funnel::Funnel::new(&args.stages)?;
+    let (race_weights, hispanic_race_weights) = load_race_weights(args)?;
+    println!("Race Weights: {:#?}", race_weights);
+    println!("Hispanic Race Weights: {:#?}", hispanic_race_weights);
+
+    let profiles = load_profiles(args)?;
+
+    let total_profiles_with_race_info = profiles
+        .iter()
+        .filter(|profile| profile.race.is_some())
+        .count();
+    let total_profiles = profiles.len();
+
+    println!("\nTotal Profiles: {}", total_profiles);
+    println!("Total Profiles with Race Information: {}", total_profiles_with_race_info);
+
+    let categories = racial_categories(profiles.iter(), &race_weights, &hispanic_race_weights);
+
+    if args.bayesian {
+        preference::print_bayesian_preferences("Race", &categories, args.prior_strength);
+    } else {
+        preference::print_preferences("Race", &categories, args.index_formula, args.sample_cutoff);
+    }
+
+    if args.cutoff_sensitivity {
+        const MAX_CUTOFF: u32 = 5;
+        const TOP_PREFERENCES: usize = 3;
+
+        // scores in category order, categories under the cutoff tie for last place
+        let scores_at = |sample_cutoff| {
+            let preferences = preference::preferences(&categories, args.index_formula, sample_cutoff);
+            let scores = categories
+                .iter()
+                .map(|category| preferences
+                    .iter()
+                    .find(|preference| preference.label == category.label)
+                    .and_then(|preference| preference.weight)
+                    .unwrap_or(f64::NEG_INFINITY))
+                .collect::<Vec<_>>();
+            let top = preferences
+                .into_iter()
+                .filter(|preference| preference.weight.is_some())
+                .take(TOP_PREFERENCES)
+                .map(|preference| preference.label)
+                .collect::<Vec<_>>();
+
+            (scores, top)
+        };
+
+        let (reference_scores, reference_top) = scores_at(args.sample_cutoff);
+        let mut unstable_cutoffs = Vec::new();
+
+        println!("\n\t         Sample Cutoff Sensitivity (Kendall's Tau of the Full Ranking vs Match Sample Cutoff={})", args.sample_cutoff);
+        println!("\tCutoff   Scored   Tau       Top Preference");
+        for sample_cutoff in 0..=MAX_CUTOFF {
+            let (scores, top) = scores_at(sample_cutoff);
+            if top != reference_top {
+                unstable_cutoffs.push(sample_cutoff.to_string());
+            }
+
+            println!("\t{:<6}   {:<6}   {:>6}    {}", 
+                sample_cutoff, 
+                scores.iter().filter(|score| score.is_finite()).count(), 
+                stats::kendall_tau(&reference_scores, &scores).map_or(String::from("n/a"), |tau| format!("{:+.3}", tau)), 
+                top.first().map_or("-", String::as_str));
+        }
+
+        if unstable_cutoffs.is_empty() {
+            println!("\tThe top {} preferences are {} for every cutoff from 0 to {}", TOP_PREFERENCES, "stable".green().bold(), MAX_CUTOFF);
+        } else {
+            println!("\tThe top {} preferences are {}, they differ from Cutoff={} at cutoffs {}", 
+                TOP_PREFERENCES, 
+                "unstable".red().bold(), 
+                args.sample_cutoff, 
+                unstable_cutoffs.join(", "));
+        }
+    }
+
+    if args.expected {
+        let counts = preference::counts(&categories);
+        let shares = preference::shares(&categories);
+        let expected = stats::expected_counts(&counts, &shares);
+
+        let mut rows = categories.iter().zip(expected).collect::<Vec<_>>();
+        rows.sort_by(|(a, a_expected), (b, b_expected)| (b.count as f64 - b_expected)
+            .total_cmp(&(a.count as f64 - a_expected))
+            .then_with(|| b.count.cmp(&a.count))
+            .then_with(|| a.label.cmp(&b.label)));
+
+        println!("\n\t         Expected vs Observed Matches (Population Baseline, {} Matches)", counts.iter().sum::<u32>());
+        println!("\t{:^55}   Expected   Observed   Difference", "Race");
+        for (category, expected) in rows {
+            println!("\t{:<55}   {:>8}   {:>8}   {:>+10.2}", category.to_string(), format::decimal(expected, 2), category.count, category.count as f64 - expected);
+        }
+    }
+
+    if args.diversity {
+        let population = preference::shares(&categories);
+        let groups = [
+            ("Matches", categories.clone()),
+            ("Conversations", racial_categories(profiles.iter().filter(|profile| profile.convo), &race_weights, &hispanic_race_weights)),
+            ("Dates", racial_categories(profiles.iter().filter(|profile| profile.who_last_replied == WhoLastReplied::Met), &race_weights, &hispanic_race_weights))
+        ];
+
+        println!("\n\t         Diversity of Race Distribution (Simpson = 1 - sum p^2, Shannon = -sum p ln p)");
+        println!("\t{:<15}   Profiles   Simpson   Shannon", "Group");
+        println!("\t{:<15}   {:<8}   {:.4}    {:.4}", "Population", "-", stats::simpson_diversity(&population), stats::shannon_entropy(&population));
+        for (group, categories) in groups {
+            let counts = categories.iter().map(|category| category.count as f64).collect::<Vec<_>>();
+            println!("\t{:<15}   {:<8}   {:.4}    {:.4}", group, counts.iter().sum::<f64>(), stats::simpson_diversity(&counts), stats::shannon_entropy(&counts));
+        }
+    }
+
+    if profiles.iter().any(|profile| profile.age.is_some()) {
+        // Source: https://data.census.gov/table/ACSST5Y2020.S0101?g=050XX00US17031,17043
+        let age_weights = age::load_age_weights(&args.age_baseline, args.baseline_units)?;
+        let age_categories = age::age_categories(profiles.iter(), &age_weights);
+
+        age::print_age_histogram(&age_categories);
+        preference::print_data_problems(&age_categories);
+        if args.bayesian {
+            preference::print_bayesian_preferences("Age", &age_categories, args.prior_strength);
+        } else {
+            preference::print_preferences("Age", &age_categories, args.index_formula, args.sample_cutoff);
+        }
+    } else {
+        println!("\nNo profiles have an age, skipping age preferences");
+    }
+
+    if profiles.iter().any(|profile| profile.height.is_some()) {
+        let heights = profiles.iter().filter_map(|profile| profile.height);
+        let height_buckets = height::height_buckets(heights, args.height_bucket, args.height_baseline);
+        let height_categories = height::height_categories(profiles.iter(), &height_buckets);
+
+        height::print_height_distribution(&profiles, &height_buckets, args.height_baseline);
+        preference::print_data_problems(&height_categories);
+        if args.bayesian {
+            preference::print_bayesian_preferences("Height", &height_categories, args.prior_strength);
+        } else {
+            preference::print_preferences("Height", &height_categories, args.index_formula, args.sample_cutoff);
+        }
+    } else {
+        println!("\nNo profiles have a height, skipping height analysis");
+    }
+
+    if profiles.iter().any(|profile| profile.religion.is_some()) {
+        // Source: https://www.pewresearch.org/religion/religious-landscape-study/ (2014), Christian excludes Catholic and
+        // Other folds in other faiths and nothing in particular
+        let mut religion_baseline = attribute::load_baseline(&args.religion_baseline, &args.geography, args.baseline_units)?;
+        preference::fill_zero_population(
+            "Religion",
+            religion_baseline.iter_mut().map(|(religion, share)| (religion.clone(), share)).collect(),
+            &national_baseline(args, &args.religion_baseline)?
+        );
+        let religion_rates = attribute::outcome_rates(profiles.iter(), |profile| profile.religion.clone());
+        let religion_categories = attribute::baseline_categories(&religion_rates, &religion_baseline);
+
+        attribute::print_outcome_rates("Religion", &religion_rates);
+        if args.bayesian {
+            preference::print_bayesian_preferences("Religion", &religion_categories, args.prior_strength);
+        } else {
+            preference::print_preferences("Religion", &religion_categories, args.index_formula, args.sample_cutoff);
+        }
+    } else {
+        println!("\nNo profiles have a religion, skipping religion analysis");
+    }
+
+    if profiles.iter().any(|profile| profile.education.is_some()) {
+        // Source: https://data.census.gov/table/ACSST1Y2022.S1501 (population 25 and over), High School covers
+        // everything short of a bachelor's degree
+        let mut education_baseline = attribute::load_baseline(&args.education_baseline, &args.geography, args.baseline_units)?;
+        preference::fill_zero_population(
+            "Education",
+            education_baseline.iter_mut().map(|(education, share)| (education.clone(), share)).collect(),
+            &national_baseline(args, &args.education_baseline)?
+        );
+        let education_rates = attribute::outcome_rates(profiles.iter(), |profile| profile.education.map(education::Education::label));
+        let education_categories = attribute::baseline_categories(&education_rates, &education_baseline);
+
+        attribute::print_outcome_rates("Education", &education_rates);
+        if args.bayesian {
+            preference::print_bayesian_preferences("Education", &education_categories, args.prior_strength);
+        } else {
+            preference::print_preferences("Education", &education_categories, args.index_formula, args.sample_cutoff);
+        }
+    } else {
+        println!("\nNo profiles have an education, skipping education analysis");
+    }
+
+    if profiles.iter().any(|profile| profile.job.is_some()) {
+        let industry_rates = attribute::outcome_rates(profiles.iter(), |profile| profile.industry().map(industry::Industry::label));
+        attribute::print_outcome_rates("Industry", &industry_rates);
+
+        let mut unclassified_jobs = profiles
+            .iter()
+            .filter(|profile| profile.industry() == Some(industry::Industry::Other))
+            .filter_map(|profile| profile.job.as_deref())
+            .collect::<Vec<_>>();
+        unclassified_jobs.sort_unstable();
+        unclassified_jobs.dedup();
+        if !unclassified_jobs.is_empty() {
+            println!("\tJobs Classified as Other: {}", unclassified_jobs.join(", "));
+        }
+    } else {
+        println!("\nNo profiles have a job, skipping industry analysis");
+    }
+
+    if profiles.iter().any(|profile| profile.politics.is_some()) {
+        let politics_rates = attribute::outcome_rates(profiles.iter(), |profile| profile.politics.map(politics::Politics::label));
+        attribute::print_outcome_rates("Politics", &politics_rates);
+
+        for &other in attribute::NAMES.iter().filter(|&&name| name != "politics") {
+            if profiles.iter().any(|profile| profile.politics.is_some() && attribute::value(profile, other).is_some()) {
+                attribute::print_crosstab(&profiles, "politics", other);
+            }
+        }
+    } else {
+        println!("\nNo profiles have a political view, skipping politics analysis");
+    }
+
+    for &column in lifestyle::COLUMNS {
+        if profiles.iter().any(|profile| attribute::value(profile, column).is_some()) {
+            let rates = attribute::outcome_rates(profiles.iter(), |profile| attribute::value(profile, column));
+            attribute::print_outcome_rates(&attribute::title(column), &rates);
+        }
+    }
+
+    if profiles.iter().any(|profile| profile.wants_kids.is_some()) {
+        let family_rates = attribute::outcome_rates(profiles.iter(), |profile| profile.wants_kids.map(family::FamilyPlans::label));
+        attribute::print_outcome_rates("Wants Kids", &family_rates);
+        attribute::print_conversation_outcomes(&profiles, "wants_kids");
+    } else {
+        println!("\nNo profiles have family plans, skipping family plans analysis");
+    }
+
+    if profiles.iter().any(|profile| profile.distance_miles.is_some()) {
+        distance::print_distance_distribution(&profiles);
+    }
+    if profiles.iter().any(|profile| profile.neighborhood.is_some()) {
+        let neighborhood_rates = attribute::outcome_rates(profiles.iter(), |profile| profile.neighborhood.clone());
+        attribute::print_outcome_rates("Neighborhood", &neighborhood_rates);
+        attribute::print_conversation_outcomes(&profiles, "neighborhood");
+    }
+    if profiles.iter().all(|profile| profile.distance_miles.is_none() && profile.neighborhood.is_none()) {
+        println!("\nNo profiles have a distance or neighborhood, skipping location analysis");
+    }
+
+    if profiles.iter().any(|profile| profile.zodiac.is_some()) {
+        zodiac::print_compatibility(&profiles, args.my_sign);
+    } else {
+        println!("\nNo profiles have a zodiac sign or birthday, skipping zodiac compatibility");
+    }
+
+    if profiles.iter().any(|profile| profile.date_rating.is_some()) {
+        rating::print_date_ratings(&profiles);
+    } else {
+        println!("\nNo profiles have a date rating, skipping date quality");
+    }
+
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => rand::make_rng()
+    };
+
+    if args.bootstrap_test {
+        let counts = preference::counts(&categories);
+        let shares = preference::shares(&categories);
+        let test = stats::bootstrap_test(&counts, &shares, args.iterations, &mut rng);
+
+        println!("\n\t         Parametric Bootstrap Test Against Population Baseline (Iterations={})", args.iterations);
+        println!("\tChi-square of {:.2} over {} matches, empirical p-value of {}", 
+            test.statistic, 
+            counts.iter().sum::<u32>(), 
+            format!("{:.4}", test.p_value).bold());
+        println!("\tCategories significant at alpha={} after {} correction are marked with *", args.alpha, args.correction);
+        println!("\t{:^55}   Matches   Expected   p-value   Adjusted", "Race");
+
+        let adjusted_p_values = stats::adjust_p_values(&test.category_p_values, args.correction);
+        let mut rows = categories
+            .iter()
+            .zip(test.expected.iter().zip(test.category_p_values.iter().zip(&adjusted_p_values)))
+            .collect::<Vec<_>>();
+        rows.sort_by(|(a_category, (_, (a, _))), (b_category, (_, (b, _)))| a
+            .total_cmp(b)
+            .then_with(|| b_category.count.cmp(&a_category.count))
+            .then_with(|| a_category.label.cmp(&b_category.label)));
+        for (category, (expected, (p_value, adjusted_p_value))) in rows {
+            let row = format!("{:<55}   {:<7}   {:>8.2}   {:.4}    {:.4}", category.to_string(), category.count, expected, p_value, adjusted_p_value);
+            if *adjusted_p_value <= args.alpha {
+                println!("\t{} *", row.bold());
+            } else {
+                println!("\t{}", row);
+            }
+        }
+    }
+
+    if args.monte_carlo {
+        let counts = preference::counts(&categories);
+        let shares = preference::shares(&categories);
+        let simulated = stats::simulate_null(&counts, &shares, args.iterations, &mut rng);
+
+        let mut rows = categories.iter().zip(simulated).collect::<Vec<_>>();
+        rows.sort_by(|(a_category, a), (b_category, b)| b
+            .percentile
+            .total_cmp(&a.percentile)
+            .then_with(|| b_category.count.cmp(&a_category.count))
+            .then_with(|| a_category.label.cmp(&b_category.label)));
+
+        println!("\n\t         Monte Carlo Null Model ({} Simulated Match Sets of {} Drawn From the Population Baseline)", args.iterations, counts.iter().sum::<u32>());
+        println!("\t{:^55}   Matches   Simulated Median   Simulated 95% Range   Percentile", "Race");
+        for (category, simulated) in rows {
+            println!("\t{:<55}   {:<7}   {:>16}   {:>19}   {:>10}", 
+                category.to_string(), 
+                category.count, 
+                simulated.median, 
+                format!("[{}, {}]", simulated.lower, simulated.upper), 
+                format::inline_percent(simulated.percentile / 100.0, 1));
+        }
+    }
+
+    let mut model_export = models::ModelExport::new(args.sample_cutoff, args.seed);
+    model_export.geographies = race_baseline_geographies(&args.baseline)?;
+
+    if args.regression {
+        let (features, reference_race) = models::profile_features(profiles.iter(), args.sample_cutoff);
+        let feature_names = features.iter().map(|feature| feature.name.clone()).collect::<Vec<_>>();
+        let rows = models::design_matrix(profiles.iter(), &features);
+
+        for outcome in models::Outcome::entries() {
+            let outcomes = profiles.iter().map(|profile| outcome.observed(profile)).collect::<Vec<_>>();
+            let model = models::logistic::fit(&rows, &outcomes, &feature_names, args.ridge);
+
+            println!("\n\t         Logistic Regression of P({}) (n={}, Events={}, Ridge={}, Reference Race={}{})", 
+                outcome, 
+                model.observations, 
+                model.events, 
+                args.ridge, 
+                reference_race.map_or(String::from("None"), |race| race.to_string()),
+                if model.converged { "" } else { ", DID NOT CONVERGE" });
+            println!("\t{:<45}   Coefficient   Std. Error   z         p-value   Odds Ratio", "Term");
+            for term in model.terms.iter() {
+                println!("\t{}", term);
+            }
+
+            model_export.models.push(models::ExportedModel {
+                name: format!("logistic_{}", outcome.key()),
+                outcome,
+                population: String::from("matches"),
+                observations: model.observations,
+                features: feature_names.clone(),
+                model: models::FittedModel::Logistic { ridge: args.ridge, converged: model.converged, terms: model.terms.clone() }
+            });
+
+            if args.cv_folds > 1 {
+                let validation = models::validation::cross_validate(&rows, &outcomes, args.cv_folds, &mut rng, |train_rows, train_outcomes, test_rows, _| {
+                    let model = models::logistic::fit(train_rows, train_outcomes, &feature_names, args.ridge);
+                    test_rows.iter().map(|row| model.predict(row)).collect()
+                });
+                println!("\t{}", validation);
+            }
+        }
+    }
+
+    if args.feature_importance {
+        let (features, _) = models::profile_features(profiles.iter(), args.sample_cutoff);
+        let feature_names = features.iter().map(|feature| feature.name.clone()).collect::<Vec<_>>();
+        let rows = models::design_matrix(profiles.iter(), &features);
+        let outcomes = profiles.iter().map(|profile| models::Outcome::Date.observed(profile)).collect::<Vec<_>>();
+
+        let mut parameters = models::tree::TreeParameters { max_depth: args.max_depth, min_samples_leaf: 5, max_features: None };
+        let mut tree_importances = vec![0.0; features.len()];
+        let samples = (0..rows.len()).collect::<Vec<_>>();
+        let tree = models::tree::fit_tree(&rows, &outcomes, &samples, parameters, 0, &mut tree_importances, &mut rng);
+
+        println!("\n\t         Decision Tree for P({}) (Max Depth={}, Min Samples per Leaf={})", models::Outcome::Date, parameters.max_depth, parameters.min_samples_leaf);
+        let mut lines = Vec::new();
+        tree.describe(&feature_names, 0, &mut lines);
+        for line in lines {
+            println!("\t{}", line);
+        }
+
+        model_export.models.push(models::ExportedModel {
+            name: String::from("decision_tree_date"),
+            outcome: models::Outcome::Date,
+            population: String::from("matches"),
+            observations: rows.len(),
+            features: feature_names.clone(),
+            model: models::FittedModel::DecisionTree { parameters, root: tree }
+        });
+
+        parameters.max_features = Some((features.len() as f64).sqrt().ceil() as usize);
+        let forest = models::tree::fit_forest(&rows, &outcomes, args.trees, parameters, &mut rng);
+        model_export.models.push(models::ExportedModel {
+            name: String::from("random_forest_date"),
+            outcome: models::Outcome::Date,
+            population: String::from("matches"),
+            observations: rows.len(),
+            features: feature_names.clone(),
+            model: models::FittedModel::RandomForest { parameters, importances: forest.importances.clone(), trees: forest.trees.clone() }
+        });
+
+        let mut importances = feature_names.iter().zip(&forest.importances).collect::<Vec<_>>();
+        importances.sort_by(|(a_name, a), (b_name, b)| b.total_cmp(a).then_with(|| a_name.cmp(b_name)));
+
+        println!("\n\t         Random Forest Feature Importance for P({}) (Trees={}, Mean Decrease in Gini Impurity)", models::Outcome::Date, forest.trees.len());
+        println!("\t{:<45}   Importance", "Feature");
+        for (name, importance) in importances {
+            println!("\t{:<45}   {:.4}       {}", name, importance, "#".repeat((importance * 50.0).round() as usize));
+        }
+
+        if args.cv_folds > 1 {
+            let tree_validation = models::validation::cross_validate(&rows, &outcomes, args.cv_folds, &mut rng, |train_rows, train_outcomes, test_rows, rng| {
+                let samples = (0..train_rows.len()).collect::<Vec<_>>();
+                let mut parameters = parameters;
+                parameters.max_features = None;
+                let tree = models::tree::fit_tree(train_rows, train_outcomes, &samples, parameters, 0, &mut vec![0.0; features.len()], rng);
+                test_rows.iter().map(|row| tree.predict(row)).collect()
+            });
+            let forest_validation = models::validation::cross_validate(&rows, &outcomes, args.cv_folds, &mut rng, |train_rows, train_outcomes, test_rows, rng| {
+                let forest = models::tree::fit_forest(train_rows, train_outcomes, args.trees, parameters, rng);
+                test_rows.iter().map(|row| forest.predict(row)).collect()
+            });
+
+            println!("\n\tDecision Tree {}", tree_validation);
+            println!("\tRandom Forest {}", forest_validation);
+        }
+    }
+
+    if args.ghosting_risk {
+        let conversations = profiles.iter().filter(|profile| profile.convo).collect::<Vec<_>>();
+        let (features, _) = models::profile_features(conversations.iter().copied(), args.sample_cutoff);
+        let feature_names = features.iter().map(|feature| feature.name.clone()).collect::<Vec<_>>();
+        let rows = models::design_matrix(conversations.iter().copied(), &features);
+        let outcomes = conversations.iter().map(|profile| models::Outcome::Date.observed(profile)).collect::<Vec<_>>();
+        let model = models::logistic::fit(&rows, &outcomes, &feature_names, args.ridge);
+        model_export.models.push(models::ExportedModel {
+            name: String::from("logistic_date_given_conversation"),
+            outcome: models::Outcome::Date,
+            population: String::from("conversations"),
+            observations: model.observations,
+            features: feature_names.clone(),
+            model: models::FittedModel::Logistic { ridge: args.ridge, converged: model.converged, terms: model.terms.clone() }
+        });
+
+        // a conversation is still open until it turns into a date
+        let mut open_conversations = conversations
+            .iter()
+            .zip(&rows)
+            .filter(|(profile, _)| profile.who_last_replied != WhoLastReplied::Met)
+            .map(|(profile, row)| (profile, 1.0 - model.predict(row)))
+            .collect::<Vec<_>>();
+        open_conversations.sort_by(|(a_profile, a), (b_profile, b)| b.total_cmp(a).then_with(|| a_profile.name.cmp(&b_profile.name)));
+
+        println!("\n\t         Ghosting Risk of Open Conversations (1 - P(Date | Conversation), Logistic Regression on {} Conversations)", conversations.len());
+        if args.cv_folds > 1 {
+            let validation = models::validation::cross_validate(&rows, &outcomes, args.cv_folds, &mut rng, |train_rows, train_outcomes, test_rows, _| {
+                let model = models::logistic::fit(train_rows, train_outcomes, &feature_names, args.ridge);
+                test_rows.iter().map(|row| model.predict(row)).collect()
+            });
+            println!("\t{}", validation);
+        }
+        println!("\t{:<4}   {:<25}   {:<40}   Last Reply   Risk", "Rank", "Name", "Race");
+        for (rank, (profile, risk)) in open_conversations.iter().enumerate() {
+            println!("\t{:<4}   {:<25}   {:<40}   {:<10}   {:.4}", 
+                rank + 1, 
+                profile.name, 
+                profile.race.map_or(String::from("Unknown"), |race| race.to_string()), 
+                format!("{:?}", profile.who_last_replied), 
+                risk);
+        }
+    }
+
+    if let Some(path) = &args.export_model {
+        serde_json::to_writer_pretty(File::create(path)?, &model_export)?;
+        println!("\nExported {} fitted models to {}", model_export.models.len(), path.display());
+    }
+
+    let computers = metric::Registry::built_in(funnel, preference_index(args, race_weights, hispanic_race_weights));
+    let computers = computers.enabled(&args.metrics, &args.skip_metrics)?;
+    metric::print(&computers, &metric::Matches::new(profiles.iter()))?;
+
+    if let Some(by) = &args.by {
+        let values = profiles.iter().map(|profile| attribute::value(profile, by)).collect::<Vec<_>>();
+        for label in attribute::labels_by_count(values.iter().flatten()) {
+            let stratum = profiles
+                .iter()
+                .zip(&values)
+                .filter(|(_, value)| value.as_ref() == Some(&label))
+                .map(|(profile, _)| profile);
+            println!("\n\t         {} = {} ({} Profiles)", attribute::title(by), label, stratum.clone().count());
+            metric::print(&computers, &metric::Matches::new(stratum))?;
+        }
+    }
+
+    let additions = run_additions(args, &profiles)?;
+    for (field, values) in &additions.fields {
+        attribute::print_outcome_rates(&attribute::title(field), &script::outcome_rates(&profiles, values));
+    }
+    if !additions.metrics.is_empty() {
+        println!("\n\t         Custom Metrics");
+        for (name, value) in &additions.metrics {
+            println!("\t{:<25} {}", attribute::title(name), value);
+        }
+    }
+
+    Ok(())
+}
+
+fn write_dates(args: &Args, output: Option<&PathBuf>) -> Result<(), Box<dyn Error>> {
+    let export = args.export.as_ref().map(|_| load_export(args)).transpose()?;
+    let profiles = if export.is_none() { load_profiles(args)? } else { Vec::new() };
+    match output {
+        Some(path) => contacts::write_dates(export.as_ref(), &profiles, File::create(path)?),
+        None => contacts::write_dates(export.as_ref(), &profiles, std::io::stdout())
+    }
+}
+
+fn census_cache(args: &Args) -> census::Cache {
+    census::Cache {
+        directory: args.cache_dir.clone().unwrap_or_else(census::Cache::default_directory),
+        refresh: args.refresh,
+        offline: args.offline
+    }
+}
+
+// Race counts of every --counties county from --baseline-source in the columns of demographics.csv and
+// hispanic_demographics.csv, returning the geographies written. Hispanic counts cover every race while the others are
+// of the non-Hispanic population.
+fn write_census_baselines(args: &Args, output: &PathBuf, hispanic_output: &PathBuf) -> Result<Vec<String>, Box<dyn Error>> {
+    if args.counties.is_empty() {
+        return Err("No counties to download, pass their FIPS codes with --counties or a ZIP code with --zip".into());
+    }
+
+    let cache = census_cache(args);
+    let mut races = csv::Writer::from_path(output)?;
+    let mut hispanic_races = csv::Writer::from_path(hispanic_output)?;
+    let mut geographies = Vec::new();
+    for county in &args.counties {
+        let counts = args.baseline_source.race_counts(county, args.vintage, &cache)?;
+        let [white, black, native, asian, pacific, other, multiracial] = counts.non_hispanic;
+        races.serialize(CountyDemographicsCSVRecord {
+            county: counts.geography.clone(),
+            white_alone: white,
+            black_african_american_alone: black,
+            american_indian_alaska_native_alone: native,
+            asian_alone: asian,
+            native_hawaiian_pacific_islander_alone: pacific,
+            some_other_race_alone: other,
+            two_or_more_races: multiracial,
+            hispanic_latino: counts.hispanic.iter().sum()
+        })?;
+        let [white, black, native, asian, pacific, other, multiracial] = counts.hispanic;
+        hispanic_races.serialize(CountyHispanicDemographicsCSVRecord {
+            county: counts.geography.clone(),
+            white_hispanic: white,
+            black_african_american_hispanic: black,
+            american_indian_alaska_native_hispanic: native,
+            asian_hispanic: asian,
+            native_hawaiian_pacific_islander_hispanic: pacific,
+            some_other_race_hispanic: other,
+            two_or_more_races_hispanic: multiracial
+        })?;
+        geographies.push(counts.geography);
+    }
+    races.flush()?;
+    hispanic_races.flush()?;
+    Ok(geographies)
+}
+
+// Points the race baselines at the counts of --counties written out to the cache directory
+fn use_census_baselines(args: &mut Args) -> Result<(), Box<dyn Error>> {
+    let directory = census_cache(args).directory.join("baselines").join(format!(
+        "counties-{}-{}-{}",
+        args.baseline_source.name(),
+        args.vintage,
+        args.counties.iter().map(ToString::to_string).collect::<Vec<_>>().join("-")
+    ));
+    std::fs::create_dir_all(&directory)?;
+
+    let (race, hispanic) = (directory.join("demographics.csv"), directory.join("hispanic_demographics.csv"));
+    let geographies = write_census_baselines(args, &race, &hispanic)?;
+    if !args.summary {
+        println!("Race Baseline: {} ({} 5-year estimates)", geographies.join(", "), args.vintage);
+    }
+
+    args.baseline = race;
+    args.hispanic_baseline = hispanic;
+    Ok(())
+}
+
+// The counties of a race baseline in the order of the file
+fn race_baseline_geographies(path: &PathBuf) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let Some(county) = reader.headers()?.iter().position(|column| column == "county") else {
+        return Ok(Vec::new());
+    };
+    Ok(reader.records().filter_map(Result::ok).filter_map(|record| record.get(county).map(str::to_string)).collect())
+}
+
+fn validate(args: &Args, path: &PathBuf) -> Result<(), Box<dyn Error>> {
+    let vocabulary = vocabulary::load(&args.last_reply_vocabulary)?;
+    let problems = ingest::validate::<HingeProfileCSVRecord, _>(path, &schema(args), &["id", "profile_id", "name"], |record| profile_from_record(record, args.strictness, &vocabulary))?;
+
+    println!("\n\t         Problems in {} ({} Found)", path.display(), problems.len());
+    for problem in &problems {
+        println!("\tLine {:<6} {}\n\t            {}", problem.line, problem.reason, problem.original);
+    }
+
+    if problems.is_empty() {
+        println!("\t{}", "No problems found".green().bold());
+        Ok(())
+    } else {
+        Err(format!("{} problems found in {}", problems.len(), path.display()).into())
+    }
+}
+
+fn write_generated(
+    args: &Args,
+    profiles: usize,
+    distributions: &generate::Distributions,
+    output: Option<&PathBuf>,
+    chats: Option<&PathBuf>
+) -> Result<(), Box<dyn Error>> {
+    let religion_baseline = attribute::load_baseline(&args.religion_baseline, &args.geography, args.baseline_units)?;
+    let education_baseline = attribute::load_baseline(&args.education_baseline, &args.geography, args.baseline_units)?;
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => rand::make_rng()
+    };
+
+    let output: Box<dyn std::io::Write> = match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(std::io::stdout())
+    };
+    let chats = chats.map(File::create).transpose()?;
+    generate::generate(profiles, distributions, &religion_baseline, &education_baseline, &mut rng, output, chats)
+}
+
+// The headline numbers of the analysis
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Summary {
+    total_matches: usize,
+    conversation_rate: f64,
+    date_rate: f64,
+    top_preference: Option<String>
+}
+
+fn summarize(args: &Args) -> Result<Summary, Box<dyn Error>> {
+    let (race_weights, hispanic_race_weights) = load_race_weights(args)?;
+    let profiles = load_profiles(args)?;
+    let categories = racial_categories(profiles.iter(), &race_weights, &hispanic_race_weights);
+    let top_preference = if args.bayesian {
+        preference::bayesian_preferences(&categories, args.prior_strength).into_iter().next().map(|preference| preference.label)
+    } else {
+        preference::preferences(&categories, args.index_formula, args.sample_cutoff)
+            .into_iter()
+            .find(|preference| preference.weight.is_some())
+            .map(|preference| preference.label)
+    };
+
+    let total_matches = profiles.len();
+    let rate = |count: usize| count as f64 / total_matches.max(1) as f64;
+    Ok(Summary {
+        total_matches,
+        conversation_rate: rate(profiles.iter().filter(|profile| profile.convo).count()),
+        date_rate: rate(profiles.iter().filter(|profile| profile.who_last_replied == WhoLastReplied::Met).count()),
+        top_preference
+    })
+}
+
+impl From<&report::Report> for Summary {
+    fn from(report: &report::Report) -> Self {
+        let race = report.sections.get("race").map_or(&[][..], |section| &section.preferences);
+        Summary {
+            total_matches: report.total_matches,
+            conversation_rate: report.conversation_rate,
+            date_rate: report.date_rate,
+            top_preference: race.iter().find(|preference| preference.index.is_some()).map(|preference| preference.label.clone())
+        }
+    }
+}
+
+// The headline numbers of the analysis on one line for shell scripts and status bars. Values ignore the number format
+// options so the line always parses the same way.
+fn print_summary_line(args: &Args) -> Result<(), Box<dyn Error>> {
+    let summary = summarize(args)?;
+    println!(
+        "total_matches={} convo_rate={:.4} date_rate={:.4} top_preference={:?}",
+        summary.total_matches,
+        summary.conversation_rate,
+        summary.date_rate,
+        summary.top_preference.unwrap_or_default()
+    );
+    Ok(())
+}
+
+// Posts the headline numbers to --webhook for sharing with friends
+fn post_summary(args: &Args, url: &str, summary: Summary) -> Result<(), Box<dyn Error>> {
+    if args.offline {
+        return Err("Can't post to --webhook with --offline".into());
+    }
+
+    let mut message = format!(
+        "Hinge stats: {} matches, {} convo rate, {} date rate",
+        format::count(summary.total_matches),
+        format::inline_percent(summary.conversation_rate, 1),
+        format::inline_percent(summary.date_rate, 1)
+    );
+    if let Some(top_preference) = summary.top_preference {
+        message.push_str(&format!("\nTop preference: {}", top_preference));
+    }
+    webhook::post(url, &message, args.webhook_image.as_deref())?;
+    if !args.summary {
+        println!("\nPosted the summary to the webhook");
+    }
+    Ok(())
+}
+
+// Name of an option's value as written on the command line
+fn value_name(value: impl clap::ValueEnum) -> String {
+    value.to_possible_value().map_or(String::new(), |value| value.get_name().to_string())
+}
+
+// The inputs and options behind a report, see report::Provenance
+fn provenance(args: &Args) -> report::Provenance {
+    let inputs = [
+        (String::from("matches"), &args.matches),
+        (String::from("baseline"), &args.baseline),
+        (String::from("hispanic_baseline"), &args.hispanic_baseline),
+        (String::from("age_baseline"), &args.age_baseline),
+        (String::from("religion_baseline"), &args.religion_baseline),
+        (String::from("education_baseline"), &args.education_baseline),
+        (String::from("last_reply_vocabulary"), &args.last_reply_vocabulary)
+    ];
+    let inputs = inputs.into_iter().chain(args.script.iter().map(|path| (String::from("script"), path)));
+    let inputs = inputs.chain(args.plugin.iter().map(|path| (format!("plugin {}", path.file_stem().unwrap_or_default().to_string_lossy()), path)));
+    report::Provenance {
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        inputs: inputs.map(|(option, path)| (option, report::Input::new(path))).collect(),
+        geography: args.geography.clone(),
+        national_geography: args.national_geography.clone(),
+        counties: args.counties.iter().map(ToString::to_string).collect(),
+        baseline_source: (!args.counties.is_empty()).then(|| format!("{} {}", args.baseline_source.name(), args.vintage)),
+        bundled_baselines: args.offline.then(|| value_name(args.bundled_baselines)),
+        baseline_units: value_name(args.baseline_units),
+        zero_population: value_name(args.zero_population),
+        filter: args.filter.as_ref().map(ToString::to_string),
+        strictness: value_name(args.strictness),
+        dedup: value_name(args.dedup),
+        sample_cutoff: args.sample_cutoff,
+        index_formula: value_name(args.index_formula),
+        prior_strength: args.bayesian.then_some(args.prior_strength),
+        height_baseline: value_name(args.height_baseline),
+        height_bucket: args.height_bucket,
+        stages: args.stages.clone()
+    }
+}
+
+// What --script and --plugin add to the analysis of the profiles, nothing without either
+fn run_additions(args: &Args, profiles: &[HingeProfile]) -> Result<script::Additions, Box<dyn Error>> {
+    let mut additions = match &args.script {
+        Some(path) => script::Script::load(path)?.run(profiles)?,
+        None => script::Additions::default()
+    };
+    if args.plugin.is_empty() {
+        return Ok(additions);
+    }
+
+    let export = args.export.as_ref().map(|_| load_export(args)).transpose()?;
+    let input = plugin::input(profiles, export.as_ref())?;
+    for path in &args.plugin {
+        for (name, value) in plugin::Plugin::load(path)?.metrics(&input)? {
+            if additions.metrics.insert(name.clone(), value).is_some() {
+                return Err(format!("The plugin {} reports {}, which the script or another plugin already does", path.display(), name).into());
+            }
+        }
+    }
+    Ok(additions)
+}
+
+// Preferences of the categories per --bayesian, with the outcomes of each value when the attribute has them
+fn report_section(args: &Args, title: &str, categories: &[preference::Category], outcomes: &[attribute::OutcomeRates]) -> report::Section {
+    let preferences = if args.bayesian {
+        preference::bayesian_preferences(categories, args.prior_strength).into_iter().map(report::PreferenceRow::from).collect()
+    } else {
+        preference::preferences(categories, args.index_formula, args.sample_cutoff).into_iter().map(report::PreferenceRow::from).collect()
+    };
+    report::Section { title: title.to_string(), preferences, outcomes: outcomes.iter().map(report::OutcomeRow::from).collect() }
+}
+
+// The race preference index metric computer with the options of the run
+fn preference_index(args: &Args, race_weights: RaceWeights, hispanic_race_weights: RaceWeights) -> metric::PreferenceIndex {
+    metric::PreferenceIndex {
+        race_weights,
+        hispanic_race_weights,
+        index_formula: args.index_formula,
+        sample_cutoff: args.sample_cutoff,
+        prior_strength: args.bayesian.then_some(args.prior_strength)
+    }
+}
+
+// The numbers of the analysis a report template can use, attributes no profile has are left out
+fn build_report(args: &Args) -> Result<report::Report, Box<dyn Error>> {
+    build_report_of(args, load_profiles(args)?)
+}
+
+// The report of profiles already read, like those the C library is handed in memory
+fn build_report_of(args: &Args, profiles: Vec<HingeProfile>) -> Result<report::Report, Box<dyn Error>> {
+    let funnel = funnel::Funnel::new(&args.stages)?;
+    let (race_weights, hispanic_race_weights) = load_race_weights(args)?;
+
+    let mut sections = BTreeMap::new();
+    let categories = racial_categories(profiles.iter(), &race_weights, &hispanic_race_weights);
+    sections.insert("race".to_string(), report_section(args, "Race", &categories, &[]));
+
+    if profiles.iter().any(|profile| profile.age.is_some()) {
+        let age_weights = age::load_age_weights(&args.age_baseline, args.baseline_units)?;
+        sections.insert("age".to_string(), report_section(args, "Age", &age::age_categories(profiles.iter(), &age_weights), &[]));
+    }
+
+    if profiles.iter().any(|profile| profile.height.is_some()) {
+        let heights = profiles.iter().filter_map(|profile| profile.height);
+        let height_buckets = height::height_buckets(heights, args.height_bucket, args.height_baseline);
+        sections.insert("height".to_string(), report_section(args, "Height", &height::height_categories(profiles.iter(), &height_buckets), &[]));
+    }
+
+    if profiles.iter().any(|profile| profile.religion.is_some()) {
+        let mut religion_baseline = attribute::load_baseline(&args.religion_baseline, &args.geography, args.baseline_units)?;
+        preference::fill_zero_population(
+            "Religion",
+            religion_baseline.iter_mut().map(|(religion, share)| (religion.clone(), share)).collect(),
+            &national_baseline(args, &args.religion_baseline)?
+        );
+        let religion_rates = attribute::outcome_rates(profiles.iter(), |profile| profile.religion.clone());
+        let religion_categories = attribute::baseline_categories(&religion_rates, &religion_baseline);
+        sections.insert("religion".to_string(), report_section(args, "Religion", &religion_categories, &religion_rates));
+    }
+
+    if profiles.iter().any(|profile| profile.education.is_some()) {
+        let mut education_baseline = attribute::load_baseline(&args.education_baseline, &args.geography, args.baseline_units)?;
+        preference::fill_zero_population(
+            "Education",
+            education_baseline.iter_mut().map(|(education, share)| (education.clone(), share)).collect(),
+            &national_baseline(args, &args.education_baseline)?
+        );
+        let education_rates = attribute::outcome_rates(profiles.iter(), |profile| profile.education.map(education::Education::label));
+        let education_categories = attribute::baseline_categories(&education_rates, &education_baseline);
+        sections.insert("education".to_string(), report_section(args, "Education", &education_categories, &education_rates));
+    }
+
+    let additions = run_additions(args, &profiles)?;
+    for (field, values) in &additions.fields {
+        let outcomes = script::outcome_rates(&profiles, values);
+        sections.insert(field.clone(), report::Section { title: attribute::title(field), preferences: Vec::new(), outcomes: outcomes.iter().map(report::OutcomeRow::from).collect() });
+    }
+
+    let computers = metric::Registry::built_in(funnel.clone(), preference_index(args, race_weights, hispanic_race_weights));
+    let computers = computers.enabled(&args.metrics, &args.skip_metrics)?;
+
+    let total_matches = profiles.len();
+    let conversations = profiles.iter().filter(|profile| profile.convo).count();
+    let dates = profiles.iter().filter(|profile| profile.who_last_replied == WhoLastReplied::Met).count();
+    Ok(report::Report {
+        report_version: report::REPORT_VERSION,
+        generated: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        matches_file: args.matches.display().to_string(),
+        total_matches,
+        conversations,
+        dates,
+        conversation_rate: conversations as f64 / total_matches.max(1) as f64,
+        date_rate: dates as f64 / total_matches.max(1) as f64,
+        bayesian: args.bayesian,
+        funnel: funnel::stage_counts(profiles.iter(), &funnel)
+            .into_iter()
+            .map(|(name, profiles)| report::FunnelStage { name, profiles })
+            .collect(),
+        sections,
+        metrics: metric::compute(&computers, &metric::Matches::new(profiles.iter()))?,
+        custom_metrics: additions.metrics,
+        provenance: provenance(args)
+    })
+}
+
+// The SMTP server and login the report is emailed through when there is anyone to email it to, read before the
+// analysis so a missing login fails fast
+fn smtp(args: &Args, email_to: &[String]) -> Result<Option<email::Smtp>, Box<dyn Error>> {
+    if !email_to.is_empty() && args.offline {
+        return Err("Can't email the report with --offline".into());
+    }
+    (!email_to.is_empty()).then(email::Smtp::from_env).transpose()
+}
+
+// Writes the report to the output, emails it through the server when there is one, or both, printing it when it goes
+// to neither
+fn write_report(
+    report: &report::Report,
+    template: Option<&PathBuf>,
+    output: Option<&PathBuf>,
+    smtp: Option<&email::Smtp>,
+    email_to: &[String],
+    email_subject: &str
+) -> Result<(), Box<dyn Error>> {
+    let (template, html) = match template {
+        Some(path) => (
+            std::fs::read_to_string(path).map_err(|err| format!("Could not read {}: {}", path.display(), err))?,
+            path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm"))
+        ),
+        None => (report::DEFAULT_TEMPLATE.to_string(), false)
+    };
+    let report = report::render(report, &template)?;
+
+    if let Some(path) = output {
+        std::fs::write(path, &report)?;
+    }
+    if let Some(smtp) = smtp {
+        email::send(smtp, email_to, email_subject, &report, html)?;
+        println!("Emailed the report to {}", email_to.join(", "));
+    } else if output.is_none() {
+        print!("{}", report);
+    }
+    Ok(())
+}
+
+// What the daemon does after each run besides saving the snapshot
+#[derive(Debug, Clone)]
+struct Notifiers<'a> {
+    template: Option<&'a PathBuf>,
+    smtp: Option<&'a email::Smtp>,
+    email_to: &'a [String],
+    email_subject: &'a str,
+    metrics: &'a std::sync::Mutex<metrics::State>,
+    // whether the latest run tripped an alert
+    alerted: &'a std::cell::Cell<bool>
+}
+
+fn print_alerts(tripped: &[String]) {
+    for alert in tripped {
+        println!("{}", format!("Alert: {}", alert).red().bold());
+    }
+}
+
+// Posts to --webhook and checks --alert against the newest snapshot in --snapshots after the analysis, from one report
+// built without the notes of the analysis. Returns whether any alert tripped.
+fn notify(args: &Args) -> Result<bool, Box<dyn Error>> {
+    let mut quiet = args.clone();
+    quiet.summary = true;
+    let report = build_report(&quiet)?;
+    if let Some(url) = &args.webhook {
+        post_summary(args, url, Summary::from(&report))?;
+    }
+
+    let previous = args.snapshots.as_deref().and_then(alert::latest_snapshot);
+    let tripped = alert::check(&args.alert, &report, previous.as_ref());
+    print_alerts(&tripped);
+    Ok(!tripped.is_empty())
+}
+
+// One run of the daemon: the report of the matches file in the data directory saved as a timestamped snapshot, then
+// the metrics updated and the webhook and email notified
+fn scheduled_run(args: &Args, data_dir: &std::path::Path, notifiers: &Notifiers) -> Result<String, Box<dyn Error>> {
+    let mut args = args.clone();
+    // an absolute --matches is used as is
+    args.matches = data_dir.join(&args.matches);
+    let report = build_report(&args)?;
+    notifiers.metrics.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).record_report(report.clone());
+
+    let snapshots = data_dir.join("snapshots");
+    // read before this run's snapshot joins it
+    let previous = alert::latest_snapshot(&snapshots);
+    std::fs::create_dir_all(&snapshots).map_err(|err| format!("Could not create {}: {}", snapshots.display(), err))?;
+    let path = snapshots.join(format!("{}.json", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    serde_json::to_writer_pretty(File::create(&path)?, &report)?;
+
+    let tripped = alert::check(&args.alert, &report, previous.as_ref());
+    print_alerts(&tripped);
+    notifiers.alerted.set(!tripped.is_empty());
+
+    let notified = args.webhook.as_ref().map_or(Ok(()), |url| post_summary(&args, url, Summary::from(&report))).and_then(|()| match notifiers.smtp {
+        Some(smtp) => write_report(&report, notifiers.template, None, Some(smtp), notifiers.email_to, notifiers.email_subject),
+        None => Ok(())
+    });
+    if let Err(err) = notified {
+        return Err(format!("Saved {} but could not notify: {}", path.display(), err).into());
+    }
+    let alerts = if tripped.is_empty() { String::new() } else { format!(", {} alerts tripped", tripped.len()) };
+    Ok(format!("{} matches, saved {}{}", report.total_matches, path.display(), alerts))
+}
+
+// The arguments of an API request, whose filter narrows --filter rather than replacing it
+fn narrowed(args: &Args, filter: Option<&filter::Filter>) -> Args {
+    let mut args = args.clone();
+    args.filter = match (args.filter.take(), filter) {
+        (Some(base), Some(filter)) => Some(filter::Filter::And(vec![base, filter.clone()])),
+        (base, filter) => base.or_else(|| filter.cloned())
+    };
+    // the loading notes of the analysis would flood the request log
+    args.summary = true;
+    args
+}
+
+// Answers the JSON API and GraphQL until the process is stopped
+fn serve_api(args: &Args, address: &str) -> Result<(), Box<dyn Error>> {
+    let (profile_args, report_args) = (args.clone(), args.clone());
+    let analysis = std::sync::Arc::new(api::Analysis {
+        profiles: Box::new(move |filter| load_profiles(&narrowed(&profile_args, filter))),
+        report: Box::new(move |filter| build_report(&narrowed(&report_args, filter))),
+        funnel: funnel::Funnel::new(&args.stages)?
+    });
+    let schema = graphql::schema(analysis.clone());
+    let server = server::serve(address, move |request| {
+        if request.path == "/graphql" {
+            graphql::respond(request, &schema)
+        } else if request.path == "/metrics" {
+            metrics::respond_report(request, || (analysis.report)(None))
+        } else {
+            api::respond(request, &analysis)
+        }
+    })?;
+    println!("Serving the API at http://{0}/api, GraphQL at http://{0}/graphql and metrics at http://{0}/metrics", server.address);
+    server.wait()
+}
+
+// Every top level option can also be set with its environment variable
+fn try_parse_from(arguments: impl IntoIterator<Item = impl Into<std::ffi::OsString> + Clone>) -> Result<Args, clap::Error> {
+    let command = Args::command().mut_args(|arg| {
+        let variable = config::environment_variable(arg.get_id().as_str());
+        arg.env(variable)
+    });
+    command.try_get_matches_from(arguments).and_then(|matches| Args::from_arg_matches(&matches))
+}
+
+fn parse_from(arguments: impl IntoIterator<Item = impl Into<std::ffi::OsString> + Clone>) -> Args {
+    try_parse_from(arguments).unwrap_or_else(|err| err.exit())
+}
+
+// The command line with the options of the config file and the selected preset in front, so options given on the
+// command line win
+fn parse_args() -> Result<Args, Box<dyn Error>> {
+    let command_line = std::env::args_os().collect::<Vec<_>>();
+    let args = parse_from(&command_line);
+    let arguments = config::load(args.config.as_deref())?.arguments(args.preset.as_deref())?;
+    if arguments.is_empty() {
+        return Ok(args);
+    }
+
+    Ok(parse_from(command_line[..1].iter().cloned().chain(arguments).chain(command_line[1..].iter().cloned())))
+}
+
+// Points the baseline options at the bundled set written out to the cache directory
+fn use_bundled_baselines(args: &mut Args) -> Result<(), Box<dyn Error>> {
+    let baselines = bundled::extract(args.bundled_baselines, &census_cache(args).directory.join("baselines"))?;
+    args.baseline = baselines.race;
+    args.hispanic_baseline = baselines.hispanic;
+    args.age_baseline = baselines.age.unwrap_or(args.age_baseline.clone());
+    args.religion_baseline = baselines.religion;
+    args.education_baseline = baselines.education;
+    args.geography = args.bundled_baselines.geography().to_string();
+    Ok(())
+}
+
+// Points the baseline options at the bundled baselines with --offline and at the census counts of --counties or --zip
+fn resolve_baselines(mut args: Args) -> Result<Args, Box<dyn Error>> {
+    if args.offline {
+        use_bundled_baselines(&mut args)?;
+    }
+    if let Some(zip) = &args.zip {
+        args.counties = zipcode::counties(zip, args.radius, args.zip_crosswalk.as_deref())?;
+    }
+    // the census command writes the files itself
+    if !args.counties.is_empty() && !matches!(args.command, Some(Command::Census { .. })) {
+        use_census_baselines(&mut args)?;
+    }
+    Ok(args)
+}
+
+// The command line program, main.rs only calls this so the analysis can also be built as a library for the C API
+pub fn run() {
+    run_with(metric::Registry::new())
+}
+
+// The command line program with the metric computers of a program using the crate as a library, printed and reported
+// after the built in ones, e.g. hinge_analyzer::run_with(Registry::new().register(MyMetric)) from its main
+pub fn run_with(extensions: metric::Registry) {
+    let args = metric::extend(extensions)
+        .map_err(Box::from)
+        .and_then(|()| parse_args())
+        .and_then(resolve_baselines)
+        .unwrap_or_else(|err| {
+            println!("error running example: {}", err);
+            process::exit(1);
+        });
+    format::set(format::Format {
+        decimals: args.decimals,
+        thousands_separator: args.thousands_separator,
+        rate_style: args.rate_style,
+        explain: args.explain
+    });
+    if args.summary {
+        progress::disable();
+    }
+    let alerted = std::cell::Cell::new(false);
+    let result = match &args.command {
+        Some(Command::Crosstab { row, column }) => load_profiles(&args).map(|profiles| attribute::print_crosstab(&profiles, row, column)),
+        Some(Command::Correlations) => load_profiles(&args).map(|profiles| association::print_association_matrix(&profiles, args.alpha)),
+        Some(Command::Map { output, neighborhoods }) => load_profiles(&args).and_then(|profiles| {
+            std::fs::write(output, geo::map(&profiles, neighborhoods.as_deref(), args.zip_crosswalk.as_deref())?)?;
+            println!("\nWrote the map to {}", output.display());
+            Ok(())
+        }),
+        Some(Command::Heatmap) => load_export(&args).map(|export| activity::print_activity(&export)),
+        Some(Command::Cohorts) => load_export(&args).map(|export| timeline::print_cohorts(&export)),
+        Some(Command::Weekly) => load_export(&args).map(|export| timeline::print_weekly_rates(&export)),
+        Some(Command::Streaks { pause_days, return_days }) => load_export(&args).map(|export| timeline::print_streaks(&export, *pause_days, *return_days)),
+        Some(Command::Burnout) => load_export(&args).map(|export| burnout::print_burnout(&export, args.alpha)),
+        Some(Command::Timing) => load_export(&args).map(|export| timeline::print_date_timing(&export)),
+        Some(Command::Survival { time, alive_days }) => load_export(&args).map(|export| survival::print_survival(&export, *time, *alive_days)),
+        Some(Command::Transitions { stall_days }) => load_export(&args).map(|export| conversation::print_transitions(&export, *stall_days)),
+        Some(Command::DoubleTexts { gap_hours }) => load_export(&args).map(|export| conversation::print_double_texts(&export, *gap_hours)),
+        Some(Command::Balance) => load_export(&args).map(|export| conversation::print_balance(&export)),
+        Some(Command::Emoji) => load_export(&args).map(|export| chat::print_emoji(&export)),
+        Some(Command::Topics { min_conversations, top }) => load_export(&args).map(|export| chat::print_topics(&export, *min_conversations, *top)),
+        Some(Command::Prompts) => load_export(&args).map(|export| likes::print_prompt_effectiveness(&export)),
+        Some(Command::Media) => load_export(&args).map(|export| likes::print_media(&export)),
+        Some(Command::Voice) => load_export(&args).map(|export| likes::print_voice(&export)),
+        Some(Command::Context) => load_export(&args).and_then(|export| {
+            let user = export.user.ok_or("No user.json in the export")?;
+            load_profiles(&args).map(|profiles| context::print_context(&profiles, &user))
+        }),
+        Some(Command::Conversion { changes }) => load_export(&args).map(|export| timeline::print_like_conversion(&export, changes)),
+        Some(Command::Funnels) => load_export(&args).map(|export| likes::print_direction_funnels(&export)),
+        Some(Command::Subscription { periods }) => load_export(&args).map(|export| timeline::print_subscription(&export, periods)),
+        Some(Command::Resurrect { stall_days, max_days, top }) => load_export(&args).map(|export| conversation::print_resurrectable(&export, *stall_days, *max_days, *top)),
+        Some(Command::Export { export: ExportCommand::Dates { output } }) => write_dates(&args, output.as_ref()),
+        Some(Command::Bundle { output, floor }) => load_profiles(&args).and_then(|profiles| {
+            let bundle = benchmark::bundle(&profiles, *floor);
+            match output {
+                Some(path) => benchmark::write_bundle(&bundle, File::create(path)?),
+                None => benchmark::write_bundle(&bundle, std::io::stdout())
+            }
+        }),
+        Some(Command::Benchmark { benchmarks, floor }) => benchmark::load_benchmarks(benchmarks).and_then(|benchmarks| {
+            load_profiles(&args).map(|profiles| benchmark::print_benchmark(&benchmark::bundle(&profiles, *floor), &benchmarks))
+        }),
+        Some(Command::Convert { output }) => load_export(&args).and_then(|export| match output {
+            Some(path) => convert::write_matches(&export, File::create(path)?),
+            None => convert::write_matches(&export, std::io::stdout())
+        }),
+        Some(Command::Census { output, hispanic_output }) => write_census_baselines(&args, output, hispanic_output).map(|geographies| {
+            println!("Wrote the race counts of {} to {} and {}", geographies.join(", "), output.display(), hispanic_output.display());
+        }),
+        Some(Command::Schema { output }) => report::json_schema().map_err(Box::from).and_then(|schema| match output {
+            Some(path) => std::fs::write(path, schema + "\n").map_err(Box::from),
+            None => {
+                println!("{}", schema);
+                Ok(())
+            }
+        }),
+        Some(Command::Report { template, output, email_to, email_subject }) => {
+            smtp(&args, email_to).and_then(|smtp| write_report(&build_report(&args)?, template.as_ref(), output.as_ref(), smtp.as_ref(), email_to, email_subject))
+        },
+        Some(Command::Daemon { interval, data_dir, runs, template, email_to, email_subject, metrics_address }) => smtp(&args, email_to).and_then(|smtp| {
+            let state = std::sync::Arc::new(std::sync::Mutex::new(metrics::State::default()));
+            let served = metrics_address.as_deref().map(|address| {
+                let state = state.clone();
+                server::serve(address, move |request| metrics::respond(request, &state))
+            });
+            served.transpose().map(|server| {
+                if let Some(server) = server {
+                    println!("Serving metrics at http://{}/metrics", server.address);
+                }
+                let notifiers = Notifiers { template: template.as_ref(), smtp: smtp.as_ref(), email_to, email_subject, metrics: &state, alerted: &alerted };
+                daemon::schedule(*interval, *runs, || {
+                    let outcome = scheduled_run(&args, data_dir, &notifiers);
+                    state.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).record_run(outcome.is_ok());
+                    outcome
+                });
+            })
+        }),
+        Some(Command::Serve { address }) => serve_api(&args, address),
+        Some(Command::Repl) => funnel::Funnel::new(&args.stages)
+            .map_err(Box::from)
+            .and_then(|funnel| {
+                let (race_weights, hispanic_race_weights) = load_race_weights(&args)?;
+                let computers = metric::Registry::built_in(funnel.clone(), preference_index(&args, race_weights, hispanic_race_weights));
+                let computers = computers.enabled(&args.metrics, &args.skip_metrics)?;
+                repl::run(&load_profiles(&args)?, &funnel, &computers).map_err(Box::from)
+            }),
+        Some(Command::WhatIf { scenario }) => funnel::Funnel::new(&args.stages)
+            .map_err(Box::from)
+            .and_then(|funnel| load_profiles(&args).map(|profiles| funnel::print_what_if(profiles.iter(), scenario, &funnel))),
+        Some(Command::Validate { path }) => validate(&args, path.as_ref().unwrap_or(&args.matches)),
+        Some(Command::Generate { profiles, output, chats, convo_rate, date_rate, missing_rate, mean_age, mean_distance }) => {
+            let distributions = generate::Distributions {
+                conversation_rate: *convo_rate,
+                date_rate: *date_rate,
+                missing_rate: *missing_rate,
+                mean_age: *mean_age,
+                mean_distance_miles: *mean_distance,
+                height_baseline: args.height_baseline,
+                race_baseline: args.baseline.clone(),
+                baseline_units: args.baseline_units
+            };
+            write_generated(&args, *profiles, &distributions, output.as_ref(), chats.as_ref())
+        },
+        Some(Command::Compare { users, streaming }) => compare(&args, users, *streaming),
+        None if args.summary => print_summary_line(&args),
+        None => run_analysis(&args)
+    };
+    let result = result.and_then(|()| match args.command.is_none() && (args.webhook.is_some() || !args.alert.is_empty()) {
+        true => notify(&args).map(|tripped| alerted.set(tripped)),
+        false => Ok(())
+    });
+    // the rejection counts would break up the single line of --summary, rejected rows are still quarantined
+    if !args.summary {
+        ingest::print_summary();
+    }
+
+    if let Err(err) = result {
+        println!("error running example: {}", err);
+        process::exit(1);
+    }
+    // a daemon that ran out of --runs reports its last run
+    if alerted.get() {
+        process::exit(alert::EXIT_CODE);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,41 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Habit {
+    Yes,
+    Sometimes,
+    No
+}
+
+// Lifestyle columns shared by drinking, smoking, marijuana and drugs
+pub const COLUMNS: &[&str] = &["drinking", "smoking", "marijuana", "drugs"];
+
+impl Habit {
+    pub fn label(self) -> &'static str {
+        match self {
+            Habit::Yes => "Yes",
+            Habit::Sometimes => "Sometimes",
+            Habit::No => "No"
+        }
+    }
+
+    // Missing and Prefer Not to Say are both treated as no answer
+    pub fn parse(value: Option<&str>) -> Result<Option<Self>, &'static str> {
+        let Some(value) = value.map(|value| value.trim().to_ascii_lowercase()).filter(|value| !value.is_empty()) else {
+            return Ok(None);
+        };
+
+        match value.as_str() {
+            "yes" | "often" | "regularly" | "daily" => Ok(Some(Habit::Yes)),
+            "sometimes" | "socially" | "occasionally" => Ok(Some(Habit::Sometimes)),
+            "no" | "never" => Ok(Some(Habit::No)),
+            "prefer not to say" => Ok(None),
+            _ => Err("Invalid value for lifestyle column, expected Yes, Sometimes or No")
+        }
+    }
+}
+
+impl std::fmt::Display for Habit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use crate::{export::{media::{self, MediaKind}, Export, Interaction, Removal}, format, stats};
+
+// Audio files a voice note shows up as in a chat, and the text Hinge puts in place of one
+const VOICE_NOTE_EXTENSIONS: [&str; 4] = [".m4a", ".aac", ".mp3", ".ogg"];
+const VOICE_NOTE_MARKERS: [&str; 3] = ["[voice", "voice note", "voice message"];
+
+// Likes in one group and how far they got, comments are only known for likes you received
+#[derive(Debug, Default, Clone, Copy)]
+struct LikeOutcomes {
+    likes: u32,
+    comments: Option<u32>,
+    matches: u32,
+    conversations: u32,
+    dates: u32
+}
+
+impl LikeOutcomes {
+    fn add(&mut self, export: &Export, interaction: &Interaction, commented: Option<bool>) {
+        self.likes += 1;
+        if let Some(commented) = commented {
+            self.comments = Some(self.comments.unwrap_or(0) + commented as u32);
+        }
+        self.matches += interaction.matched_at().is_some() as u32;
+        self.conversations += export.conversation(interaction) as u32;
+        self.dates += interaction.met() as u32;
+    }
+}
+
+fn print_like_outcomes(title: &str, label: &str, rows: &[(String, LikeOutcomes)]) {
+    let width = rows.iter().map(|(label, _)| label.chars().count()).max().unwrap_or(0).max(label.len()).min(50);
+    println!("\n\t         {}", title);
+    println!("\t{:<width$}   Likes     Commented   Matches   Convo Rate    Date Rate", label);
+    for (label, outcomes) in rows {
+        let label = if label.chars().count() > width { format!("{}…", label.chars().take(width - 1).collect::<String>()) } else { label.clone() };
+        println!(
+            "\t{:<width$}   {:<7}   {:<9}   {:<7}   {}     {}",
+            label,
+            outcomes.likes,
+            outcomes.comments.map_or(String::from("-"), |comments| format::percent(comments as f64 / outcomes.likes as f64)),
+            outcomes.matches,
+            format::percent(outcomes.conversations as f64 / outcomes.matches.max(1) as f64),
+            format::percent(outcomes.dates as f64 / outcomes.matches.max(1) as f64)
+        );
+        format::explain(&[
+            format::Rate::new("convo rate", outcomes.conversations as usize, "convos", outcomes.matches as usize, "matches"),
+            format::Rate::new("date rate", outcomes.dates as usize, "dates", outcomes.matches as usize, "matches")
+        ]);
+    }
+}
+
+// Which of your prompts and photos received likes were on, with the conversion of the matches each one brought. Likes
+// only carry what they were on in exports merged with your received likes, otherwise matches are compared by who
+// liked first.
+pub fn print_prompt_effectiveness(export: &Export) {
+    let mut by_content = HashMap::<String, LikeOutcomes>::new();
+    for interaction in &export.interactions {
+        for like in &interaction.received_like {
+            let content = like.content.as_deref().map_or("Unknown", str::trim);
+            let commented = like.comment.as_deref().is_some_and(|comment| !comment.trim().is_empty());
+            by_content.entry(content.to_string()).or_default().add(export, interaction, Some(commented));
+        }
+    }
+
+    if by_content.keys().any(|content| content != "Unknown") {
+        let mut rows = by_content.into_iter().collect::<Vec<_>>();
+        rows.sort_by(|a, b| b.1.likes.cmp(&a.1.likes).then_with(|| a.0.cmp(&b.0)));
+        print_like_outcomes("Likes Received by Profile Content", "Content", &rows);
+    } else {
+        println!("\nNo received likes in the export say what they were on, comparing matches by who liked first instead");
+    }
+
+    let mut by_first_like = [(String::from("You Liked First"), LikeOutcomes::default()), (String::from("They Liked First"), LikeOutcomes::default())];
+    for (_, interaction) in export.matches() {
+        let they_liked_first = interaction.they_liked_first();
+        let commented = (!interaction.received_like.is_empty())
+            .then(|| interaction.received_like.iter().any(|like| like.comment.as_deref().is_some_and(|comment| !comment.trim().is_empty())));
+        by_first_like[they_liked_first as usize].1.add(export, interaction, commented);
+    }
+    print_like_outcomes("Matches by Who Liked First", "Like", &by_first_like);
+}
+
+// Your photos and videos from media.json, each with the received likes that were on it, and how received likes split
+// between photos, videos and everything else on the profile
+pub fn print_media(export: &Export) {
+    if export.media.is_empty() {
+        println!("\nNo media.json in the export, skipping media statistics");
+        return;
+    }
+
+    let count = |kind: MediaKind| export.media.iter().filter(|item| item.kind == kind).count();
+    println!("\n\t         Your Media ({} Items)", export.media.len());
+    println!("\tPhotos: {}, Videos: {}, Other: {}", count(MediaKind::Photo), count(MediaKind::Video), count(MediaKind::Other));
+    println!(
+        "\tWith a Prompt: {}, With a Caption: {}, From Social Media: {}",
+        export.media.iter().filter(|item| item.prompt.is_some()).count(),
+        export.media.iter().filter(|item| item.caption.as_deref().is_some_and(|caption| !caption.trim().is_empty())).count(),
+        export.media.iter().filter(|item| item.from_social_media == Some(true)).count()
+    );
+
+    let mut rows = vec![LikeOutcomes::default(); export.media.len()];
+    // received likes on [photos, videos, other media, anything else]
+    let mut referenced = [0; 4];
+    for interaction in &export.interactions {
+        for like in &interaction.received_like {
+            let commented = like.comment.as_deref().is_some_and(|comment| !comment.trim().is_empty());
+            match like.content.as_deref().and_then(|content| media::find(&export.media, content)) {
+                Some(i) => {
+                    rows[i].add(export, interaction, Some(commented));
+                    referenced[export.media[i].kind as usize] += 1;
+                },
+                None => referenced[3] += 1
+            }
+        }
+    }
+
+    let likes = referenced.iter().sum::<u32>();
+    if likes == 0 {
+        println!("\nNo received likes in the export, skipping likes by media");
+        return;
+    }
+
+    println!("\tReceived Likes on Photos: {}, on Videos: {}, on Other Media: {}, on Prompts or Unknown: {}", referenced[0], referenced[1], referenced[2], referenced[3]);
+
+    let mut positions = [0; 2];
+    let rows = export
+        .media
+        .iter()
+        .zip(rows)
+        .map(|(item, outcomes)| {
+            let position = &mut positions[(item.kind == MediaKind::Video) as usize];
+            *position += 1;
+            let label = match item.caption.as_deref().or(item.prompt.as_deref()) {
+                Some(text) if !text.trim().is_empty() => format!("{}: {}", item.label(*position), text.trim()),
+                _ => item.label(*position)
+            };
+            (label, outcomes)
+        })
+        .collect::<Vec<_>>();
+    print_like_outcomes("Received Likes by Media", "Media", &rows);
+}
+
+// A match engaged with voice when their like was on a voice prompt or the chat had a voice note in it
+fn engaged_with_voice(interaction: &Interaction) -> bool {
+    let liked_voice_prompt = interaction
+        .received_like
+        .iter()
+        .any(|like| like.content.as_deref().is_some_and(|content| content.to_lowercase().contains("voice")));
+    let voice_note = interaction.chats.iter().any(|message| {
+        let body = message.body.trim().to_lowercase();
+        VOICE_NOTE_MARKERS.iter().any(|marker| body.starts_with(marker)) || VOICE_NOTE_EXTENSIONS.iter().any(|extension| body.ends_with(extension))
+    });
+    liked_voice_prompt || voice_note
+}
+
+// Conversion of matches that engaged with voice prompts or voice notes against those that did not, with the
+// chi-square test of each outcome being independent of voice engagement
+pub fn print_voice(export: &Export) {
+    let mut groups = [(String::from("No Voice"), LikeOutcomes::default()), (String::from("Engaged with Voice"), LikeOutcomes::default())];
+    for (_, interaction) in export.matches() {
+        groups[engaged_with_voice(interaction) as usize].1.add(export, interaction, None);
+    }
+
+    if groups[1].1.matches == 0 {
+        println!("\nNo voice prompt likes or voice notes in the export, skipping voice prompt usage");
+        return;
+    }
+
+    print_like_outcomes("Matches by Voice Prompt Engagement", "Voice", &groups);
+
+    let outcomes = [("Conversation", groups.each_ref().map(|(_, outcomes)| outcomes.conversations)), ("Date", groups.each_ref().map(|(_, outcomes)| outcomes.dates))];
+    for (outcome, counts) in outcomes {
+        let table = groups.iter().zip(counts).map(|((_, outcomes), count)| vec![count, outcomes.matches - count]).collect::<Vec<_>>();
+        match stats::independence_test(&table) {
+            Some(test) => println!("\t{} Independent of Voice: {}", outcome, test),
+            None => println!("\t{} Independent of Voice: not enough variation to test", outcome)
+        }
+    }
+}
+
+type Reached<'a> = Box<dyn Fn(&Interaction) -> bool + 'a>;
+
+// The conversation and date funnel of matches split by who liked first, each stage as a share of the stage it follows
+// with a test of that step converting the same for both directions. The We Met survey answers follow the dates as
+// post-date stages when the export has them, and matches that were unmatched, blocked or hidden end the funnel as
+// terminal states out of all matches.
+pub fn print_direction_funnels(export: &Export) {
+    // (stage, the stage it follows, whether an interaction reached it)
+    let mut stages: Vec<(&str, usize, Reached)> = vec![
+        ("Matches", 0, Box::new(|_| true)),
+        ("Messaged", 0, Box::new(|interaction| !interaction.chats.is_empty())),
+        ("Conversations", 1, Box::new(|interaction| export.conversation(interaction))),
+        ("Dates", 2, Box::new(|interaction| interaction.met()))
+    ];
+    let meetings = export.interactions.iter().flat_map(|interaction| &interaction.we_met);
+    if meetings.clone().any(|meeting| meeting.was_my_type.is_some()) {
+        stages.push(("My Type", 3, Box::new(|interaction| interaction.we_met_answer(|meeting| meeting.was_my_type) == Some(true))));
+    }
+    if meetings.clone().any(|meeting| meeting.would_meet_again.is_some()) {
+        stages.push(("Would Meet Again", 3, Box::new(|interaction| interaction.we_met_answer(|meeting| meeting.would_meet_again) == Some(true))));
+    }
+    let terminal = stages.len();
+    for (stage, removal) in [("Unmatched", Removal::Unmatched), ("Blocked", Removal::Blocked), ("Hidden", Removal::Hidden)] {
+        if export.interactions.iter().any(|interaction| interaction.removal().is_some_and(|(_, ended)| ended == removal)) {
+            stages.push((stage, 0, Box::new(move |interaction| interaction.removal().is_some_and(|(_, ended)| ended == removal))));
+        }
+    }
+
+    // stage counts for [you liked first, they liked first]
+    let mut funnels = [vec![0_u32; stages.len()], vec![0_u32; stages.len()]];
+    for (_, interaction) in export.matches() {
+        let funnel = &mut funnels[interaction.they_liked_first() as usize];
+        for (count, (_, _, reached)) in funnel.iter_mut().zip(&stages) {
+            *count += reached(interaction) as u32;
+        }
+    }
+
+    if funnels.iter().all(|funnel| funnel[0] == 0) {
+        println!("\nNo matches in the export, skipping direction funnels");
+        return;
+    }
+
+    println!("\n\t         Funnel by Who Liked First (Rate = Share of the Stage It Follows)");
+    println!("\t{:<16}   You Liked First         They Liked First        Step Independent of Direction", "Stage");
+    for (i, (stage, previous, _)) in stages.iter().enumerate() {
+        if i == terminal {
+            println!("\t{:<16}   (Share of Matches)", "Ended");
+        }
+        let cells = funnels.each_ref().map(|funnel| if i == 0 {
+            format!("{:<7}", funnel[i])
+        } else {
+            format!("{:<7} {}", funnel[i], format::percent(funnel[i] as f64 / funnel[*previous].max(1) as f64))
+        });
+
+        let test = (i > 0)
+            .then(|| stats::independence_test(&funnels.each_ref().map(|funnel| vec![funnel[i], funnel[*previous] - funnel[i]])))
+            .flatten()
+            .map_or(String::from("-"), |test| format!("p = {:.4}, Cramér's V = {:.3}", test.p_value, test.cramers_v));
+        println!("\t{:<16}   {:<21}   {:<21}   {}", stage, cells[0], cells[1], test);
+    }
+}
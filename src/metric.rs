@@ -0,0 +1,364 @@
+use std::{collections::BTreeMap, error::Error, sync::OnceLock};
+
+use colored::Colorize;
+
+use crate::{api::ProfileRow, attribute, format, funnel, preference, racial_categories, stats, HingeProfile, Race, WhoLastReplied};
+
+// Numbers of a metric computer by name, e.g. you_ghosting
+pub type Metrics = BTreeMap<String, f64>;
+
+// Computers added by a program using the crate as a library, set once before anything is analyzed
+static EXTENSIONS: OnceLock<Registry> = OnceLock::new();
+
+// The matches metrics are computed from, every match or those with one value of --by
+pub struct Matches<'a> {
+    // the matches as the API returns them
+    pub profiles: Vec<ProfileRow>,
+    matches: Vec<&'a HingeProfile>
+}
+
+impl<'a> Matches<'a> {
+    pub(crate) fn new(matches: impl Iterator<Item = &'a HingeProfile>) -> Self {
+        let matches = matches.collect::<Vec<_>>();
+        Matches { profiles: matches.iter().map(|profile| ProfileRow::from(*profile)).collect(), matches }
+    }
+}
+
+// A block of metrics of the analysis, printed with it and kept in the report. The crate registers the built in ones and
+// a program using it as a library adds its own through run_with.
+pub trait MetricComputer: Send + Sync {
+    // What --metrics and --skip-metrics call it, e.g. ghosting
+    fn name(&self) -> &str;
+
+    fn compute(&self, matches: &Matches) -> Result<Metrics, Box<dyn Error>>;
+
+    // How it shows in the analysis, a list of its numbers unless it has something better
+    fn print(&self, matches: &Matches) -> Result<(), Box<dyn Error>> {
+        println!("\n{}", attribute::title(self.name()));
+        for (name, value) in self.compute(matches)? {
+            println!("{}: {}", attribute::title(&name), format::decimal(value, 4));
+        }
+        Ok(())
+    }
+}
+
+// Metric computers in the order they are printed
+#[derive(Default)]
+pub struct Registry {
+    computers: Vec<Box<dyn MetricComputer>>
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    pub fn register(mut self, computer: impl MetricComputer + 'static) -> Self {
+        self.computers.push(Box::new(computer));
+        self
+    }
+
+    // The outcome metrics the analysis always had, then the funnel and the race preference index
+    pub(crate) fn built_in(funnel: funnel::Funnel, preference_index: PreferenceIndex) -> Self {
+        Registry::new().register(Ghosting).register(Conversations).register(Dates).register(Funnel(funnel)).register(preference_index)
+    }
+
+    // These computers followed by the extensions, only those in `only` when it isn't empty and none of those in `skip`
+    pub(crate) fn enabled(&self, only: &[String], skip: &[String]) -> Result<Vec<&dyn MetricComputer>, String> {
+        let computers = self.computers.iter().chain(EXTENSIONS.get().into_iter().flat_map(|extensions| &extensions.computers)).map(Box::as_ref);
+        let computers = computers.collect::<Vec<_>>();
+        let names = computers.iter().map(|computer| computer.name()).collect::<Vec<_>>();
+        if let Some(name) = names.iter().enumerate().find_map(|(i, name)| names[..i].contains(name).then_some(name)) {
+            return Err(format!("Two metric computers are named {}", name));
+        }
+        if let Some(name) = only.iter().chain(skip).find(|name| !names.contains(&name.as_str())) {
+            return Err(format!("Unknown metric computer {}, expected one of {}", name, names.join(", ")));
+        }
+
+        Ok(computers
+            .into_iter()
+            .filter(|computer| only.is_empty() || only.iter().any(|name| name == computer.name()))
+            .filter(|computer| !skip.iter().any(|name| name == computer.name()))
+            .collect())
+    }
+}
+
+// Adds the computers of a program using the crate as a library after the built in ones. They can be added once per
+// process, adding none, as run does, is always fine.
+pub(crate) fn extend(extensions: Registry) -> Result<(), String> {
+    if extensions.computers.is_empty() {
+        return Ok(());
+    }
+    EXTENSIONS.set(extensions).map_err(|_| "Metric computers can only be added once per process, register them all in the first run_with".to_string())
+}
+
+pub(crate) fn print(computers: &[&dyn MetricComputer], matches: &Matches) -> Result<(), Box<dyn Error>> {
+    computers.iter().try_for_each(|computer| computer.print(matches))
+}
+
+// Numbers of every computer by its name
+pub(crate) fn compute(computers: &[&dyn MetricComputer], matches: &Matches) -> Result<BTreeMap<String, Metrics>, Box<dyn Error>> {
+    computers.iter().map(|computer| Ok((computer.name().to_string(), computer.compute(matches)?))).collect()
+}
+
+// A metric name from a rate, stage or category, e.g. date_rate_of_matches_you_dont_ghost or asian_non_hispanic
+fn key(name: &str) -> String {
+    let key = name
+        .chars()
+        .filter(|c| *c != '\'')
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect::<String>();
+    key.split('_').filter(|word| !word.is_empty()).collect::<Vec<_>>().join("_")
+}
+
+fn metrics(rates: &[format::Rate]) -> Metrics {
+    rates.iter().map(|rate| (key(rate.name), rate.value())).collect()
+}
+
+// Matches by how far they got and who replied last
+#[derive(Debug, Default)]
+struct Outcomes {
+    no_convo_attempted_count: usize,
+    no_convo_you_failed_count: usize,
+    no_convo_they_failed_count: usize,
+    convo_started_count: usize,
+    convo_started_you_failed_count: usize,
+    convo_started_they_failed_count: usize,
+    you_met_count: usize
+}
+
+impl Outcomes {
+    fn new(matches: &Matches) -> Self {
+        let mut outcomes = Outcomes::default();
+        for profile in &matches.matches {
+            if profile.convo {
+                outcomes.convo_started_count += 1;
+                match profile.who_last_replied {
+                    WhoLastReplied::You => outcomes.convo_started_you_failed_count += 1,
+                    WhoLastReplied::Them => outcomes.convo_started_they_failed_count += 1,
+                    WhoLastReplied::Met => outcomes.you_met_count += 1,
+                    WhoLastReplied::None => unreachable!("None should not be in convo")
+                }
+            } else {
+                match profile.who_last_replied {
+                    WhoLastReplied::You => outcomes.no_convo_you_failed_count += 1,
+                    WhoLastReplied::Them => outcomes.no_convo_they_failed_count += 1,
+                    WhoLastReplied::None => outcomes.no_convo_attempted_count += 1,
+                    WhoLastReplied::Met => unreachable!("Met should not be in no convo")
+                }
+            }
+        }
+        outcomes
+    }
+
+    fn total_profiles(&self) -> usize {
+        self.no_convo_attempted_count + self.no_convo_you_failed_count + self.no_convo_they_failed_count + self.convo_started_count
+    }
+
+    fn convo_you_attempted_count(&self) -> usize {
+        self.total_profiles() - self.no_convo_attempted_count - self.no_convo_they_failed_count
+    }
+
+    fn no_activity(&self) -> format::Rate {
+        format::Rate::new("no activity", self.no_convo_attempted_count, "matches without messages", self.total_profiles(), "matches")
+    }
+
+    // you ghosting, them ghosting, no activity and date rate
+    fn ghosting(&self) -> [format::Rate; 4] {
+        let total_profiles = self.total_profiles();
+        [
+            format::Rate::new("you ghosting", self.no_convo_they_failed_count + self.convo_started_they_failed_count, "matches they last replied to", total_profiles, "matches"),
+            format::Rate::new("them ghosting", self.no_convo_you_failed_count + self.convo_started_you_failed_count, "matches you last replied to", total_profiles, "matches"),
+            self.no_activity(),
+            format::Rate::new("date rate", self.you_met_count, "dates", total_profiles, "matches")
+        ]
+    }
+
+    // interest, not interested, start, start failure, then ghosting and dates within conversations
+    fn conversations(&self) -> [format::Rate; 7] {
+        let (total_profiles, convo_you_attempted_count) = (self.total_profiles(), self.convo_you_attempted_count());
+        [
+            format::Rate::new("conversation interest", convo_you_attempted_count, "matches you messaged", total_profiles, "matches"),
+            format::Rate::new("not interested", self.no_convo_they_failed_count, "unanswered messages from them", total_profiles, "matches"),
+            format::Rate::new("conversation start", self.convo_started_count, "convos", convo_you_attempted_count, "matches you messaged"),
+            format::Rate::new("conversation start failure", self.no_convo_you_failed_count, "unanswered openers", convo_you_attempted_count, "matches you messaged"),
+            format::Rate::new("you ghosting in convos", self.convo_started_they_failed_count, "convos they last replied to", self.convo_started_count, "convos"),
+            format::Rate::new("them ghosting in convos", self.convo_started_you_failed_count, "convos you last replied to", self.convo_started_count, "convos"),
+            format::Rate::new("date conversion", self.you_met_count, "dates", self.convo_started_count, "convos")
+        ]
+    }
+
+    // starting a conversation then going on a date reduces to dates over matches you messaged, and dating the
+    // conversations you don't ghost to dates over those conversations
+    fn dates(&self) -> [format::Rate; 3] {
+        [
+            format::Rate::new("date rate when interested", self.you_met_count, "dates", self.convo_you_attempted_count(), "matches you messaged"),
+            format::Rate::new(
+                "date rate when not ghosting",
+                self.you_met_count,
+                "dates",
+                self.convo_started_count - self.convo_started_they_failed_count,
+                "convos you didn't ghost"
+            ),
+            format::Rate::new(
+                "date rate of matches you don't ghost",
+                self.you_met_count,
+                "dates",
+                self.no_convo_you_failed_count + self.convo_started_you_failed_count + self.you_met_count,
+                "matches you didn't ghost"
+            )
+        ]
+    }
+}
+
+struct Ghosting;
+
+impl MetricComputer for Ghosting {
+    fn name(&self) -> &str {
+        "ghosting"
+    }
+
+    fn compute(&self, matches: &Matches) -> Result<Metrics, Box<dyn Error>> {
+        Ok(metrics(&Outcomes::new(matches).ghosting()))
+    }
+
+    fn print(&self, matches: &Matches) -> Result<(), Box<dyn Error>> {
+        let [you_ghosting, them_ghosting, no_activity, date] = Outcomes::new(matches).ghosting();
+        println!("\nMatch Outcome Metrics");
+        println!("You end up ghosting {} of your matches, {} of your matches end up ghosting you, {} of your matches have no activity, and {} of your matches result in a date.",
+            format::inline_percent(you_ghosting.value(), 2),
+            format::inline_percent(them_ghosting.value(), 2),
+            format::inline_percent(no_activity.value(), 2),
+            format::inline_percent(date.value(), 2));
+        format::explain(&[you_ghosting, them_ghosting, no_activity, date]);
+        Ok(())
+    }
+}
+
+struct Conversations;
+
+impl MetricComputer for Conversations {
+    fn name(&self) -> &str {
+        "conversations"
+    }
+
+    fn compute(&self, matches: &Matches) -> Result<Metrics, Box<dyn Error>> {
+        Ok(metrics(&Outcomes::new(matches).conversations()))
+    }
+
+    fn print(&self, matches: &Matches) -> Result<(), Box<dyn Error>> {
+        let outcomes = Outcomes::new(matches);
+        let no_activity = outcomes.no_activity();
+        let [
+            conversation_interested,
+            conversation_they_failed,
+            conversation_starter,
+            conversation_starter_failed,
+            conversation_to_you_ghosting,
+            conversation_to_them_ghosting,
+            conversation_to_date
+        ] = outcomes.conversations();
+
+        println!("\nConversation Success Metrics");
+        println!("You are interested in having a conversation with {} of your matches, {} of the time you are not interested despite receiving a message, {} of the time no one is interested.",
+            format::inline_percent(conversation_interested.value(), 2),
+            format::inline_percent(conversation_they_failed.value(), 2),
+            format::inline_percent(no_activity.value(), 2)
+        );
+        format::explain(&[conversation_interested, conversation_they_failed, no_activity]);
+        println!("Of the matches you are interested in, you succeed in starting a conversation {} of the time and fail {} of the time.",
+            format::inline_percent(conversation_starter.value(), 2),
+            format::inline_percent(conversation_starter_failed.value(), 2));
+        format::explain(&[conversation_starter, conversation_starter_failed]);
+        println!("Of the matches you have a conversation with, you eventually ghost them {} of the time, they eventually ghost you {} of the time, and you go on a date {} of the time.",
+            format::inline_percent(conversation_to_you_ghosting.value(), 2),
+            format::inline_percent(conversation_to_them_ghosting.value(), 2),
+            format::inline_percent(conversation_to_date.value(), 2));
+        format::explain(&[conversation_to_you_ghosting, conversation_to_them_ghosting, conversation_to_date]);
+        Ok(())
+    }
+}
+
+struct Dates;
+
+impl MetricComputer for Dates {
+    fn name(&self) -> &str {
+        "dates"
+    }
+
+    fn compute(&self, matches: &Matches) -> Result<Metrics, Box<dyn Error>> {
+        Ok(metrics(&Outcomes::new(matches).dates()))
+    }
+
+    fn print(&self, matches: &Matches) -> Result<(), Box<dyn Error>> {
+        let [interested_to_date, not_ghosting_to_date, not_ghosting_matches_to_date] = Outcomes::new(matches).dates();
+        println!("\nDate Conversion Rate");
+        println!("Given that you're interested in having a conversation with your match, there's a {} chance that you go on a date.",
+            format::inline_percent(interested_to_date.value(), 2));
+        format::explain(&[interested_to_date]);
+        println!("Given that you're interested in going on a date with the match you're having a conversation with, there's a {} chance that you do.",
+            format::inline_percent(not_ghosting_to_date.value(), 2));
+        format::explain(&[not_ghosting_to_date]);
+        println!("Given that you don't ghost your match, {} of your matches result in a date.",
+            format::inline_percent(not_ghosting_matches_to_date.value(), 2).green().bold());
+        format::explain(&[not_ghosting_matches_to_date]);
+        Ok(())
+    }
+}
+
+// Share of the matches reaching every stage of --stages, only printed when the stages aren't the default since the
+// outcome metrics already cover conversations and dates
+struct Funnel(funnel::Funnel);
+
+impl MetricComputer for Funnel {
+    fn name(&self) -> &str {
+        "funnel"
+    }
+
+    fn compute(&self, matches: &Matches) -> Result<Metrics, Box<dyn Error>> {
+        let stages = funnel::stage_counts(matches.matches.iter().copied(), &self.0);
+        Ok(stages.into_iter().map(|(stage, profiles)| (key(&stage), profiles as f64 / matches.matches.len().max(1) as f64)).collect())
+    }
+
+    fn print(&self, matches: &Matches) -> Result<(), Box<dyn Error>> {
+        if self.0.is_custom() {
+            funnel::print_funnel(matches.matches.iter().copied(), &self.0);
+        }
+        Ok(())
+    }
+}
+
+// Race preference index of the matches, the scores of --index-formula or the posterior means with --bayesian, by
+// category. Races under the sample cutoff have no score and are left out. The table is printed with the rest of the
+// race analysis it's the start of, so this only adds the scores to the report and /metrics.
+pub(crate) struct PreferenceIndex {
+    pub(crate) race_weights: BTreeMap<Race, f64>,
+    pub(crate) hispanic_race_weights: BTreeMap<Race, f64>,
+    pub(crate) index_formula: stats::IndexFormula,
+    pub(crate) sample_cutoff: u32,
+    // the prior strength with --bayesian
+    pub(crate) prior_strength: Option<f64>
+}
+
+impl MetricComputer for PreferenceIndex {
+    fn name(&self) -> &str {
+        "preference_index"
+    }
+
+    fn compute(&self, matches: &Matches) -> Result<Metrics, Box<dyn Error>> {
+        let categories = racial_categories(matches.matches.iter().copied(), &self.race_weights, &self.hispanic_race_weights);
+        Ok(match self.prior_strength {
+            Some(prior_strength) => {
+                preference::bayesian_preferences(&categories, prior_strength).into_iter().map(|preference| (key(&preference.label), preference.index)).collect()
+            },
+            None => preference::preferences(&categories, self.index_formula, self.sample_cutoff)
+                .into_iter()
+                .filter_map(|preference| Some((key(&preference.label), preference.weight?)))
+                .collect()
+        })
+    }
+
+    fn print(&self, _: &Matches) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use std::{error::Error, fmt::Write, sync::Mutex};
+
+use crate::{report, server};
+
+// What the daemon has done so far, the report is the latest one to finish
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct State {
+    pub report: Option<report::Report>,
+    pub runs: u64,
+    pub failures: u64,
+    pub last_success: Option<i64>
+}
+
+impl State {
+    pub fn record_report(&mut self, report: report::Report) {
+        self.report = Some(report);
+        self.last_success = Some(chrono::Utc::now().timestamp());
+    }
+
+    pub fn record_run(&mut self, succeeded: bool) {
+        self.runs += 1;
+        self.failures += (!succeeded) as u64;
+    }
+}
+
+// Label values escaped per the Prometheus text format
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn metric(text: &mut String, name: &str, kind: &str, help: &str, samples: &[(String, f64)]) -> std::fmt::Result {
+    writeln!(text, "# HELP hinge_analyzer_{} {}", name, help)?;
+    writeln!(text, "# TYPE hinge_analyzer_{} {}", name, kind)?;
+    for (labels, value) in samples {
+        writeln!(text, "hinge_analyzer_{}{} {}", name, labels, value)?;
+    }
+    Ok(())
+}
+
+// The one unlabeled sample of a metric
+fn single(value: f64) -> Vec<(String, f64)> {
+    vec![(String::new(), value)]
+}
+
+// The latest report and the daemon's runs in the Prometheus text exposition format
+pub fn render(state: &State) -> Result<String, std::fmt::Error> {
+    let mut text = String::new();
+    metric(&mut text, "runs_total", "counter", "Scheduled runs since the daemon started", &single(state.runs as f64))?;
+    metric(&mut text, "run_failures_total", "counter", "Scheduled runs that failed", &single(state.failures as f64))?;
+    if let Some(last_success) = state.last_success {
+        metric(&mut text, "last_success_timestamp_seconds", "gauge", "When the latest report finished", &single(last_success as f64))?;
+    }
+
+    if let Some(report) = &state.report {
+        render_report(&mut text, report)?;
+    }
+    Ok(text)
+}
+
+// The numbers of a report, which serve scrapes without the daemon's runs
+fn render_report(text: &mut String, report: &report::Report) -> std::fmt::Result {
+    metric(text, "matches", "gauge", "Matches in the matches file", &single(report.total_matches as f64))?;
+    metric(text, "conversations", "gauge", "Matches with a conversation", &single(report.conversations as f64))?;
+    metric(text, "dates", "gauge", "Matches you met", &single(report.dates as f64))?;
+    metric(text, "conversation_rate", "gauge", "Share of matches with a conversation", &single(report.conversation_rate))?;
+    metric(text, "date_rate", "gauge", "Share of matches you met", &single(report.date_rate))?;
+
+    let stages = report
+        .funnel
+        .iter()
+        .map(|stage| (format!("{{stage=\"{}\"}}", label(&stage.name)), stage.profiles as f64))
+        .collect::<Vec<_>>();
+    metric(text, "funnel_profiles", "gauge", "Profiles that reached each stage of the conversation funnel", &stages)?;
+
+    let mut counts = Vec::new();
+    let mut indexes = Vec::new();
+    for (attribute, section) in &report.sections {
+        for preference in &section.preferences {
+            let labels = format!("{{attribute=\"{}\",category=\"{}\"}}", label(attribute), label(&preference.label));
+            counts.push((labels.clone(), preference.count as f64));
+            if let Some(index) = preference.index {
+                indexes.push((labels, index));
+            }
+        }
+    }
+    metric(text, "category_matches", "gauge", "Matches in each category of every attribute", &counts)?;
+    metric(text, "preference_index", "gauge", "Preference index of each category above the sample cutoff", &indexes)?;
+
+    let computed = report
+        .metrics
+        .iter()
+        .flat_map(|(computer, metrics)| metrics.iter().map(move |(name, value)| (format!("{{computer=\"{}\",metric=\"{}\"}}", label(computer), label(name)), *value)))
+        .collect::<Vec<_>>();
+    metric(text, "computed", "gauge", "Numbers of every metric computer", &computed)
+}
+
+// GET /metrics for Prometheus to scrape
+pub fn respond(request: &server::Request, state: &Mutex<State>) -> server::Response {
+    scrape(request, || {
+        let state = state.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+        render(&state).map_err(Box::from)
+    })
+}
+
+// GET /metrics of serve, the report built when Prometheus scrapes
+pub fn respond_report(request: &server::Request, report: impl FnOnce() -> Result<report::Report, Box<dyn Error>>) -> server::Response {
+    scrape(request, || {
+        let mut text = String::new();
+        render_report(&mut text, &report()?)?;
+        Ok(text)
+    })
+}
+
+fn scrape(request: &server::Request, text: impl FnOnce() -> Result<String, Box<dyn Error>>) -> server::Response {
+    if request.path != "/metrics" {
+        return server::Response::not_found(&request.path);
+    }
+    if request.method != "GET" {
+        return server::Response::text(405, "Only GET is supported\n");
+    }
+
+    match text() {
+        Ok(text) => server::Response { status: 200, content_type: "text/plain; version=0.0.4; charset=utf-8", body: text },
+        Err(err) => server::Response::text(500, format!("{}\n", err))
+    }
+}
@@ -0,0 +1,172 @@
+use statrs::distribution::{ContinuousCDF, Normal};
+
+use crate::stats;
+
+const MAX_ITERATIONS: usize = 100;
+const CONVERGENCE_TOLERANCE: f64 = 1e-8;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LogisticTerm {
+    pub name: String,
+    pub coefficient: f64,
+    pub standard_error: f64
+}
+
+impl LogisticTerm {
+    pub fn z(&self) -> f64 {
+        self.coefficient / self.standard_error
+    }
+
+    // Two-sided Wald test of the coefficient being zero
+    pub fn p_value(&self) -> f64 {
+        let normal = Normal::standard();
+        2.0 * (1.0 - normal.cdf(self.z().abs()))
+    }
+}
+
+impl std::fmt::Display for LogisticTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:<45}   {:>+11.4}   {:>10.4}   {:>+7.2}   {:.4}    {:>10.4}", self.name, self.coefficient, self.standard_error, self.z(), self.p_value(), self.coefficient.exp())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogisticModel {
+    // the first term is always the intercept, the rest line up with the feature columns
+    pub terms: Vec<LogisticTerm>,
+    pub observations: usize,
+    pub events: usize,
+    pub converged: bool
+}
+
+impl LogisticModel {
+    pub fn predict(&self, features: &[f64]) -> f64 {
+        let linear = self.terms[0].coefficient + self.terms[1..]
+            .iter()
+            .zip(features)
+            .map(|(term, value)| term.coefficient * value)
+            .sum::<f64>();
+
+        1.0 / (1.0 + (-linear).exp())
+    }
+}
+
+// Fits the model by Newton-Raphson on the ridge penalized log-likelihood. The penalty leaves the intercept alone and
+// keeps coefficients finite when a feature perfectly separates the outcome, which is common with a few hundred matches.
+pub fn fit(rows: &[Vec<f64>], outcomes: &[bool], feature_names: &[String], ridge: f64) -> LogisticModel {
+    let size = feature_names.len() + 1;
+    let design = rows
+        .iter()
+        .map(|row| std::iter::once(1.0).chain(row.iter().copied()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let mut coefficients = vec![0.0; size];
+    let mut information = vec![vec![0.0; size]; size];
+    let mut converged = false;
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut gradient = (0..size)
+            .map(|j| if j == 0 { 0.0 } else { -ridge * coefficients[j] })
+            .collect::<Vec<_>>();
+        information = (0..size)
+            .map(|j| (0..size).map(|k| if j == k && j != 0 { ridge } else { 0.0 }).collect())
+            .collect();
+
+        for (x, &outcome) in design.iter().zip(outcomes) {
+            let linear = x.iter().zip(&coefficients).map(|(value, coefficient)| value * coefficient).sum::<f64>();
+            let probability = 1.0 / (1.0 + (-linear).exp());
+            let residual = outcome as u8 as f64 - probability;
+            let weight = probability * (1.0 - probability);
+
+            for j in 0..size {
+                gradient[j] += x[j] * residual;
+                for k in 0..size {
+                    information[j][k] += x[j] * x[k] * weight;
+                }
+            }
+        }
+
+        let Some(covariance) = stats::invert_matrix(&information) else {
+            break;
+        };
+
+        let mut largest_step: f64 = 0.0;
+        for j in 0..size {
+            let step = (0..size).map(|k| covariance[j][k] * gradient[k]).sum::<f64>();
+            coefficients[j] += step;
+            largest_step = largest_step.max(step.abs());
+        }
+
+        if largest_step < CONVERGENCE_TOLERANCE {
+            converged = true;
+            break;
+        }
+    }
+
+    let covariance = stats::invert_matrix(&information).unwrap_or_else(|| vec![vec![f64::NAN; size]; size]);
+    let terms = std::iter::once(String::from("Intercept"))
+        .chain(feature_names.iter().cloned())
+        .enumerate()
+        .map(|(j, name)| LogisticTerm {
+            name,
+            coefficient: coefficients[j],
+            standard_error: covariance[j][j].sqrt()
+        })
+        .collect();
+
+    LogisticModel {
+        terms,
+        observations: outcomes.len(),
+        events: outcomes.iter().filter(|&&outcome| outcome).count(),
+        converged
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f64 = 1e-6;
+
+    // One binary feature, 1 event in 4 without it and 3 in 4 with it
+    fn two_by_two() -> (Vec<Vec<f64>>, Vec<bool>) {
+        let rows = [0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0].iter().map(|&value| vec![value]).collect();
+        let outcomes = vec![true, false, false, false, true, true, true, false];
+        (rows, outcomes)
+    }
+
+    #[test]
+    fn unpenalized_fit_matches_the_log_odds() {
+        let (rows, outcomes) = two_by_two();
+        let model = fit(&rows, &outcomes, &[String::from("Feature")], 0.0);
+        assert!(model.converged);
+        assert_eq!((model.observations, model.events), (8, 4));
+
+        // the intercept is the log odds without the feature and the coefficient the log odds ratio, whose variance is
+        // the sum of the reciprocal cell counts
+        let (intercept, feature) = (&model.terms[0], &model.terms[1]);
+        assert!((intercept.coefficient + 3.0_f64.ln()).abs() < TOLERANCE);
+        assert!((intercept.standard_error - (4.0_f64 / 3.0).sqrt()).abs() < TOLERANCE);
+        assert!((feature.coefficient - 2.0 * 3.0_f64.ln()).abs() < TOLERANCE);
+        assert!((feature.standard_error - (8.0_f64 / 3.0).sqrt()).abs() < TOLERANCE);
+        assert!((model.predict(&[0.0]) - 0.25).abs() < TOLERANCE);
+        assert!((model.predict(&[1.0]) - 0.75).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn ridge_keeps_separated_outcomes_finite() {
+        let rows = [0.0, 0.0, 0.0, 1.0, 1.0, 1.0].iter().map(|&value| vec![value]).collect::<Vec<_>>();
+        let outcomes = [false, false, false, true, true, true];
+        let model = fit(&rows, &outcomes, &[String::from("Feature")], 1.0);
+        assert!(model.converged);
+        assert!(model.terms.iter().all(|term| term.coefficient.is_finite() && term.standard_error.is_finite()));
+        assert!(model.terms[1].coefficient > 0.0);
+    }
+
+    #[test]
+    fn zero_coefficient_has_a_p_value_of_one() {
+        let term = LogisticTerm { name: String::from("Feature"), coefficient: 0.0, standard_error: 1.0 };
+        assert_eq!(term.p_value(), 1.0);
+    }
+}
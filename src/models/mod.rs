@@ -0,0 +1,150 @@
+pub mod logistic;
+pub mod tree;
+pub mod validation;
+
+use crate::{HingeProfile, Race, WhoLastReplied};
+
+// A numeric column of the design matrix derived from a profile, new profile attributes become new features here
+pub struct Feature {
+    pub name: String,
+    value: Box<dyn Fn(&HingeProfile) -> f64>
+}
+
+impl Feature {
+    pub fn new(name: impl Into<String>, value: impl Fn(&HingeProfile) -> f64 + 'static) -> Self {
+        Self { name: name.into(), value: Box::new(value) }
+    }
+
+    pub fn value(&self, profile: &HingeProfile) -> f64 {
+        (self.value)(profile)
+    }
+}
+
+// Indicator features for every profile attribute. Races are encoded against the most common race as the reference
+// level and races with fewer than `min_count` profiles are left out so they cannot perfectly separate an outcome.
+pub fn profile_features<'a>(profiles: impl Iterator<Item = &'a HingeProfile> + Clone, min_count: u32) -> (Vec<Feature>, Option<Race>) {
+    let mut features = vec![Feature::new("Ethnicity Specified", |profile| profile.ethnicity_specified as u8 as f64)];
+
+    let race_counts = Race::aggregate(profiles.clone().filter_map(|profile| profile.race));
+    let reference_race = Race::entries()
+        .filter(|race| race_counts[race] > 0)
+        .max_by_key(|race| race_counts[race]);
+
+    for race in Race::entries() {
+        if Some(race) != reference_race && race_counts[&race] >= min_count.max(1) {
+            features.push(Feature::new(format!("Race: {}", race), move |profile| (profile.race == Some(race)) as u8 as f64));
+        }
+    }
+
+    let unknown_race_count = profiles.filter(|profile| profile.race.is_none()).count() as u32;
+    if unknown_race_count >= min_count.max(1) {
+        features.push(Feature::new("Race: Unknown", |profile| profile.race.is_none() as u8 as f64));
+    }
+
+    (features, reference_race)
+}
+
+pub fn design_matrix<'a>(profiles: impl Iterator<Item = &'a HingeProfile>, features: &[Feature]) -> Vec<Vec<f64>> {
+    profiles
+        .map(|profile| features.iter().map(|feature| feature.value(profile)).collect())
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// A conversation was started
+    Conversation,
+    /// You sent the last message and they never replied
+    GhostedByThem,
+    /// You went on a date
+    Date
+}
+
+impl Outcome {
+    pub fn entries() -> impl Iterator<Item = Self> {
+        [Outcome::Conversation, Outcome::GhostedByThem, Outcome::Date].iter().copied()
+    }
+
+    pub fn key(&self) -> &'static str {
+        match *self {
+            Outcome::Conversation => "conversation",
+            Outcome::GhostedByThem => "ghosted_by_them",
+            Outcome::Date => "date"
+        }
+    }
+
+    pub fn observed(&self, profile: &HingeProfile) -> bool {
+        match *self {
+            Outcome::Conversation => profile.convo,
+            Outcome::GhostedByThem => profile.who_last_replied == WhoLastReplied::You,
+            Outcome::Date => profile.who_last_replied == WhoLastReplied::Met
+        }
+    }
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match *self {
+            Outcome::Conversation => "Conversation",
+            Outcome::GhostedByThem => "Ghosted by Them",
+            Outcome::Date => "Date"
+        })?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FittedModel {
+    Logistic {
+        ridge: f64,
+        converged: bool,
+        terms: Vec<logistic::LogisticTerm>
+    },
+    DecisionTree {
+        parameters: tree::TreeParameters,
+        root: tree::Node
+    },
+    RandomForest {
+        parameters: tree::TreeParameters,
+        importances: Vec<f64>,
+        trees: Vec<tree::Node>
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExportedModel {
+    pub name: String,
+    pub outcome: Outcome,
+    // population the model was fit on, e.g. all matches or only conversations
+    pub population: String,
+    pub observations: usize,
+    pub features: Vec<String>,
+    #[serde(flatten)]
+    pub model: FittedModel
+}
+
+// Everything fit during a run, written out by --export-model so models can be inspected or reused elsewhere
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ModelExport {
+    pub generator: String,
+    pub sample_cutoff: u32,
+    pub seed: Option<u64>,
+    // counties of the race baseline the models were fit against
+    pub geographies: Vec<String>,
+    pub models: Vec<ExportedModel>
+}
+
+impl ModelExport {
+    pub fn new(sample_cutoff: u32, seed: Option<u64>) -> Self {
+        Self {
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            sample_cutoff,
+            seed,
+            geographies: Vec::new(),
+            models: Vec::new()
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use rand::{rngs::StdRng, seq::index, Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+
+use crate::stats;
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Node {
+    Leaf {
+        probability: f64,
+        samples: usize
+    },
+    Split {
+        feature: usize,
+        threshold: f64,
+        left: Box<Node>,
+        right: Box<Node>
+    }
+}
+
+impl Node {
+    pub fn predict(&self, features: &[f64]) -> f64 {
+        match self {
+            Node::Leaf { probability, .. } => *probability,
+            Node::Split { feature, threshold, left, right } => if features[*feature] <= *threshold {
+                left.predict(features)
+            } else {
+                right.predict(features)
+            }
+        }
+    }
+
+    // Indented outline of the tree, one line per split branch or leaf
+    pub fn describe(&self, feature_names: &[String], depth: usize, lines: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+        match self {
+            Node::Leaf { probability, samples } => lines.push(format!("{}=> {:.3} (n={})", indent, probability, samples)),
+            Node::Split { feature, threshold, left, right } => {
+                lines.push(format!("{}{} <= {}", indent, feature_names[*feature], threshold));
+                left.describe(feature_names, depth + 1, lines);
+                lines.push(format!("{}{} > {}", indent, feature_names[*feature], threshold));
+                right.describe(feature_names, depth + 1, lines);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct TreeParameters {
+    pub max_depth: usize,
+    pub min_samples_leaf: usize,
+    // number of randomly chosen features considered at each split, all of them when None
+    pub max_features: Option<usize>
+}
+
+#[derive(Debug, Clone)]
+pub struct RandomForest {
+    pub trees: Vec<Node>,
+    // mean decrease in Gini impurity per feature, normalized to sum to 1
+    pub importances: Vec<f64>
+}
+
+impl RandomForest {
+    pub fn predict(&self, features: &[f64]) -> f64 {
+        self.trees.iter().map(|tree| tree.predict(features)).sum::<f64>() / self.trees.len() as f64
+    }
+}
+
+fn gini(positives: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    let p = positives as f64 / total as f64;
+    2.0 * p * (1.0 - p)
+}
+
+// Grows a CART classification tree on the rows in `samples`, adding the weighted impurity decrease of every split to
+// `importances` so forests can report which features the trees lean on.
+pub fn fit_tree(
+    rows: &[Vec<f64>],
+    outcomes: &[bool],
+    samples: &[usize],
+    parameters: TreeParameters,
+    depth: usize,
+    importances: &mut [f64],
+    rng: &mut impl Rng
+) -> Node {
+    let mut grower = Grower { rows, outcomes, parameters, values: Vec::with_capacity(samples.len()) };
+    grower.grow(samples, depth, importances, rng)
+}
+
+// What stays the same while a tree grows, and the (value, outcome) pairs of a candidate feature sorted in one buffer
+// reused by every split instead of allocated for each
+struct Grower<'a> {
+    rows: &'a [Vec<f64>],
+    outcomes: &'a [bool],
+    parameters: TreeParameters,
+    values: Vec<(f64, bool)>
+}
+
+impl Grower<'_> {
+    fn grow(&mut self, samples: &[usize], depth: usize, importances: &mut [f64], rng: &mut impl Rng) -> Node {
+        let Grower { rows, outcomes, parameters, .. } = *self;
+
+        let positives = samples.iter().filter(|&&i| outcomes[i]).count();
+        let leaf = Node::Leaf { probability: positives as f64 / samples.len().max(1) as f64, samples: samples.len() };
+
+        let impurity = gini(positives, samples.len());
+        if depth >= parameters.max_depth || samples.len() < 2 * parameters.min_samples_leaf || impurity == 0.0 {
+            return leaf;
+        }
+
+        let feature_count = rows.first().map_or(0, Vec::len);
+        let candidates = match parameters.max_features {
+            Some(max_features) if max_features < feature_count => index::sample(rng, feature_count, max_features).into_vec(),
+            _ => (0..feature_count).collect()
+        };
+
+        // (impurity decrease, feature, threshold)
+        let mut best: Option<(f64, usize, f64)> = None;
+        for feature in candidates {
+            self.values.clear();
+            self.values.extend(samples.iter().map(|&i| (rows[i][feature], outcomes[i])));
+            self.values.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let values = &self.values;
+
+            let mut left_positives = 0;
+            for split in 1..values.len() {
+                left_positives += values[split - 1].1 as usize;
+                if values[split - 1].0 == values[split].0 || split < parameters.min_samples_leaf || values.len() - split < parameters.min_samples_leaf {
+                    continue;
+                }
+
+                let right_total = values.len() - split;
+                let weighted_impurity = (split as f64 * gini(left_positives, split)
+                    + right_total as f64 * gini(positives - left_positives, right_total)) / values.len() as f64;
+                let decrease = impurity - weighted_impurity;
+
+                if best.is_none_or(|(best_decrease, _, _)| decrease > best_decrease) {
+                    best = Some((decrease, feature, (values[split - 1].0 + values[split].0) / 2.0));
+                }
+            }
+        }
+
+        let Some((decrease, feature, threshold)) = best else {
+            return leaf;
+        };
+
+        importances[feature] += decrease * samples.len() as f64;
+
+        let (left, right): (Vec<usize>, Vec<usize>) = samples.iter().partition(|&&i| rows[i][feature] <= threshold);
+        Node::Split {
+            feature,
+            threshold,
+            left: Box::new(self.grow(&left, depth + 1, importances, rng)),
+            right: Box::new(self.grow(&right, depth + 1, importances, rng))
+        }
+    }
+}
+
+// Bagged ensemble of trees, each grown on a bootstrap resample with a random subset of features per split
+pub fn fit_forest(rows: &[Vec<f64>], outcomes: &[bool], tree_count: usize, parameters: TreeParameters, rng: &mut impl Rng) -> RandomForest {
+    let feature_count = rows.first().map_or(0, Vec::len);
+    // trees are grown in parallel, each with its own importances summed after
+    let (trees, tree_importances): (Vec<_>, Vec<_>) = stats::seeds(tree_count, rng)
+        .into_par_iter()
+        .map(|seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut importances = vec![0.0; feature_count];
+            let samples = (0..rows.len()).map(|_| rng.random_range(0..rows.len())).collect::<Vec<_>>();
+            (fit_tree(rows, outcomes, &samples, parameters, 0, &mut importances, &mut rng), importances)
+        })
+        .unzip();
+    let mut importances = vec![0.0; feature_count];
+    for tree_importances in tree_importances {
+        importances.iter_mut().zip(tree_importances).for_each(|(importance, tree_importance)| *importance += tree_importance);
+    }
+
+    let total_importance = importances.iter().sum::<f64>();
+    if total_importance > 0.0 {
+        importances.iter_mut().for_each(|importance| *importance /= total_importance);
+    }
+
+    RandomForest { trees, importances }
+}
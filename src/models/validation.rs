@@ -0,0 +1,136 @@
+use rand::{seq::SliceRandom, Rng};
+
+#[derive(Debug, Clone, Copy)]
+pub struct FoldScore {
+    pub accuracy: f64,
+    // undefined when the held out fold only contains one class
+    pub auc: Option<f64>
+}
+
+#[derive(Debug, Clone)]
+pub struct CrossValidation {
+    pub folds: Vec<FoldScore>,
+    pub majority_accuracy: f64
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Spread {
+    pub mean: f64,
+    pub standard_deviation: f64,
+    pub min: f64,
+    pub max: f64
+}
+
+impl Spread {
+    fn of(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (values.len() as f64 - 1.0).max(1.0);
+        Some(Spread {
+            mean,
+            standard_deviation: variance.sqrt(),
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+        })
+    }
+}
+
+impl std::fmt::Display for Spread {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.4} ± {:.4} [{:.4}, {:.4}]", self.mean, self.standard_deviation, self.min, self.max)?;
+        Ok(())
+    }
+}
+
+impl CrossValidation {
+    pub fn accuracy(&self) -> Option<Spread> {
+        Spread::of(&self.folds.iter().map(|fold| fold.accuracy).collect::<Vec<_>>())
+    }
+
+    pub fn auc(&self) -> Option<Spread> {
+        Spread::of(&self.folds.iter().filter_map(|fold| fold.auc).collect::<Vec<_>>())
+    }
+}
+
+impl std::fmt::Display for CrossValidation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let na = || String::from("n/a");
+        write!(f, "{}-Fold Cross-Validation: Accuracy {} (Majority Class {:.4}), AUC {}", 
+            self.folds.len(), 
+            self.accuracy().map_or_else(na, |spread| spread.to_string()), 
+            self.majority_accuracy, 
+            self.auc().map_or_else(na, |spread| spread.to_string()))?;
+        Ok(())
+    }
+}
+
+// Area under the ROC curve as the probability a random positive is scored above a random negative, ties count half
+pub fn auc(predictions: &[f64], outcomes: &[bool]) -> Option<f64> {
+    let positives = predictions.iter().zip(outcomes).filter(|(_, &outcome)| outcome).map(|(&prediction, _)| prediction).collect::<Vec<_>>();
+    let negatives = predictions.iter().zip(outcomes).filter(|(_, &outcome)| !outcome).map(|(&prediction, _)| prediction).collect::<Vec<_>>();
+    if positives.is_empty() || negatives.is_empty() {
+        return None;
+    }
+
+    let wins = positives
+        .iter()
+        .flat_map(|positive| negatives.iter().map(move |negative| match positive.total_cmp(negative) {
+            std::cmp::Ordering::Greater => 1.0,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Less => 0.0
+        }))
+        .sum::<f64>();
+
+    Some(wins / (positives.len() * negatives.len()) as f64)
+}
+
+// Stratified k-fold cross-validation. Positives and negatives are dealt into folds separately so rare outcomes like
+// dates show up in every held out fold. `fit_predict` trains on the first rows and outcomes and predicts the third.
+pub fn cross_validate<R: Rng>(
+    rows: &[Vec<f64>],
+    outcomes: &[bool],
+    fold_count: usize,
+    rng: &mut R,
+    mut fit_predict: impl FnMut(&[Vec<f64>], &[bool], &[Vec<f64>], &mut R) -> Vec<f64>
+) -> CrossValidation {
+    let mut positives = (0..rows.len()).filter(|&i| outcomes[i]).collect::<Vec<_>>();
+    let mut negatives = (0..rows.len()).filter(|&i| !outcomes[i]).collect::<Vec<_>>();
+    positives.shuffle(rng);
+    negatives.shuffle(rng);
+
+    let mut assignments = vec![0; rows.len()];
+    for (position, &i) in positives.iter().chain(&negatives).enumerate() {
+        assignments[i] = position % fold_count;
+    }
+
+    let folds = (0..fold_count)
+        .map(|fold| {
+            let (train_rows, train_outcomes): (Vec<_>, Vec<_>) = (0..rows.len())
+                .filter(|&i| assignments[i] != fold)
+                .map(|i| (rows[i].clone(), outcomes[i]))
+                .unzip();
+            let (test_rows, test_outcomes): (Vec<_>, Vec<_>) = (0..rows.len())
+                .filter(|&i| assignments[i] == fold)
+                .map(|i| (rows[i].clone(), outcomes[i]))
+                .unzip();
+
+            let predictions = fit_predict(&train_rows, &train_outcomes, &test_rows, rng);
+            let correct = predictions
+                .iter()
+                .zip(&test_outcomes)
+                .filter(|(&prediction, &outcome)| (prediction >= 0.5) == outcome)
+                .count();
+
+            FoldScore {
+                accuracy: correct as f64 / test_outcomes.len().max(1) as f64,
+                auc: auc(&predictions, &test_outcomes)
+            }
+        })
+        .collect();
+
+    let positive_rate = positives.len() as f64 / rows.len().max(1) as f64;
+    CrossValidation { folds, majority_accuracy: positive_rate.max(1.0 - positive_rate) }
+}
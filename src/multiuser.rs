@@ -0,0 +1,141 @@
+use std::{error::Error, path::PathBuf};
+
+use crate::{attribute, baseline, format, preference, stats, HingeProfile, WhoLastReplied};
+
+// One person's matches file, written NAME=PATH or NAME=PATH@GEOGRAPHY when their baselines are for another geography
+#[derive(Debug, Clone)]
+pub struct UserFile {
+    pub name: String,
+    pub path: PathBuf,
+    pub geography: Option<String>
+}
+
+impl std::str::FromStr for UserFile {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, rest) = value.split_once('=').ok_or_else(|| format!("Expected NAME=PATH for user {}", value))?;
+        let (path, geography) = match rest.split_once('@') {
+            Some((path, geography)) => (path, Some(geography.trim().to_string())),
+            None => (rest, None)
+        };
+        if name.trim().is_empty() || path.trim().is_empty() {
+            return Err(format!("Expected NAME=PATH for user {}", value));
+        }
+        Ok(UserFile { name: name.trim().to_string(), path: PathBuf::from(path.trim()), geography })
+    }
+}
+
+type Outcome = fn(&HingeProfile) -> bool;
+
+const OUTCOMES: [(&str, Outcome); 4] = [
+    ("Convo Rate", |profile| profile.convo),
+    ("Date Rate", |profile| profile.who_last_replied == WhoLastReplied::Met),
+    ("You Ghosted", |profile| profile.who_last_replied == WhoLastReplied::Them),
+    ("They Ghosted", |profile| profile.who_last_replied == WhoLastReplied::You)
+];
+
+// Ages kept per user for the median, far more than a matches file has so the median is exact short of huge files
+const AGE_SAMPLE: usize = 10_000;
+
+// Everything the comparison needs of one user's matches or of them pooled, counted a profile at a time so the profiles
+// never have to be held at once
+pub struct Tally {
+    matches: u32,
+    reached: [u32; OUTCOMES.len()],
+    religions: Vec<attribute::OutcomeRates>,
+    ages: stats::Reservoir<f64>
+}
+
+impl Tally {
+    pub fn new(seed: u64) -> Tally {
+        Tally { matches: 0, reached: [0; OUTCOMES.len()], religions: Vec::new(), ages: stats::Reservoir::new(AGE_SAMPLE, seed) }
+    }
+
+    pub fn add(&mut self, profile: &HingeProfile) {
+        self.matches += 1;
+        for (reached, (_, outcome)) in self.reached.iter_mut().zip(OUTCOMES) {
+            *reached += outcome(profile) as u32;
+        }
+        if let Some(religion) = &profile.religion {
+            attribute::add_outcome(&mut self.religions, religion, profile);
+        }
+        if let Some(age) = profile.age {
+            self.ages.add(age as f64);
+        }
+    }
+
+    fn share(&self, outcome: usize) -> String {
+        format::percent(self.reached[outcome] as f64 / self.matches.max(1) as f64)
+    }
+
+    fn median_age(&self) -> String {
+        let mut ages = self.ages.values().to_vec();
+        ages.sort_by(f64::total_cmp);
+        match ages.is_empty() {
+            true => "-".to_string(),
+            false => format::decimal(stats::quantile(&ages, 0.5), 1)
+        }
+    }
+}
+
+// Outcome metrics side by side for every user and pooled, a test of whether the users differ, and the religion
+// preference of the pooled matches. Pooling expects each user's matches to follow their own baseline, so the pooled
+// expected share of a category is the average of the users' baseline shares weighted by their number of matches.
+pub fn print_comparison(
+    users: &[(UserFile, Tally)],
+    pooled: &Tally,
+    religion_baseline: &std::path::Path,
+    units: baseline::Units,
+    geography: &str,
+    index_formula: stats::IndexFormula,
+    sample_cutoff: u32
+) -> Result<(), Box<dyn Error>> {
+    let columns = users.iter().map(|(user, tally)| (user.name.as_str(), tally)).chain([("Pooled", pooled)]).collect::<Vec<_>>();
+
+    println!("\n\t         Outcomes by User");
+    println!("\t{:<15}   {}", "", columns.iter().map(|(name, _)| format!("{:<12}", name)).collect::<Vec<_>>().join("   "));
+    println!("\t{:<15}   {}", "Matches", columns.iter().map(|(_, tally)| format!("{:<12}", format::count(tally.matches))).collect::<Vec<_>>().join("   "));
+    for (index, (label, _)) in OUTCOMES.iter().enumerate() {
+        println!("\t{:<15}   {}", label, columns.iter().map(|(_, tally)| format!("{:<12}", tally.share(index))).collect::<Vec<_>>().join("   "));
+    }
+    println!("\t{:<15}   {}", "Median Age", columns.iter().map(|(_, tally)| format!("{:<12}", tally.median_age())).collect::<Vec<_>>().join("   "));
+
+    for (index, (label, _)) in OUTCOMES.iter().enumerate() {
+        let table = users.iter().map(|(_, tally)| vec![tally.reached[index], tally.matches - tally.reached[index]]).collect::<Vec<_>>();
+        match stats::independence_test(&table) {
+            Some(test) => println!("\t{} Independent of User: {}", label, test),
+            None => println!("\t{} Independent of User: not enough variation to test", label)
+        }
+    }
+
+    if pooled.religions.is_empty() {
+        println!("\nNo profiles have a religion, skipping pooled religion preference");
+        return Ok(());
+    }
+
+    let mut categories: Vec<preference::Category> = Vec::new();
+    let mut total = 0.0;
+    for (user, tally) in users {
+        let baseline = attribute::load_baseline(religion_baseline, user.geography.as_deref().unwrap_or(geography), units)?;
+        let mut rates = tally.religions.clone();
+        attribute::sort_outcome_rates(&mut rates);
+        let user_categories = attribute::baseline_categories(&rates, &baseline);
+        let matches = user_categories.iter().map(|category| category.count).sum::<u32>() as f64;
+        total += matches;
+
+        for category in user_categories {
+            match categories.iter_mut().find(|pooled| pooled.label.eq_ignore_ascii_case(&category.label)) {
+                Some(pooled) => {
+                    pooled.count += category.count;
+                    pooled.population += category.population * matches;
+                },
+                None => categories.push(preference::Category { population: category.population * matches, ..category })
+            }
+        }
+    }
+    categories.iter_mut().for_each(|category| category.population /= total.max(1.0));
+
+    preference::print_preferences("Pooled Religion", &categories, index_formula, sample_cutoff);
+    Ok(())
+}
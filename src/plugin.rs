@@ -0,0 +1,123 @@
+use std::{collections::BTreeMap, error::Error, path::{Path, PathBuf}};
+
+use wasmtime::{Config, Engine, Instance, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::{api, export, HingeProfile};
+
+// Version of the plugin interface below, a plugin built against another version is refused rather than misread
+pub const ABI_VERSION: i32 = 1;
+
+// Instructions a plugin may run per analysis, so one stuck in a loop fails instead of hanging the run
+const FUEL: u64 = 10_000_000_000;
+
+// Memory a plugin may grow to on top of the input it's given, so one allocating without end fails instead of taking
+// the machine's memory with it
+const MEMORY: usize = 256 * 1024 * 1024;
+
+// A WebAssembly module computing metrics from the matches, run by wasmtime with no imports so it can't reach files,
+// the network or anything else outside its own memory. It exports
+//
+//     memory                                 its linear memory
+//     hinge_abi_version() -> i32             the ABI_VERSION it was built against
+//     hinge_alloc(length: i32) -> i32        the address of length bytes the input can be written to
+//     hinge_metrics(input: i32, length: i32) -> i64
+//
+// hinge_metrics reads the input, JSON of the abi_version, the profiles as the API returns them and the conversations
+// of --export when given, and returns the address of its output in the high 32 bits and its length in the low 32
+// bits. The output is a JSON object of numbers by metric name, e.g. {"median_reply_hours": 5.5}. plugins/dates.wat is
+// a small example.
+pub struct Plugin {
+    path: PathBuf,
+    engine: Engine,
+    module: Module
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Input<'a> {
+    abi_version: i32,
+    profiles: Vec<api::ProfileRow>,
+    conversations: Vec<Conversation<'a>>
+}
+
+// A match of the export with its messages in the order they were sent
+#[derive(Debug, serde::Serialize)]
+struct Conversation<'a> {
+    id: Option<&'a str>,
+    matched_at: String,
+    met: bool,
+    messages: Vec<Message<'a>>
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Message<'a> {
+    from_me: bool,
+    sent_at: String,
+    body: &'a str
+}
+
+// The input of every plugin, serialized once since it's the same for all of them
+pub fn input(profiles: &[HingeProfile], export: Option<&export::Export>) -> Result<Vec<u8>, serde_json::Error> {
+    let conversations = export.into_iter().flat_map(|export| export.matches()).map(|(matched_at, interaction)| Conversation {
+        id: interaction.id.as_deref(),
+        matched_at: matched_at.to_string(),
+        met: interaction.met(),
+        messages: interaction
+            .messages()
+            .into_iter()
+            .map(|message| Message { from_me: message.sender == export::Sender::Me, sent_at: message.timestamp.to_string(), body: &message.body })
+            .collect()
+    });
+    serde_json::to_vec(&Input { abi_version: ABI_VERSION, profiles: profiles.iter().map(api::ProfileRow::from).collect(), conversations: conversations.collect() })
+}
+
+impl Plugin {
+    // Compiles a .wasm module, or a .wat one written by hand
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|err| format!("Could not start the plugin runtime: {}", err))?;
+        let module = Module::from_file(&engine, path).map_err(|err| format!("Could not compile the plugin {}: {:#}", path.display(), err))?;
+        Ok(Plugin { path: path.to_path_buf(), engine, module })
+    }
+
+    // Runs the plugin on the input in a fresh instance, so nothing it keeps in memory carries over between runs
+    pub fn metrics(&self, input: &[u8]) -> Result<BTreeMap<String, f64>, Box<dyn Error>> {
+        // traps carry a backtrace of the plugin, which means little to whoever runs it
+        let failed = |err: wasmtime::Error| match err.downcast_ref::<wasmtime::Trap>() {
+            Some(trap) => format!("The plugin {} failed: {}", self.path.display(), trap),
+            None => format!("The plugin {} failed: {:#}", self.path.display(), err)
+        };
+        if let Some(import) = self.module.imports().next() {
+            return Err(format!("The plugin {} imports {} from {}, plugins can't import anything", self.path.display(), import.name(), import.module()).into());
+        }
+        let limits = StoreLimitsBuilder::new().memory_size(MEMORY.saturating_add(input.len())).instances(1).memories(1).build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits: &mut StoreLimits| limits);
+        store.set_fuel(FUEL).map_err(failed)?;
+        let instance = Instance::new(&mut store, &self.module, &[]).map_err(failed)?;
+
+        let version = instance.get_typed_func::<(), i32>(&mut store, "hinge_abi_version").and_then(|version| version.call(&mut store, ())).map_err(failed)?;
+        if version != ABI_VERSION {
+            return Err(format!("The plugin {} is for version {} of the plugin interface, this is version {}", self.path.display(), version, ABI_VERSION).into());
+        }
+        let memory = instance.get_memory(&mut store, "memory").ok_or_else(|| format!("The plugin {} exports no memory", self.path.display()))?;
+        let length = i32::try_from(input.len()).map_err(|_| "The matches are too large to give a plugin")?;
+        let address = instance.get_typed_func::<i32, i32>(&mut store, "hinge_alloc").and_then(|alloc| alloc.call(&mut store, length)).map_err(failed)?;
+        memory
+            .write(&mut store, address as u32 as usize, input)
+            .map_err(|_| format!("The plugin {} allocated too little memory for the input", self.path.display()))?;
+
+        let output = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, "hinge_metrics")
+            .and_then(|metrics| metrics.call(&mut store, (address, length)))
+            .map_err(failed)?;
+        // the output is read where it is rather than copied, so a made up length can't allocate anything
+        let (address, length) = ((output as u64 >> 32) as usize, (output as u64 & 0xFFFF_FFFF) as usize);
+        let output = address
+            .checked_add(length)
+            .filter(|&end| end <= memory.data_size(&store))
+            .map(|end| &memory.data(&store)[address..end])
+            .ok_or_else(|| format!("The plugin {} returned output outside its memory", self.path.display()))?;
+        serde_json::from_slice(output).map_err(|err| format!("The plugin {} should return a JSON object of numbers: {}", self.path.display(), err).into())
+    }
+}
@@ -0,0 +1,40 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Politics {
+    Liberal,
+    Moderate,
+    Conservative,
+    Other
+}
+
+impl Politics {
+    pub fn label(self) -> &'static str {
+        match self {
+            Politics::Liberal => "Liberal",
+            Politics::Moderate => "Moderate",
+            Politics::Conservative => "Conservative",
+            Politics::Other => "Other"
+        }
+    }
+}
+
+impl std::str::FromStr for Politics {
+    type Err = &'static str;
+
+    // Hinge's Not Political is grouped with Other
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "liberal" | "progressive" | "left" => Ok(Politics::Liberal),
+            "moderate" | "centrist" | "center" => Ok(Politics::Moderate),
+            "conservative" | "right" => Ok(Politics::Conservative),
+            "other" | "not political" | "apolitical" | "none" => Ok(Politics::Other),
+            _ => Err("Invalid value for Politics")
+        }
+    }
+}
+
+impl std::fmt::Display for Politics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+use std::cmp::Ordering;
+
+use colored::Colorize;
+
+use crate::{format, stats, warning};
+
+// A cell of a baseline population (a race, an age bracket, ...) with the number of matches that fell into it and its
+// share of the population. Every preference analysis works on a list of these regardless of the attribute.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Category {
+    pub label: String,
+    pub count: u32,
+    pub population: f64
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Preference {
+    pub label: String,
+    pub weight: Option<f64>,
+    pub count: u32,
+    pub population: f64,
+    pub residual: Option<f64>,
+    pub effect_size: f64
+}
+
+impl std::fmt::Display for Preference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let weight = self.weight.map_or(String::from("-"), |weight| format!("{:.4}", weight));
+        let residual = self.residual.map_or(String::from("n/a"), |residual| format!("{:+.2}", residual));
+        write!(f, "{:<55}   {:<7}  {:<7}   {}     {:>7}    {:>+7.3}", self.label, weight, self.count, format::percent(self.population), residual, self.effect_size)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct BayesianPreference {
+    pub label: String,
+    pub index: f64,
+    pub lower: f64,
+    pub upper: f64,
+    pub count: u32,
+    pub population: f64
+}
+
+impl std::fmt::Display for BayesianPreference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:<55}   {:>7.4}   [{:>7.4}, {:>7.4}]   {:<7}   {}", self.label, self.index, self.lower, self.upper, self.count, format::percent(self.population))?;
+        Ok(())
+    }
+}
+
+// What to do with baseline cells that have no population on record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ZeroPopulation {
+    /// Leave the cell out of the analysis
+    Exclude,
+    /// Use the cell's share of the national baseline
+    National
+}
+
+// Gives baseline cells with no population their national share when there is one, renormalizing the shares after, and
+// warns about every cell with no population. Cells still without a share are left out by the categories built from
+// the baseline, which is all of them when the national baseline is empty.
+pub fn fill_zero_population(attribute: &str, mut shares: Vec<(String, &mut f64)>, national: &[(String, f64)]) {
+    let mut filled = false;
+    for (label, share) in shares.iter_mut() {
+        if **share > 0.0 {
+            continue;
+        }
+
+        let national_share = national
+            .iter()
+            .find(|(category, _)| category.eq_ignore_ascii_case(label))
+            .map(|(_, national_share)| *national_share)
+            .filter(|national_share| *national_share > 0.0);
+        let warning = match national_share {
+            Some(national_share) => {
+                **share = national_share;
+                filled = true;
+                format!("Warning: {} has no population in the {} baseline, using its national share of {}", label, attribute, format::inline_percent(national_share, 3))
+            },
+            None => format!("Warning: {} has no population in the {} baseline, left out of the {} analysis", label, attribute, attribute)
+        };
+        warning::warn(warning);
+    }
+
+    if filled {
+        let total = shares.iter().map(|(_, share)| **share).sum::<f64>();
+        shares.iter_mut().for_each(|(_, share)| **share /= total);
+    }
+}
+
+// Higher scores first with unscored categories last, ties broken by more matches and then the label
+fn rank(a: (Option<f64>, u32, &str), b: (Option<f64>, u32, &str)) -> Ordering {
+    match (a.0, b.0) {
+        (Some(a_score), Some(b_score)) => b_score.total_cmp(&a_score),
+        (a_score, b_score) => b_score.is_some().cmp(&a_score.is_some())
+    }
+    .then_with(|| b.1.cmp(&a.1))
+    .then_with(|| a.2.cmp(b.2))
+}
+
+// Categories no score can be computed for because the baseline gives them no population share, or one that isn't a
+// number
+pub fn data_problems(categories: &[Category]) -> Vec<String> {
+    categories
+        .iter()
+        .filter(|category| !category.population.is_finite() || category.population <= 0.0)
+        .map(|category| format!("{} has a population share of {} in the baseline, its score is undefined", category.label, category.population))
+        .collect()
+}
+
+// Only age and height keep such categories, the categories of the other attributes leave out cells with no population
+// after fill_zero_population warned about them
+pub fn print_data_problems(categories: &[Category]) {
+    for problem in data_problems(categories) {
+        println!("\t{}", format!("Data problem: {}", problem).yellow().bold());
+    }
+}
+
+pub fn counts(categories: &[Category]) -> Vec<u32> {
+    categories.iter().map(|category| category.count).collect()
+}
+
+pub fn shares(categories: &[Category]) -> Vec<f64> {
+    categories.iter().map(|category| category.population).collect()
+}
+
+// Scores every category with the index formula, categories with fewer matches than the cutoff or a score that isn't a
+// finite number are left unscored. Sorted from the most to the least preferred.
+pub fn preferences(categories: &[Category], index_formula: stats::IndexFormula, sample_cutoff: u32) -> Vec<Preference> {
+    let counts = counts(categories);
+    let shares = shares(categories);
+    let total_count = counts.iter().sum::<u32>() as f64;
+    let total_share = shares.iter().sum::<f64>();
+    let expected = stats::expected_counts(&counts, &shares);
+
+    let mut preferences = categories
+        .iter()
+        .zip(expected)
+        .map(|(category, expected)| Preference {
+            label: category.label.clone(),
+            weight: if category.count < sample_cutoff {
+                None
+            } else {
+                Some(index_formula.score(category.count as f64 / total_count, category.population / total_share)).filter(|weight| weight.is_finite())
+            },
+            count: category.count,
+            population: category.population,
+            residual: stats::standardized_residual(category.count, expected),
+            effect_size: stats::cohens_h(category.count as f64 / total_count, category.population / total_share)
+        })
+        .collect::<Vec<_>>();
+
+    if index_formula == stats::IndexFormula::Normalized {
+        let preferences_total_weight = preferences.iter().filter_map(|preference| preference.weight).sum::<f64>();
+        preferences
+            .iter_mut()
+            .filter_map(|preference| preference.weight.as_mut())
+            .for_each(|weight| *weight /= preferences_total_weight);
+    }
+    preferences.sort_by(|a, b| rank((a.weight, a.count, &a.label), (b.weight, b.count, &b.label)));
+
+    preferences
+}
+
+// Posterior preference index of every category with a population share, 1.0 is matching at the population rate.
+// Sorted from the most to the least preferred.
+pub fn bayesian_preferences(categories: &[Category], prior_strength: f64) -> Vec<BayesianPreference> {
+    let shares = shares(categories);
+    let total_share = shares.iter().sum::<f64>();
+    let posteriors = stats::dirichlet_posterior(&counts(categories), &shares, prior_strength, 0.95);
+
+    let mut preferences = categories
+        .iter()
+        .zip(posteriors)
+        .filter(|(category, _)| category.population > 0.0)
+        .filter_map(|(category, posterior)| {
+            let (posterior, population_share) = (posterior?, category.population / total_share);
+            Some(BayesianPreference {
+                label: category.label.clone(),
+                index: posterior.mean / population_share,
+                lower: posterior.lower / population_share,
+                upper: posterior.upper / population_share,
+                count: category.count,
+                population: category.population
+            })
+        })
+        .collect::<Vec<_>>();
+    preferences.sort_by(|a, b| rank((Some(a.index), a.count, &a.label), (Some(b.index), b.count, &b.label)));
+
+    preferences
+}
+
+pub fn print_preferences(attribute: &str, categories: &[Category], index_formula: stats::IndexFormula, sample_cutoff: u32) {
+    println!("\n\t         {} Preference Index (Adjusted for Population, Match Sample Cutoff={})", attribute, sample_cutoff);
+    println!("\t{}, - = fewer matches than the cutoff", index_formula.description());
+    println!("\tResidual = (Observed - Expected) / sqrt(Expected), Effect Size = Cohen's h of match share vs population share");
+    println!("\t{:^55}   Score    Matches   Population   Residual   Effect Size", attribute);
+    for preference in preferences(categories, index_formula, sample_cutoff) {
+        println!("\t{}", preference);
+    }
+}
+
+pub fn print_bayesian_preferences(attribute: &str, categories: &[Category], prior_strength: f64) {
+    println!("\n\t         {} Preference Index (Bayesian Dirichlet-Multinomial, Prior Strength={}, 1.0 = Matched at Population Rate)", attribute, prior_strength);
+    println!("\t{:^55}   {:>7}   {:^18}   Matches   Population", attribute, "Index", "95% Credible");
+    for preference in bayesian_preferences(categories, prior_strength) {
+        println!("\t{}", preference);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categories(counts: &[u32], populations: &[f64]) -> Vec<Category> {
+        counts
+            .iter()
+            .zip(populations)
+            .enumerate()
+            .map(|(i, (&count, &population))| Category { label: format!("Category {}", i), count, population })
+            .collect()
+    }
+
+    #[test]
+    fn bayesian_index_is_one_at_the_population_rate() {
+        // shares of a baseline with cells left out, adding up to half
+        for preference in bayesian_preferences(&categories(&[10, 30], &[0.125, 0.375]), 14.0) {
+            assert!((preference.index - 1.0).abs() < 1e-9, "{}", preference);
+            assert!(preference.lower < 1.0 && preference.upper > 1.0, "{}", preference);
+        }
+    }
+
+    #[test]
+    fn bayesian_index_ignores_the_scale_of_the_shares() {
+        let scaled = bayesian_preferences(&categories(&[12, 3, 25], &[0.1, 0.15, 0.25]), 14.0);
+        let normalized = bayesian_preferences(&categories(&[12, 3, 25], &[0.2, 0.3, 0.5]), 14.0);
+        for (scaled, normalized) in scaled.iter().zip(&normalized) {
+            assert_eq!(scaled.label, normalized.label);
+            for (a, b) in [(scaled.index, normalized.index), (scaled.lower, normalized.lower), (scaled.upper, normalized.upper)] {
+                assert!((a - b).abs() < 1e-9, "{} is not {}", scaled, normalized);
+            }
+        }
+    }
+}
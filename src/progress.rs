@@ -0,0 +1,64 @@
+use std::{cell::Cell, sync::atomic::{AtomicBool, Ordering}, time::Duration};
+
+use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
+
+// Off for output meant for other programs, bars are drawn on stderr and hidden anyway when it isn't a terminal
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+thread_local! {
+    // Set while a caller on this thread runs with bars hidden, without turning them off for the rest of the process
+    static HIDDEN: Cell<bool> = const { Cell::new(false) };
+}
+
+const TICK: Duration = Duration::from_millis(100);
+
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+}
+
+// Restores whether bars were hidden on the thread, also when the hidden work panics
+struct Restore(bool);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        HIDDEN.set(self.0);
+    }
+}
+
+// Runs `work` with the bars it starts on this thread hidden, for callers like the C library whose host owns the terminal
+pub fn hidden<T>(work: impl FnOnce() -> T) -> T {
+    let _restore = Restore(HIDDEN.replace(true));
+    work()
+}
+
+fn hidden_unless_enabled(bar: ProgressBar) -> ProgressBar {
+    if ENABLED.load(Ordering::Relaxed) && !HIDDEN.get() {
+        bar
+    } else {
+        ProgressBar::hidden()
+    }
+}
+
+// A phase with a known number of steps, like the chats of an export
+pub fn bar(len: usize, message: &'static str) -> ProgressBar {
+    let bar = ProgressBar::new(len as u64)
+        .with_style(ProgressStyle::with_template("{msg:<24} [{bar:40}] {pos}/{len} ({eta})").expect("Bad progress template").progress_chars("=> "))
+        .with_message(message)
+        .with_finish(ProgressFinish::AndClear);
+    hidden_unless_enabled(bar)
+}
+
+// Reading a number of bytes, like a file out of the export ZIP
+pub fn bytes(len: u64, message: &'static str) -> ProgressBar {
+    let bar = ProgressBar::new(len)
+        .with_style(ProgressStyle::with_template("{msg:<24} [{bar:40}] {bytes}/{total_bytes} ({eta})").expect("Bad progress template").progress_chars("=> "))
+        .with_message(message);
+    hidden_unless_enabled(bar)
+}
+
+// A phase with no measure of how far along it is, like parsing JSON
+pub fn spinner(message: &'static str) -> ProgressBar {
+    let spinner = hidden_unless_enabled(ProgressBar::new_spinner().with_message(message));
+    spinner.enable_steady_tick(TICK);
+    spinner
+}
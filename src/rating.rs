@@ -0,0 +1,49 @@
+use crate::{attribute, format, stats, HingeProfile};
+
+// Numeric profile features checked against the date rating
+const FEATURES: [&str; 3] = ["age", "height", "distance_miles"];
+
+fn mean(ratings: &[u8]) -> f64 {
+    ratings.iter().map(|&rating| rating as f64).sum::<f64>() / ratings.len().max(1) as f64
+}
+
+// Average 1-5 rating of your dates overall and for every value of every categorical attribute, followed by the
+// Pearson correlation of each numeric feature with the rating
+pub fn print_date_ratings(profiles: &[HingeProfile]) {
+    let rated = profiles.iter().filter_map(|profile| Some((profile, profile.date_rating?))).collect::<Vec<_>>();
+    let ratings = rated.iter().map(|(_, rating)| *rating).collect::<Vec<_>>();
+
+    println!("\n\t         Date Quality ({} Rated Dates)", rated.len());
+    println!("\tMean Rating: {} / 5", format::decimal(mean(&ratings), 2));
+    println!("\t{:<6}   Dates", "Rating");
+    for rating in 1..=5 {
+        println!("\t{:<6}   {}", rating, ratings.iter().filter(|other| **other == rating).count());
+    }
+
+    println!("\n\t         Mean Date Rating by Attribute");
+    println!("\t{:<12}   {:<25}   Dates     Mean Rating", "Attribute", "Value");
+    for name in attribute::NAMES {
+        let values = rated.iter().map(|(profile, _)| attribute::value(profile, name)).collect::<Vec<_>>();
+        for label in attribute::labels_by_count(values.iter().flatten()) {
+            let group = rated
+                .iter()
+                .zip(&values)
+                .filter(|(_, value)| value.as_ref() == Some(&label))
+                .map(|((_, rating), _)| *rating)
+                .collect::<Vec<_>>();
+            println!("\t{:<12}   {:<25}   {:<7}   {}", attribute::title(name), label, group.len(), format::decimal(mean(&group), 2));
+        }
+    }
+
+    println!("\n\t         Correlation of Features with Date Rating");
+    for feature in FEATURES {
+        let (values, feature_ratings): (Vec<f64>, Vec<f64>) = rated
+            .iter()
+            .filter_map(|(profile, rating)| Some((attribute::value(profile, feature)?.parse::<f64>().ok()?, *rating as f64)))
+            .unzip();
+        match stats::pearson(&values, &feature_ratings) {
+            Some(correlation) => println!("\t{:<14}   {}", attribute::title(feature), correlation),
+            None => println!("\t{:<14}   not enough rated dates with a value to test", attribute::title(feature))
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::io::{self, BufRead, Write};
+
+use colored::Colorize;
+
+use crate::{attribute, filter::Filter, funnel, metric, HingeProfile};
+
+const HELP: &str = "\
+filter EXPR          keep only the matches fitting EXPR as well, in the syntax of --filter
+by ATTRIBUTE         split what is shown by the values of an attribute, by alone stops splitting
+show outcomes        ghosting, conversation and date metrics, or those of --metrics
+show funnel          the conversation funnel
+show conversations   how conversations fared for each value of the by attribute
+show crosstab ATTR   the by attribute against another
+show names           names of the matches
+status               the filters and by attribute in effect
+reset                back to every match
+quit                 leave, as does end of input";
+
+// What the matches are narrowed to and split by, the matches are read once when the session starts
+#[derive(Debug, Default)]
+struct Session {
+    filters: Vec<Filter>,
+    by: Option<String>
+}
+
+impl Session {
+    fn profiles(&self, profiles: &[HingeProfile]) -> Vec<HingeProfile> {
+        profiles.iter().filter(|profile| self.filters.iter().all(|filter| filter.matches(profile))).cloned().collect()
+    }
+
+    fn by(&self) -> Result<&str, String> {
+        self.by.as_deref().ok_or_else(|| String::from("Pick an attribute to split by first, e.g. by race"))
+    }
+
+    // The profiles for every value of the by attribute, most common first, or all of them without one
+    fn groups(&self, profiles: &[HingeProfile]) -> Vec<(String, Vec<HingeProfile>)> {
+        let Some(by) = &self.by else {
+            return vec![(String::from("All Matches"), profiles.to_vec())];
+        };
+        let values = profiles.iter().map(|profile| attribute::value(profile, by)).collect::<Vec<_>>();
+        attribute::labels_by_count(values.iter().flatten())
+            .into_iter()
+            .map(|label| {
+                let group = profiles.iter().zip(&values).filter(|(_, value)| value.as_ref() == Some(&label)).map(|(profile, _)| profile.clone());
+                (format!("{} = {}", attribute::title(by), label), group.collect())
+            })
+            .collect()
+    }
+
+    fn show(&self, what: &str, profiles: &[HingeProfile], funnel: &funnel::Funnel, computers: &[&dyn metric::MetricComputer]) -> Result<(), String> {
+        let (what, argument) = what.split_once(char::is_whitespace).map_or((what, ""), |(what, argument)| (what, argument.trim()));
+        match what {
+            "outcomes" | "funnel" => {
+                for (title, group) in self.groups(profiles) {
+                    println!("\n\t         {} ({} Profiles)", title, group.len());
+                    if what == "outcomes" {
+                        metric::print(computers, &metric::Matches::new(group.iter())).map_err(|err| err.to_string())?;
+                    } else {
+                        funnel::print_funnel(group.iter(), funnel);
+                    }
+                }
+            },
+            "conversations" => attribute::print_conversation_outcomes(profiles, self.by()?),
+            "crosstab" => attribute::print_crosstab(profiles, self.by()?, &attribute::parse_name(argument)?),
+            "names" => profiles.iter().for_each(|profile| println!("{}", profile.name)),
+            _ => return Err(String::from("Expected show outcomes, funnel, conversations, crosstab ATTR or names"))
+        }
+        Ok(())
+    }
+
+    fn print_status(&self) {
+        match self.filters.is_empty() {
+            true => println!("No filters"),
+            false => self.filters.iter().for_each(|filter| println!("filter {}", filter))
+        }
+        println!("{}", self.by.as_ref().map_or(String::from("Not split"), |by| format!("by {}", by)));
+    }
+}
+
+// Reads commands from standard input until quit or the end of input, keeping the filters and the attribute to split
+// by between commands
+pub fn run(profiles: &[HingeProfile], funnel: &funnel::Funnel, computers: &[&dyn metric::MetricComputer]) -> io::Result<()> {
+    let mut session = Session::default();
+    println!("{} matches read, type help for the commands", profiles.len());
+    let stdin = io::stdin();
+    loop {
+        let current = session.profiles(profiles);
+        print!("hinge ({} matches)> ", current.len());
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            println!();
+            return Ok(());
+        }
+        let line = line.trim();
+        let (command, rest) = line.split_once(char::is_whitespace).map_or((line, ""), |(command, rest)| (command, rest.trim()));
+        let outcome = match command {
+            "" => Ok(()),
+            "help" => {
+                println!("{}", HELP);
+                Ok(())
+            },
+            "quit" | "exit" => return Ok(()),
+            "filter" => rest.parse::<Filter>().map(|filter| session.filters.push(filter)),
+            "by" if rest.is_empty() => {
+                session.by = None;
+                Ok(())
+            },
+            "by" => attribute::parse_name(rest).map(|by| session.by = Some(by)).map_err(|err| format!("Unknown attribute {}, {}", rest, err)),
+            "show" => session.show(rest, &current, funnel, computers),
+            "status" => {
+                session.print_status();
+                Ok(())
+            },
+            "reset" => {
+                session = Session::default();
+                Ok(())
+            },
+            _ => Err(format!("Unknown command {}, type help for the commands", command))
+        };
+        if let Err(err) = outcome {
+            println!("{}", err.red());
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::{collections::BTreeMap, error::Error, fs::File, io, path::Path};
+
+use sha2::Digest;
+
+use crate::{attribute, metric, preference};
+
+// Written when no --template is given, and a starting point for writing one
+pub const DEFAULT_TEMPLATE: &str = include_str!("../templates/report.md");
+
+// Version of the report format in report_version, bumped whenever a field is renamed, removed or changes meaning so
+// consumers of the JSON snapshots and the API can tell the formats apart. Adding a field doesn't bump it.
+pub const REPORT_VERSION: u32 = 1;
+
+// A category's preference. The index is missing for categories under the sample cutoff and the bounds are only there
+// with --bayesian.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema, async_graphql::SimpleObject)]
+pub struct PreferenceRow {
+    pub label: String,
+    pub count: u32,
+    pub population: f64,
+    pub index: Option<f64>,
+    pub lower: Option<f64>,
+    pub upper: Option<f64>
+}
+
+impl From<preference::Preference> for PreferenceRow {
+    fn from(preference: preference::Preference) -> Self {
+        PreferenceRow {
+            label: preference.label,
+            count: preference.count,
+            population: preference.population,
+            index: preference.weight,
+            lower: None,
+            upper: None
+        }
+    }
+}
+
+impl From<preference::BayesianPreference> for PreferenceRow {
+    fn from(preference: preference::BayesianPreference) -> Self {
+        PreferenceRow {
+            label: preference.label,
+            count: preference.count,
+            population: preference.population,
+            index: Some(preference.index),
+            lower: Some(preference.lower),
+            upper: Some(preference.upper)
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema, async_graphql::SimpleObject)]
+pub struct OutcomeRow {
+    pub label: String,
+    pub matches: u32,
+    pub conversations: u32,
+    pub dates: u32,
+    pub conversation_rate: f64,
+    pub date_rate: f64
+}
+
+impl From<&attribute::OutcomeRates> for OutcomeRow {
+    fn from(rates: &attribute::OutcomeRates) -> Self {
+        OutcomeRow {
+            label: rates.label.clone(),
+            matches: rates.matches,
+            conversations: rates.conversations,
+            dates: rates.dates,
+            conversation_rate: rates.conversation_rate(),
+            date_rate: rates.date_rate()
+        }
+    }
+}
+
+// Profiles that reached a stage of the conversation funnel, starting from matches
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct FunnelStage {
+    pub name: String,
+    pub profiles: usize
+}
+
+// One attribute's preferences, with the outcomes of each value for attributes that have them
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema, async_graphql::SimpleObject)]
+pub struct Section {
+    pub title: String,
+    pub preferences: Vec<PreferenceRow>,
+    pub outcomes: Vec<OutcomeRow>
+}
+
+// A file the analysis read, with its SHA-256 so a change to it shows up between reports. The hash is missing for a
+// file that couldn't be read, like a baseline the analysis never needed.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct Input {
+    pub path: String,
+    pub sha256: Option<String>
+}
+
+impl Input {
+    pub fn new(path: &Path) -> Self {
+        let sha256 = File::open(path).and_then(|mut file| {
+            let mut hasher = sha2::Sha256::new();
+            io::copy(&mut file, &mut hasher)?;
+            Ok(format!("{:x}", hasher.finalize()))
+        });
+        Input { path: path.display().to_string(), sha256: sha256.ok() }
+    }
+
+    // An input that was never a file, like the matches handed to the C library
+    pub fn of_bytes(path: &str, bytes: &[u8]) -> Self {
+        Input { path: path.to_string(), sha256: Some(format!("{:x}", sha2::Sha256::digest(bytes))) }
+    }
+}
+
+// What a report was computed from, so two reports can be compared knowing whether the inputs, the options or the
+// code changed. Inputs are keyed by the option that named them, e.g. matches or religion_baseline.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct Provenance {
+    pub crate_version: String,
+    pub inputs: BTreeMap<String, Input>,
+    pub geography: String,
+    pub national_geography: String,
+    pub counties: Vec<String>,
+    pub baseline_source: Option<String>,
+    pub bundled_baselines: Option<String>,
+    pub baseline_units: String,
+    pub zero_population: String,
+    pub filter: Option<String>,
+    pub strictness: String,
+    pub dedup: String,
+    pub sample_cutoff: u32,
+    pub index_formula: String,
+    pub prior_strength: Option<f64>,
+    pub height_baseline: String,
+    pub height_bucket: u8,
+    pub stages: Vec<String>
+}
+
+// Everything a template can use. Sections are keyed by attribute (race, age, height, religion and education) and only
+// there when some profiles have the attribute, so templates can test for them with {% if sections.age %}.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct Report {
+    pub report_version: u32,
+    pub generated: String,
+    pub matches_file: String,
+    pub total_matches: usize,
+    pub conversations: usize,
+    pub dates: usize,
+    pub conversation_rate: f64,
+    pub date_rate: f64,
+    pub bayesian: bool,
+    pub funnel: Vec<FunnelStage>,
+    pub sections: BTreeMap<String, Section>,
+    // numbers of every metric computer by its name, missing from reports before there were metric computers
+    #[serde(default)]
+    pub metrics: BTreeMap<String, metric::Metrics>,
+    // metrics of --script and --plugin by name, missing from reports before there were scripts
+    #[serde(default)]
+    pub custom_metrics: BTreeMap<String, f64>,
+    pub provenance: Provenance
+}
+
+// Tera puts what went wrong in the template in the sources of its error
+fn describe(err: &tera::Error) -> String {
+    let mut description = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        description.push_str(&format!(": {}", err));
+        source = err.source();
+    }
+    description
+}
+
+// The report rendered through a Tera template, see https://keats.github.io/tera/docs/ for the syntax. Nothing is
+// escaped since reports are usually Markdown or plain text.
+pub fn render(report: &Report, template: &str) -> Result<String, Box<dyn Error>> {
+    let context = tera::Context::from_serialize(report)?;
+    tera::Tera::one_off(template, &context, false).map_err(|err| format!("Could not render the report template: {}", describe(&err)).into())
+}
+
+// JSON Schema of the report as saved in snapshots and returned by the API, for consumers to validate against
+pub fn json_schema() -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&schemars::schema_for!(Report))
+}
@@ -0,0 +1,107 @@
+use std::{collections::BTreeMap, error::Error, path::Path};
+
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+
+use crate::{api, attribute, format, HingeProfile};
+
+// Limits of a script, so one stuck in a loop or building ever larger values fails instead of hanging the run or
+// taking the machine's memory. Operations count per call of derive or metrics, and the profiles handed to metrics are
+// an array like any other so there can be at most MAX_ARRAY_SIZE of them.
+const MAX_OPERATIONS: u64 = 100_000_000;
+const MAX_CALL_LEVELS: usize = 64;
+const MAX_STRING_SIZE: usize = 1024 * 1024;
+const MAX_ARRAY_SIZE: usize = 100_000;
+const MAX_MAP_SIZE: usize = 10_000;
+
+// A Rhai script (https://rhai.rs/book/) adding to the analysis. Every profile reaches it as a map with the fields of
+// the API's profiles, e.g. profile.age or profile.last_reply == "Met". The script can define either function:
+//
+//     fn derive(profile) { #{ age_group: if profile.age < 30 { "Under 30" } else { "30 and Over" } } }
+//     fn metrics(profiles) { #{ tall_share: profiles.filter(|p| p.height >= 72).len() / profiles.len().to_float() } }
+//
+// derive returns fields derived from one profile, whose outcomes are reported like the built in attributes, and
+// metrics returns numbers computed from every profile with its derived fields, reported as custom metrics.
+pub struct Script {
+    engine: Engine,
+    ast: AST
+}
+
+// What a script added to the analysis, derived fields by name and then by profile in the order of the profiles
+#[derive(Debug, Clone, Default)]
+pub struct Additions {
+    pub fields: BTreeMap<String, Vec<Option<String>>>,
+    pub metrics: BTreeMap<String, f64>
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(MAX_OPERATIONS)
+            .set_max_call_levels(MAX_CALL_LEVELS)
+            .set_max_string_size(MAX_STRING_SIZE)
+            .set_max_array_size(MAX_ARRAY_SIZE)
+            .set_max_map_size(MAX_MAP_SIZE);
+        let ast = engine.compile_file(path.into()).map_err(|err| format!("Could not compile {}: {}", path.display(), err))?;
+        Ok(Script { engine, ast })
+    }
+
+    fn defines(&self, name: &str) -> bool {
+        self.ast.iter_functions().any(|function| function.name == name && function.params.len() == 1)
+    }
+
+    fn call(&self, name: &str, argument: Dynamic) -> Result<Map, Box<dyn Error>> {
+        self.engine
+            .call_fn::<Map>(&mut Scope::new(), &self.ast, name, (argument,))
+            .map_err(|err| format!("The script's {} failed: {}", name, err).into())
+    }
+
+    // Runs derive on every profile and metrics on all of them, leaving out whichever the script doesn't define
+    pub fn run(&self, profiles: &[HingeProfile]) -> Result<Additions, Box<dyn Error>> {
+        if profiles.len() > MAX_ARRAY_SIZE {
+            return Err(format!("Scripts can be run on at most {} profiles", format::count(MAX_ARRAY_SIZE)).into());
+        }
+        let mut maps = profiles
+            .iter()
+            .map(|profile| rhai::serde::to_dynamic(api::ProfileRow::from(profile)).map(|profile| profile.cast::<Map>()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut additions = Additions::default();
+        if self.defines("derive") {
+            for (i, map) in maps.iter_mut().enumerate() {
+                for (name, value) in self.call("derive", map.clone().into())? {
+                    let name = name.to_string();
+                    if attribute::FIELDS.contains(&name.as_str()) {
+                        return Err(format!("The script's derive returned {}, which is already a field of every profile", name).into());
+                    }
+                    // a value the profile doesn't have is left out like a blank column
+                    let text = (!value.is_unit()).then(|| value.to_string());
+                    additions.fields.entry(name.clone()).or_insert_with(|| vec![None; profiles.len()])[i] = text;
+                    map.insert(name.into(), value);
+                }
+            }
+        }
+
+        if self.defines("metrics") {
+            let profiles = maps.into_iter().map(Dynamic::from).collect::<rhai::Array>();
+            for (name, value) in self.call("metrics", profiles.into())? {
+                let number = value.as_float().or_else(|_| value.as_int().map(|value| value as f64));
+                let number = number.map_err(|kind| format!("The script's metric {} is a {} rather than a number", name, kind))?;
+                additions.metrics.insert(name.to_string(), number);
+            }
+        }
+        Ok(additions)
+    }
+}
+
+// Outcomes of every value of a derived field, most common first
+pub fn outcome_rates(profiles: &[HingeProfile], values: &[Option<String>]) -> Vec<attribute::OutcomeRates> {
+    let mut rates = Vec::new();
+    for (profile, value) in profiles.iter().zip(values) {
+        if let Some(value) = value {
+            attribute::add_outcome(&mut rates, value, profile);
+        }
+    }
+    attribute::sort_outcome_rates(&mut rates);
+    rates
+}
@@ -0,0 +1,271 @@
+use std::{
+    error::Error,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    panic::{self, AssertUnwindSafe},
+    thread,
+    time::{Duration, Instant}
+};
+
+// The request line with its query parameters decoded and the body, headers are skipped apart from its length
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub body: String
+}
+
+impl Request {
+    // Value of a query parameter, e.g. the expression of /api/profiles?filter=age%20%3E%3D%2030
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.query.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+// How long a client may take to send its whole request or read the response, connections are answered one at a time
+// so a stalled one holds up the rest until then
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Largest body accepted, GraphQL queries are the only bodies and they are small
+const MAX_BODY_LENGTH: usize = 1024 * 1024;
+
+// Largest request line and headers accepted together, and the most headers
+const MAX_HEADER_LENGTH: usize = 16 * 1024;
+const MAX_HEADERS: usize = 100;
+
+// Reads the stream until one deadline for the whole request, where a read timeout alone restarts with every read
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    until: Instant
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.until.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+// Undoes the percent encoding of a query string, with + for spaces
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            },
+            (b'+', _) => {
+                decoded.push(b' ');
+                i += 1;
+            },
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String
+}
+
+impl Response {
+    pub fn text(status: u16, body: impl Into<String>) -> Self {
+        Response { status, content_type: "text/plain; charset=utf-8", body: body.into() }
+    }
+
+    pub fn json(status: u16, body: &impl serde::Serialize) -> Self {
+        match serde_json::to_string_pretty(body) {
+            Ok(body) => Response { status, content_type: "application/json", body },
+            Err(err) => Response::text(500, format!("{}\n", err))
+        }
+    }
+
+    pub fn not_found(path: &str) -> Self {
+        Response::text(404, format!("Nothing at {}\n", path))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            413 => "Payload Too Large",
+            431 => "Request Header Fields Too Large",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error"
+        }
+    }
+}
+
+// A timed out read answers 408 and anything else that went wrong 400
+fn failed(err: io::Error) -> Response {
+    match err.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => Response::text(408, format!("Requests must arrive within {} seconds\n", TIMEOUT.as_secs())),
+        _ => Response::text(400, format!("{}\n", err))
+    }
+}
+
+// The next line of the request line and headers, which share MAX_HEADER_LENGTH
+fn read_header_line(reader: &mut impl BufRead, remaining: &mut usize) -> Result<String, Response> {
+    let mut line = String::new();
+    let read = reader.take(*remaining as u64).read_line(&mut line).map_err(failed)?;
+    *remaining -= read;
+    if *remaining == 0 && !line.ends_with('\n') {
+        return Err(Response::text(431, format!("The request line and headers are limited to {} bytes\n", MAX_HEADER_LENGTH)));
+    }
+    Ok(line)
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, Response> {
+    let malformed = |err: &dyn std::fmt::Display| Response::text(400, format!("{}\n", err));
+    let mut reader = BufReader::new(Deadline { stream, until: Instant::now() + TIMEOUT });
+    let mut remaining = MAX_HEADER_LENGTH;
+    let line = read_header_line(&mut reader, &mut remaining)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(malformed(&"Malformed request line"));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect();
+
+    let mut length = 0;
+    for headers in 0.. {
+        let header = read_header_line(&mut reader, &mut remaining)?;
+        if header.trim().is_empty() {
+            break;
+        }
+        if headers == MAX_HEADERS {
+            return Err(Response::text(431, format!("Requests are limited to {} headers\n", MAX_HEADERS)));
+        }
+        if let Some((name, value)) = header.split_once(':').filter(|(name, _)| name.trim().eq_ignore_ascii_case("content-length")) {
+            length = value.trim().parse().map_err(|_| malformed(&format!("Invalid {} header", name.trim())))?;
+        }
+    }
+    if length > MAX_BODY_LENGTH {
+        return Err(Response::text(413, format!("Bodies are limited to {} bytes\n", MAX_BODY_LENGTH)));
+    }
+
+    let mut body = Vec::with_capacity(length);
+    reader.take(length as u64).read_to_end(&mut body).map_err(failed)?;
+    if body.len() < length {
+        return Err(malformed(&"The body is shorter than its Content-Length"));
+    }
+    let body = String::from_utf8(body).map_err(|err| malformed(&err))?;
+    Ok(Request { method: method.to_string(), path: path.to_string(), query, body })
+}
+
+fn respond(mut stream: TcpStream, handler: &impl Fn(&Request) -> Response) -> std::io::Result<()> {
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let response = match read_request(&stream) {
+        // a panicking handler fails its request rather than the server
+        Ok(request) => panic::catch_unwind(AssertUnwindSafe(|| handler(&request))).unwrap_or_else(|_| Response::text(500, "The request failed\n")),
+        Err(response) => response
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+// A server answering requests from its background thread
+pub struct Server {
+    pub address: SocketAddr,
+    thread: thread::JoinHandle<()>
+}
+
+impl Server {
+    // Blocks for as long as the server runs, which is until it fails
+    pub fn wait(self) -> Result<(), Box<dyn Error>> {
+        match self.thread.join() {
+            Ok(()) => Err(format!("The server at {} stopped", self.address).into()),
+            Err(_) => Err(format!("The server at {} failed", self.address).into())
+        }
+    }
+}
+
+// Answers requests on the address with the handler from a background thread, one connection at a time since the
+// endpoints only read the latest results. Binding happens before returning so a taken port is reported right away.
+pub fn serve(address: &str, handler: impl Fn(&Request) -> Response + Send + 'static) -> Result<Server, Box<dyn Error>> {
+    let listener = TcpListener::bind(address).map_err(|err| format!("Could not listen on {}: {}", address, err))?;
+    let address = listener.local_addr()?;
+    let thread = thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // a client hanging up early or too slow is its own problem
+            let _ = respond(stream, &handler);
+        }
+    });
+    Ok(Server { address, thread })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The status line of the server's answer to the raw request
+    fn status_of(request: &[u8]) -> String {
+        let server = serve("127.0.0.1:0", |request| Response::text(200, format!("{} {:?}", request.path, request.parameter("q")))).unwrap();
+        let mut stream = TcpStream::connect(server.address).unwrap();
+        // the server may answer and hang up before reading all of an oversized request
+        let _ = stream.write_all(request);
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response);
+        response.lines().next().unwrap_or_default().to_string()
+    }
+
+    #[test]
+    fn answers_requests() {
+        assert_eq!(status_of(b"GET /metrics?q=a%20b HTTP/1.1\r\nHost: localhost\r\n\r\n"), "HTTP/1.1 200 OK");
+    }
+
+    #[test]
+    fn long_header_lines_are_rejected() {
+        let request = format!("GET / HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(MAX_HEADER_LENGTH));
+        assert_eq!(status_of(request.as_bytes()), "HTTP/1.1 431 Request Header Fields Too Large");
+        let request = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_HEADER_LENGTH));
+        assert_eq!(status_of(request.as_bytes()), "HTTP/1.1 431 Request Header Fields Too Large");
+    }
+
+    #[test]
+    fn too_many_headers_are_rejected() {
+        let request = format!("GET / HTTP/1.1\r\n{}\r\n", "X: y\r\n".repeat(MAX_HEADERS + 1));
+        assert_eq!(status_of(request.as_bytes()), "HTTP/1.1 431 Request Header Fields Too Large");
+        let request = format!("GET / HTTP/1.1\r\n{}\r\n", "X: y\r\n".repeat(MAX_HEADERS));
+        assert_eq!(status_of(request.as_bytes()), "HTTP/1.1 200 OK");
+    }
+
+    #[test]
+    fn oversized_bodies_are_rejected() {
+        let request = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_LENGTH + 1);
+        assert_eq!(status_of(request.as_bytes()), "HTTP/1.1 413 Payload Too Large");
+    }
+}
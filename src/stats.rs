@@ -0,0 +1,539 @@
+use rand::{rngs::StdRng, Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+use statrs::distribution::{Beta, ChiSquared, ContinuousCDF, StudentsT};
+
+#[derive(Debug, Clone, Copy)]
+pub struct PosteriorEstimate {
+    pub mean: f64,
+    pub lower: f64,
+    pub upper: f64
+}
+
+// Dirichlet-multinomial posterior over category probabilities. The prior puts `prior_strength` pseudo-observations
+// across the categories proportional to `shares`, so sparse cells are shrunk towards their population share rather
+// than being zeroed out. Each marginal of the Dirichlet posterior is a Beta distribution which gives the interval.
+pub fn dirichlet_posterior(counts: &[u32], shares: &[f64], prior_strength: f64, credibility: f64) -> Vec<Option<PosteriorEstimate>> {
+    let total_count = counts.iter().sum::<u32>() as f64;
+    let total_share = shares.iter().sum::<f64>();
+    let total_alpha = prior_strength + total_count;
+    let tail = (1.0 - credibility) / 2.0;
+
+    counts
+        .iter()
+        .zip(shares)
+        .map(|(&count, &share)| {
+            let alpha = prior_strength * share / total_share + count as f64;
+            let beta = Beta::new(alpha, total_alpha - alpha).ok()?;
+
+            Some(PosteriorEstimate {
+                mean: alpha / total_alpha,
+                lower: beta.inverse_cdf(tail),
+                upper: beta.inverse_cdf(1.0 - tail)
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct BootstrapTest {
+    pub statistic: f64,
+    pub p_value: f64,
+    pub expected: Vec<f64>,
+    pub category_p_values: Vec<f64>
+}
+
+pub fn expected_counts(counts: &[u32], shares: &[f64]) -> Vec<f64> {
+    let total_count = counts.iter().sum::<u32>() as f64;
+    let total_share = shares.iter().sum::<f64>();
+    shares.iter().map(|share| total_count * share / total_share).collect()
+}
+
+// Pearson residual of a single cell, how many standard deviations the count is from what the baseline expects. None
+// when the baseline expects no matches at all.
+pub fn standardized_residual(count: u32, expected: f64) -> Option<f64> {
+    (expected > 0.0).then(|| (count as f64 - expected) / expected.sqrt())
+}
+
+// Cohen's h between an observed and an expected proportion, roughly 0.2 is small, 0.5 medium and 0.8 large
+pub fn cohens_h(observed: f64, expected: f64) -> f64 {
+    2.0 * observed.sqrt().asin() - 2.0 * expected.sqrt().asin()
+}
+
+// Gini-Simpson index, the probability that two random draws from the distribution land in different categories
+pub fn simpson_diversity(weights: &[f64]) -> f64 {
+    let total = weights.iter().sum::<f64>();
+    1.0 - weights.iter().map(|weight| (weight / total).powi(2)).sum::<f64>()
+}
+
+// Shannon entropy in nats, empty categories contribute nothing
+pub fn shannon_entropy(weights: &[f64]) -> f64 {
+    let total = weights.iter().sum::<f64>();
+    -weights
+        .iter()
+        .filter(|&&weight| weight > 0.0)
+        .map(|weight| weight / total)
+        .map(|p| p * p.ln())
+        .sum::<f64>()
+}
+
+// Kendall's tau-b rank correlation between two scorings of the same items, ties in either scoring are accounted for.
+// None when either scoring ranks every item the same.
+pub fn kendall_tau(a: &[f64], b: &[f64]) -> Option<f64> {
+    use std::cmp::Ordering::Equal;
+
+    let (mut concordant, mut discordant, mut ties_a, mut ties_b) = (0.0_f64, 0.0, 0.0, 0.0);
+    for i in 0..a.len() {
+        for j in (i + 1)..a.len() {
+            match (a[i].total_cmp(&a[j]), b[i].total_cmp(&b[j])) {
+                (Equal, Equal) => {},
+                (Equal, _) => ties_a += 1.0,
+                (_, Equal) => ties_b += 1.0,
+                (order_a, order_b) if order_a == order_b => concordant += 1.0,
+                _ => discordant += 1.0
+            }
+        }
+    }
+
+    let denominator = ((concordant + discordant + ties_a) * (concordant + discordant + ties_b)).sqrt();
+    (denominator > 0.0).then(|| (concordant - discordant) / denominator)
+}
+
+// Gauss-Jordan elimination with partial pivoting, None when the matrix is singular
+pub fn invert_matrix(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    const SINGULAR_TOLERANCE: f64 = 1e-12;
+
+    let size = matrix.len();
+    let mut augmented = matrix
+        .iter()
+        .enumerate()
+        .map(|(i, row)| row.iter().copied().chain((0..size).map(|j| if i == j { 1.0 } else { 0.0 })).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    for column in 0..size {
+        let pivot = (column..size).max_by(|&a, &b| augmented[a][column].abs().total_cmp(&augmented[b][column].abs()))?;
+        if augmented[pivot][column].abs() < SINGULAR_TOLERANCE {
+            return None;
+        }
+        augmented.swap(column, pivot);
+
+        let divisor = augmented[column][column];
+        augmented[column].iter_mut().for_each(|value| *value /= divisor);
+
+        let pivot_row = augmented[column].clone();
+        for (row, values) in augmented.iter_mut().enumerate() {
+            let factor = values[column];
+            if row != column && factor != 0.0 {
+                values.iter_mut().zip(&pivot_row).for_each(|(value, pivot_value)| *value -= factor * pivot_value);
+            }
+        }
+    }
+
+    Some(augmented.into_iter().map(|row| row[size..].to_vec()).collect())
+}
+
+// Quantile of sorted values interpolating linearly between the closest ranks
+pub fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+
+    let rank = (sorted.len() - 1) as f64 * q;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+// Pearson chi-square statistic, cells without any expected matches carry no information and are skipped
+pub fn chi_square_statistic(counts: &[u32], expected: &[f64]) -> f64 {
+    counts
+        .iter()
+        .zip(expected)
+        .filter(|(_, &expected)| expected > 0.0)
+        .map(|(&count, &expected)| (count as f64 - expected).powi(2) / expected)
+        .sum()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct IndependenceTest {
+    pub statistic: f64,
+    pub degrees_of_freedom: usize,
+    pub p_value: f64,
+    // Cramér's V, 0 is no association and 1 is one variable completely determining the other
+    pub cramers_v: f64
+}
+
+// Pearson chi-square test of independence on a contingency table, empty rows and columns are dropped. None when fewer
+// than two rows or columns are left.
+pub fn independence_test(table: &[Vec<u32>]) -> Option<IndependenceTest> {
+    let column_count = table.first().map_or(0, Vec::len);
+    let columns = (0..column_count)
+        .filter(|&j| table.iter().any(|row| row[j] > 0))
+        .collect::<Vec<_>>();
+    let rows = table
+        .iter()
+        .filter(|row| row.iter().any(|&count| count > 0))
+        .map(|row| columns.iter().map(|&j| row[j]).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    if rows.len() < 2 || columns.len() < 2 {
+        return None;
+    }
+
+    let row_totals = rows.iter().map(|row| row.iter().sum::<u32>() as f64).collect::<Vec<_>>();
+    let column_totals = (0..columns.len()).map(|j| rows.iter().map(|row| row[j]).sum::<u32>() as f64).collect::<Vec<_>>();
+    let total = row_totals.iter().sum::<f64>();
+
+    let statistic = rows
+        .iter()
+        .zip(&row_totals)
+        .map(|(row, row_total)| {
+            let expected = column_totals.iter().map(|column_total| row_total * column_total / total).collect::<Vec<_>>();
+            chi_square_statistic(row, &expected)
+        })
+        .sum::<f64>();
+    let degrees_of_freedom = (rows.len() - 1) * (columns.len() - 1);
+    let p_value = 1.0 - ChiSquared::new(degrees_of_freedom as f64).ok()?.cdf(statistic);
+    let cramers_v = (statistic / (total * (rows.len().min(columns.len()) - 1) as f64)).sqrt();
+
+    Some(IndependenceTest { statistic, degrees_of_freedom, p_value, cramers_v })
+}
+
+impl std::fmt::Display for IndependenceTest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Chi-Square = {:.3}, df = {}, p = {:.4}, Cramér's V = {:.3}", self.statistic, self.degrees_of_freedom, self.p_value, self.cramers_v)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Correlation {
+    pub r: f64,
+    pub observations: usize,
+    pub p_value: f64
+}
+
+// Pearson's r with the two-sided t-test of r being zero, None without variation in either variable
+pub fn pearson(a: &[f64], b: &[f64]) -> Option<Correlation> {
+    let n = a.len() as f64;
+    if a.len() < 3 {
+        return None;
+    }
+
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    let covariance = a.iter().zip(b).map(|(a, b)| (a - mean_a) * (b - mean_b)).sum::<f64>();
+    let variance_a = a.iter().map(|a| (a - mean_a).powi(2)).sum::<f64>();
+    let variance_b = b.iter().map(|b| (b - mean_b).powi(2)).sum::<f64>();
+    if variance_a == 0.0 || variance_b == 0.0 {
+        return None;
+    }
+
+    let r = covariance / (variance_a * variance_b).sqrt();
+    let degrees_of_freedom = n - 2.0;
+    let t = r * (degrees_of_freedom / (1.0 - r * r).max(f64::EPSILON)).sqrt();
+    let p_value = 2.0 * (1.0 - StudentsT::new(0.0, 1.0, degrees_of_freedom).ok()?.cdf(t.abs()));
+
+    Some(Correlation { r, observations: a.len(), p_value })
+}
+
+// Point-biserial correlation between a numeric variable and a binary one, which is Pearson's r with the flag coded as
+// 0 and 1
+pub fn point_biserial(values: &[f64], flags: &[bool]) -> Option<Correlation> {
+    pearson(values, &flags.iter().map(|&flag| flag as u8 as f64).collect::<Vec<_>>())
+}
+
+impl std::fmt::Display for Correlation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "r = {:+.3}, n = {}, p = {:.4}", self.r, self.observations, self.p_value)?;
+        Ok(())
+    }
+}
+
+// Draws `total` samples from the categorical distribution described by `cumulative_shares` into `counts`
+fn sample_counts(total: u32, cumulative_shares: &[f64], counts: &mut [u32], rng: &mut impl Rng) {
+    counts.iter_mut().for_each(|count| *count = 0);
+    let total_share = cumulative_shares.last().copied().unwrap_or(0.0);
+    for _ in 0..total {
+        let draw = rng.random::<f64>() * total_share;
+        let category = cumulative_shares.partition_point(|&share| share <= draw).min(counts.len() - 1);
+        counts[category] += 1;
+    }
+}
+
+// A uniform sample of at most `capacity` values out of a stream of any length (Algorithm R), so quantiles of every
+// value read are estimated in constant memory. The sample is every value until more than `capacity` have been added.
+pub struct Reservoir<T> {
+    capacity: usize,
+    seen: u64,
+    values: Vec<T>,
+    rng: StdRng
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(capacity: usize, seed: u64) -> Reservoir<T> {
+        Reservoir { capacity, seen: 0, values: Vec::new(), rng: StdRng::seed_from_u64(seed) }
+    }
+
+    pub fn add(&mut self, value: T) {
+        self.seen += 1;
+        if self.values.len() < self.capacity {
+            self.values.push(value);
+        } else {
+            let index = self.rng.random_range(0..self.seen) as usize;
+            if index < self.capacity {
+                self.values[index] = value;
+            }
+        }
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+}
+
+// A seed for each of the iterations of a simulation drawn up front, so iterations run in parallel give the same
+// results for the same seed however they are scheduled
+pub fn seeds(iterations: usize, rng: &mut impl Rng) -> Vec<u64> {
+    (0..iterations).map(|_| rng.random()).collect()
+}
+
+// Counts of match sets of `total` matches drawn from the categorical distribution, one per iteration, in parallel. The
+// counts of every iteration share one buffer, a row of one count per category for each iteration in order.
+fn simulate_counts(total: u32, cumulative_shares: &[f64], iterations: u32, rng: &mut impl Rng) -> Vec<u32> {
+    let categories = cumulative_shares.len();
+    let seeds = seeds(iterations as usize, rng);
+    let mut simulated = vec![0; seeds.len() * categories];
+    if categories > 0 {
+        simulated
+            .par_chunks_mut(categories)
+            .zip(seeds)
+            .for_each(|(counts, seed)| sample_counts(total, cumulative_shares, counts, &mut StdRng::seed_from_u64(seed)));
+    }
+    simulated
+}
+
+// Parametric bootstrap test of the observed counts against the baseline. Every iteration draws the same number of
+// matches from the multinomial of the population shares, and the p-values are the fraction of draws at least as extreme
+// as what was observed, both overall (chi-square) and per category (distance from the expected count).
+pub fn bootstrap_test(counts: &[u32], shares: &[f64], iterations: u32, rng: &mut impl Rng) -> BootstrapTest {
+    const TOLERANCE: f64 = 1e-9;
+
+    let total_count = counts.iter().sum::<u32>();
+    let expected = expected_counts(counts, shares);
+    let statistic = chi_square_statistic(counts, &expected);
+    let deviations = counts
+        .iter()
+        .zip(&expected)
+        .map(|(&count, &expected)| (count as f64 - expected).abs())
+        .collect::<Vec<_>>();
+
+    let cumulative_shares = shares
+        .iter()
+        .scan(0.0, |total, &share| {
+            *total += share;
+            Some(*total)
+        })
+        .collect::<Vec<_>>();
+
+    let mut extreme_count = 0;
+    let mut category_extreme_counts = vec![0; counts.len()];
+
+    for simulated in simulate_counts(total_count, &cumulative_shares, iterations, rng).chunks(counts.len().max(1)) {
+        if chi_square_statistic(simulated, &expected) >= statistic - TOLERANCE {
+            extreme_count += 1;
+        }
+
+        for (i, &count) in simulated.iter().enumerate() {
+            if (count as f64 - expected[i]).abs() >= deviations[i] - TOLERANCE {
+                category_extreme_counts[i] += 1;
+            }
+        }
+    }
+
+    let p_value = |extreme_count: u32| (extreme_count + 1) as f64 / (iterations + 1) as f64;
+
+    BootstrapTest {
+        statistic,
+        p_value: p_value(extreme_count),
+        expected,
+        category_p_values: category_extreme_counts.into_iter().map(p_value).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum IndexFormula {
+    /// Matches per unit of population share, rescaled so the scores of all categories sum to 1
+    Normalized,
+    /// Share of matches divided by share of population
+    RepresentationRatio,
+    /// Odds of a match being in the category divided by the odds for the population
+    OddsRatio,
+    /// Jacobs' selectivity index, from -1 (avoided) through 0 (proportional) to 1 (exclusive)
+    Selectivity
+}
+
+impl IndexFormula {
+    pub fn description(&self) -> &'static str {
+        match *self {
+            IndexFormula::Normalized => "Score = (matches / population share) / sum over all categories, scores add up to 1",
+            IndexFormula::RepresentationRatio => "Score = match share / population share, 1.0 is matching at the population rate",
+            IndexFormula::OddsRatio => "Score = (m / (1 - m)) / (p / (1 - p)) for match share m and population share p, 1.0 is neutral",
+            IndexFormula::Selectivity => "Score = (m - p) / (m + p - 2mp) for match share m and population share p, -1 avoided to 1 exclusive"
+        }
+    }
+
+    // Unnormalized score of a category from its share of matches and its share of the population
+    pub fn score(&self, observed: f64, expected: f64) -> f64 {
+        match *self {
+            IndexFormula::Normalized | IndexFormula::RepresentationRatio => observed / expected,
+            IndexFormula::OddsRatio => (observed / (1.0 - observed)) / (expected / (1.0 - expected)),
+            IndexFormula::Selectivity => (observed - expected) / (observed + expected - 2.0 * observed * expected)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Correction {
+    /// No correction, every category is tested on its own
+    None,
+    /// Holm-Bonferroni step-down, controls the family-wise error rate
+    Holm,
+    /// Benjamini-Hochberg step-up, controls the false discovery rate
+    BenjaminiHochberg
+}
+
+impl std::fmt::Display for Correction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match *self {
+            Correction::None => "None",
+            Correction::Holm => "Holm",
+            Correction::BenjaminiHochberg => "Benjamini-Hochberg"
+        })?;
+
+        Ok(())
+    }
+}
+
+// Adjusted p-values in the same order as `p_values`, comparable directly against the significance level
+pub fn adjust_p_values(p_values: &[f64], correction: Correction) -> Vec<f64> {
+    let m = p_values.len();
+    let mut order = (0..m).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| p_values[a].total_cmp(&p_values[b]));
+
+    let mut adjusted = p_values.to_vec();
+    match correction {
+        Correction::None => {},
+        Correction::Holm => {
+            let mut running_max: f64 = 0.0;
+            for (rank, &i) in order.iter().enumerate() {
+                running_max = running_max.max(((m - rank) as f64 * p_values[i]).min(1.0));
+                adjusted[i] = running_max;
+            }
+        },
+        Correction::BenjaminiHochberg => {
+            let mut running_min: f64 = 1.0;
+            for (rank, &i) in order.iter().enumerate().rev() {
+                running_min = running_min.min(p_values[i] * m as f64 / (rank + 1) as f64);
+                adjusted[i] = running_min;
+            }
+        }
+    }
+
+    adjusted
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SimulatedCount {
+    pub percentile: f64,
+    pub median: u32,
+    pub lower: u32,
+    pub upper: u32
+}
+
+// Monte Carlo null model, simulates match sets of the observed size drawn from the baseline and locates each observed
+// count within its simulated distribution. The percentile counts ties as half so an unremarkable count lands near 50.
+pub fn simulate_null(counts: &[u32], shares: &[f64], iterations: u32, rng: &mut impl Rng) -> Vec<SimulatedCount> {
+    let total_count = counts.iter().sum::<u32>();
+    let cumulative_shares = shares
+        .iter()
+        .scan(0.0, |total, &share| {
+            *total += share;
+            Some(*total)
+        })
+        .collect::<Vec<_>>();
+
+    let mut simulations = vec![Vec::with_capacity(iterations as usize); counts.len()];
+    for simulated in simulate_counts(total_count, &cumulative_shares, iterations, rng).chunks(counts.len().max(1)) {
+        for (i, &count) in simulated.iter().enumerate() {
+            simulations[i].push(count);
+        }
+    }
+
+    counts
+        .iter()
+        .zip(simulations.iter_mut())
+        .map(|(&count, simulation)| {
+            simulation.sort_unstable();
+            let below = simulation.partition_point(|&simulated| simulated < count);
+            let not_above = simulation.partition_point(|&simulated| simulated <= count);
+            let quantile = |q: f64| simulation[((simulation.len() - 1) as f64 * q).round() as usize];
+
+            SimulatedCount {
+                percentile: (below as f64 + (not_above - below) as f64 / 2.0) / simulation.len() as f64 * 100.0,
+                median: quantile(0.5),
+                lower: quantile(0.025),
+                upper: quantile(0.975)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f64 = 1e-9;
+
+    fn assert_close(actual: &[Vec<f64>], expected: &[Vec<f64>]) {
+        for (actual_row, expected_row) in actual.iter().zip(expected) {
+            for (actual, expected) in actual_row.iter().zip(expected_row) {
+                assert!((actual - expected).abs() < TOLERANCE, "{:?} is not {:?}", actual_row, expected_row);
+            }
+        }
+    }
+
+    #[test]
+    fn inverts_a_known_matrix() {
+        let matrix = vec![vec![4.0, 7.0], vec![2.0, 6.0]];
+        assert_close(&invert_matrix(&matrix).unwrap(), &[vec![0.6, -0.7], vec![-0.2, 0.4]]);
+    }
+
+    #[test]
+    fn inverse_times_matrix_is_the_identity() {
+        // the first pivot is zero, so this needs a row swap
+        let matrix = vec![vec![0.0, 2.0, 1.0], vec![1.0, 1.0, 0.0], vec![3.0, 0.0, 5.0]];
+        let inverse = invert_matrix(&matrix).unwrap();
+        let product = (0..3)
+            .map(|i| (0..3).map(|j| (0..3).map(|k| matrix[i][k] * inverse[k][j]).sum()).collect())
+            .collect::<Vec<Vec<f64>>>();
+        assert_close(&product, &[vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0], vec![0.0, 0.0, 1.0]]);
+    }
+
+    #[test]
+    fn singular_matrices_have_no_inverse() {
+        assert!(invert_matrix(&[vec![1.0, 2.0], vec![2.0, 4.0]]).is_none());
+        assert!(invert_matrix(&[vec![0.0, 0.0], vec![0.0, 0.0]]).is_none());
+        assert!(invert_matrix(&[vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0], vec![5.0, 7.0, 9.0]]).is_none());
+    }
+
+    #[test]
+    fn empty_matrix_is_its_own_inverse() {
+        assert_eq!(invert_matrix(&[]), Some(Vec::new()));
+    }
+
+    #[test]
+    fn degenerate_inputs_have_no_residual_or_tau() {
+        assert_eq!(standardized_residual(3, 0.0), None);
+        assert_eq!(standardized_residual(6, 4.0), Some(1.0));
+        assert_eq!(kendall_tau(&[1.0, 1.0, 1.0], &[1.0, 2.0, 3.0]), None);
+        assert_eq!(kendall_tau(&[1.0, 2.0, 3.0], &[3.0, 2.0, 1.0]), Some(-1.0));
+    }
+}
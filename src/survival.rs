@@ -0,0 +1,119 @@
+use chrono::NaiveDateTime;
+
+use crate::{export::{Export, Interaction, Sender}, format};
+
+const PLOT_WIDTH: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SurvivalTime {
+    /// Number of messages exchanged
+    Messages,
+    /// Whole days from the first to the last message
+    Days
+}
+
+impl SurvivalTime {
+    fn label(self) -> &'static str {
+        match self {
+            SurvivalTime::Messages => "Messages",
+            SurvivalTime::Days => "Days"
+        }
+    }
+}
+
+// How long one conversation lasted and whether it died, conversations that led to a date or were still active when the
+// export was taken are censored
+#[derive(Debug, Clone, Copy)]
+struct Lifetime {
+    time: u32,
+    died: bool
+}
+
+fn lifetime(interaction: &Interaction, time: SurvivalTime, export_end: NaiveDateTime, alive_days: i64) -> Option<Lifetime> {
+    let chat = interaction.messages();
+    let (first, last) = (chat.first()?, chat.last()?);
+    Some(Lifetime {
+        time: match time {
+            SurvivalTime::Messages => chat.len() as u32,
+            SurvivalTime::Days => (last.timestamp - first.timestamp).num_days() as u32
+        },
+        died: !interaction.met() && (export_end - last.timestamp).num_days() >= alive_days
+    })
+}
+
+// (time, at risk, deaths, survival) at every time a conversation died
+fn kaplan_meier(lifetimes: &[Lifetime]) -> Vec<(u32, usize, usize, f64)> {
+    let mut times = lifetimes.iter().filter(|lifetime| lifetime.died).map(|lifetime| lifetime.time).collect::<Vec<_>>();
+    times.sort_unstable();
+    times.dedup();
+
+    let mut survival = 1.0;
+    times
+        .into_iter()
+        .map(|time| {
+            let at_risk = lifetimes.iter().filter(|lifetime| lifetime.time >= time).count();
+            let deaths = lifetimes.iter().filter(|lifetime| lifetime.died && lifetime.time == time).count();
+            survival *= 1.0 - deaths as f64 / at_risk as f64;
+            (time, at_risk, deaths, survival)
+        })
+        .collect()
+}
+
+fn print_curve(title: &str, time: SurvivalTime, lifetimes: &[Lifetime]) {
+    let curve = kaplan_meier(lifetimes);
+    let censored = lifetimes.iter().filter(|lifetime| !lifetime.died).count();
+
+    println!("\n\t         {} ({} Conversations, {} Censored)", title, lifetimes.len(), censored);
+    println!("\t{:<8}   At Risk   Died      Survival", time.label());
+    for (at, at_risk, deaths, survival) in &curve {
+        println!(
+            "\t{:<8}   {:<7}   {:<7}   {}   {}",
+            at,
+            at_risk,
+            deaths,
+            format::percent(*survival),
+            "█".repeat((survival * PLOT_WIDTH as f64).round() as usize)
+        );
+    }
+
+    // the median is the first time survival drops to half, it is undefined while more than half are still alive
+    match curve.iter().find(|(_, _, _, survival)| *survival <= 0.5) {
+        Some((at, _, _, _)) => println!("\tMedian Lifetime: {} {}", at, time.label().to_lowercase()),
+        None => println!("\tMedian Lifetime: not reached, over half the conversations survive")
+    }
+}
+
+// Kaplan-Meier survival of conversations where the event is the conversation dying, overall and by who sent the
+// first message when the export knows the senders
+pub fn print_survival(export: &Export, time: SurvivalTime, alive_days: i64) {
+    let Some(export_end) = export.interactions.iter().flat_map(|interaction| interaction.timestamps()).max() else {
+        println!("\nNo activity in the export, skipping conversation survival");
+        return;
+    };
+
+    let lifetimes = export
+        .matches()
+        .filter_map(|(_, interaction)| Some((interaction.messages().first()?.sender, lifetime(interaction, time, export_end, alive_days)?)))
+        .collect::<Vec<_>>();
+    if lifetimes.is_empty() {
+        println!("\nNo conversations in the export, skipping conversation survival");
+        return;
+    }
+
+    let title = |group: &str| format!("Conversation Survival by {}, {}", time.label(), group);
+    print_curve(&title("All"), time, &lifetimes.iter().map(|(_, lifetime)| *lifetime).collect::<Vec<_>>());
+    if export.has_received_messages() {
+        for (sender, group) in [(Sender::Me, "You Opened"), (Sender::Them, "They Opened")] {
+            let stratum = lifetimes
+                .iter()
+                .filter(|(opener, _)| *opener == sender)
+                .map(|(_, lifetime)| *lifetime)
+                .collect::<Vec<_>>();
+            if !stratum.is_empty() {
+                print_curve(&title(group), time, &stratum);
+            }
+        }
+    } else {
+        println!("\nNo received messages in the export, skipping survival by opener");
+    }
+}
@@ -0,0 +1,428 @@
+use std::collections::HashSet;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+
+use crate::{export::{Export, Sender}, format, stats};
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+#[derive(Debug, Default, Clone, Copy)]
+struct CohortCounts {
+    matches: u32,
+    conversations: u32,
+    dates: u32
+}
+
+impl CohortCounts {
+    fn add(&mut self, conversation: bool, date: bool) {
+        self.matches += 1;
+        self.conversations += conversation as u32;
+        self.dates += date as u32;
+    }
+}
+
+fn print_cohort_row(label: &str, counts: CohortCounts) {
+    println!(
+        "\t{:<10}   {:<7}   {:<7}   {}     {:<7}   {}",
+        label,
+        counts.matches,
+        counts.conversations,
+        format::percent(counts.conversations as f64 / counts.matches.max(1) as f64),
+        counts.dates,
+        format::percent(counts.dates as f64 / counts.conversations.max(1) as f64)
+    );
+    format::explain(&[
+        format::Rate::new("convo rate", counts.conversations as usize, "convos", counts.matches as usize, "matches"),
+        format::Rate::new("date conversion", counts.dates as usize, "dates", counts.conversations as usize, "convos")
+    ]);
+}
+
+// Months since the first month of the export
+fn month_index(timestamp: NaiveDateTime) -> i32 {
+    timestamp.year() * 12 + timestamp.month0() as i32
+}
+
+// Conversion of matches grouped by the local month they matched, the same grouped by calendar month across years to
+// show seasonality, and the correlation of cohort age with each outcome to show whether newer cohorts do better
+pub fn print_cohorts(export: &Export) {
+    let matches = export
+        .matches()
+        .map(|(matched_at, interaction)| (export.local(matched_at), export.conversation(interaction), interaction.met()))
+        .collect::<Vec<_>>();
+    let Some(first) = matches.iter().map(|(matched_at, _, _)| month_index(*matched_at)).min() else {
+        println!("\nNo matches in the export, skipping cohort analysis");
+        return;
+    };
+    let last = matches.iter().map(|(matched_at, _, _)| month_index(*matched_at)).max().unwrap_or(first);
+
+    let mut cohorts = vec![CohortCounts::default(); (last - first + 1) as usize];
+    let mut calendar_months = [CohortCounts::default(); 12];
+    for &(matched_at, conversation, date) in &matches {
+        cohorts[(month_index(matched_at) - first) as usize].add(conversation, date);
+        calendar_months[matched_at.month0() as usize].add(conversation, date);
+    }
+
+    println!("\n\t         Monthly Match Cohorts (Date Conversion = Dates / Conversations)");
+    println!("\t{:<10}   Matches   Convos    Convo Rate    Dates     Date Conversion", "Cohort");
+    for (i, counts) in cohorts.iter().enumerate() {
+        let month = first + i as i32;
+        print_cohort_row(&format!("{}-{:02}", month / 12, month % 12 + 1), *counts);
+    }
+
+    println!("\n\t         Seasonality (All Years by Calendar Month)");
+    println!("\t{:<10}   Matches   Convos    Convo Rate    Dates     Date Conversion", "Month");
+    for (month, counts) in MONTHS.iter().zip(calendar_months) {
+        print_cohort_row(month, counts);
+    }
+
+    let ages = matches.iter().map(|(matched_at, _, _)| (month_index(*matched_at) - first) as f64).collect::<Vec<_>>();
+    let conversations = matches.iter().map(|(_, conversation, _)| *conversation).collect::<Vec<_>>();
+    let conversation_dates = matches
+        .iter()
+        .filter(|(_, conversation, _)| *conversation)
+        .map(|(matched_at, _, date)| ((month_index(*matched_at) - first) as f64, *date))
+        .unzip::<_, _, Vec<_>, Vec<_>>();
+
+    println!("\tPositive r = newer cohorts do better");
+    for (outcome, correlation) in [
+        ("Conversation Rate", stats::point_biserial(&ages, &conversations)),
+        ("Date Conversion", stats::point_biserial(&conversation_dates.0, &conversation_dates.1))
+    ] {
+        match correlation {
+            Some(correlation) => println!("\tCohort Trend in {}: {}", outcome, correlation),
+            None => println!("\tCohort Trend in {}: not enough variation to test", outcome)
+        }
+    }
+}
+
+// Totals over the span of the export divided by the number of weeks with any activity and by the number of calendar
+// weeks spanned, so exports covering different stretches of time can be compared
+pub fn print_weekly_rates(export: &Export) {
+    let timestamps = export.interactions.iter().flat_map(|interaction| interaction.timestamps()).map(|timestamp| export.local(timestamp));
+    let (Some(first), Some(last)) = (timestamps.clone().min(), timestamps.clone().max()) else {
+        println!("\nNo activity in the export, skipping weekly rates");
+        return;
+    };
+
+    let active_weeks = timestamps
+        .map(|timestamp| {
+            let week = timestamp.iso_week();
+            (week.year(), week.week())
+        })
+        .collect::<HashSet<_>>()
+        .len();
+    let calendar_weeks = ((last - first).num_days() / 7 + 1) as usize;
+
+    let likes = export.interactions.iter().filter(|interaction| !interaction.like.is_empty()).count();
+    let matches = export.matches().count();
+    let conversations = export.matches().filter(|(_, interaction)| export.conversation(interaction)).count();
+    let dates = export.matches().filter(|(_, interaction)| interaction.met()).count();
+    let messages = export.interactions.iter().map(|interaction| interaction.chats.len()).sum::<usize>();
+
+    println!("\n\t         Activity Rates ({} to {})", first.date(), last.date());
+    println!("\tActive Weeks: {} of {} Calendar Weeks ({})", active_weeks, calendar_weeks, format::inline_percent(active_weeks as f64 / calendar_weeks as f64, 1));
+    println!("\t{:<15}   Total     Per Active Week   Per Calendar Week", "");
+    for (label, total) in [("Likes Sent", likes), ("Matches", matches), ("Messages", messages), ("Conversations", conversations), ("Dates", dates)] {
+        println!("\t{:<15}   {:<7}   {:<15}   {}", label, format::count(total), format::decimal(total as f64 / active_weeks as f64, 3), format::decimal(total as f64 / calendar_weeks as f64, 3));
+    }
+}
+
+// Stretches of your activity (likes sent and messages sent) split wherever nothing happened for longer than
+// `pause_days`. Matches made within `return_days` of coming back from a pause are compared against all other matches.
+pub fn print_streaks(export: &Export, pause_days: i64, return_days: i64) {
+    let mut activity = export
+        .interactions
+        .iter()
+        .flat_map(|interaction| interaction.like
+            .iter()
+            .map(|event| event.timestamp)
+            .chain(interaction.chats.iter().filter(|message| message.sender == Sender::Me).map(|message| message.timestamp)))
+        .map(|timestamp| export.local(timestamp))
+        .collect::<Vec<_>>();
+    activity.sort();
+
+    let Some(&first) = activity.first() else {
+        println!("\nNo likes or messages in the export, skipping streak analysis");
+        return;
+    };
+
+    // (start, end) of every active streak
+    let mut streaks = vec![(first, first)];
+    for &timestamp in &activity[1..] {
+        let streak = streaks.last_mut().expect("Bad streak");
+        if (timestamp - streak.1).num_days() > pause_days {
+            streaks.push((timestamp, timestamp));
+        } else {
+            streak.1 = timestamp;
+        }
+    }
+
+    let matches = export
+        .matches()
+        .map(|(matched_at, interaction)| (export.local(matched_at), export.conversation(interaction), interaction.met()))
+        .collect::<Vec<_>>();
+
+    println!("\n\t         Active Streaks (Pause = No Likes or Messages Sent for Over {} Days)", pause_days);
+    println!("\t{:<10}   {:<10}   Days      Matches   Pause Before", "Start", "End");
+    for (i, (start, end)) in streaks.iter().enumerate() {
+        let streak_matches = matches.iter().filter(|(matched_at, _, _)| matched_at >= start && matched_at <= end).count();
+        let pause = if i == 0 { String::from("-") } else { format!("{} days", (*start - streaks[i - 1].1).num_days()) };
+        println!("\t{:<10}   {:<10}   {:<7}   {:<7}   {}", start.date(), end.date(), (*end - *start).num_days() + 1, streak_matches, pause);
+    }
+
+    let longest = streaks.iter().map(|(start, end)| (*end - *start).num_days() + 1).max().unwrap_or(0);
+    let longest_pause = streaks.windows(2).map(|pair| (pair[1].0 - pair[0].1).num_days()).max();
+    println!("\tLongest Streak: {} days, Longest Pause: {}", longest, longest_pause.map_or(String::from("none"), |days| format!("{} days", days)));
+
+    // streaks after the first start right after a pause
+    let returns = streaks[1..].iter().map(|(start, _)| *start).collect::<Vec<_>>();
+    if returns.is_empty() {
+        return;
+    }
+
+    let after_return = |matched_at: &NaiveDateTime| returns.iter().any(|start| matched_at >= start && (*matched_at - *start).num_days() < return_days);
+    let groups = [true, false].map(|returning| {
+        let mut counts = CohortCounts::default();
+        for (_, conversation, date) in matches.iter().filter(|(matched_at, _, _)| after_return(matched_at) == returning) {
+            counts.add(*conversation, *date);
+        }
+        counts
+    });
+
+    println!("\n\t         Outcomes After Returning from a Pause (First {} Days of a Streak)", return_days);
+    println!("\t{:<10}   Matches   Convos    Convo Rate    Dates     Date Conversion", "");
+    print_cohort_row("Returning", groups[0]);
+    print_cohort_row("Otherwise", groups[1]);
+
+    let conversation_table = groups.iter().map(|counts| vec![counts.conversations, counts.matches - counts.conversations]).collect::<Vec<_>>();
+    let date_table = groups.iter().map(|counts| vec![counts.dates, counts.conversations - counts.dates]).collect::<Vec<_>>();
+    for (outcome, table) in [("Conversation Rate", conversation_table), ("Date Conversion", date_table)] {
+        match stats::independence_test(&table) {
+            Some(test) => println!("\t{} vs Returning: {}", outcome, test),
+            None => println!("\t{} vs Returning: not enough variation to test", outcome)
+        }
+    }
+}
+
+// Words that suggest a message is making plans to meet, matched against whole words of the message
+const PLAN_KEYWORDS: &[&str] = &[
+    "drink", "drinks", "coffee", "dinner", "lunch", "brunch", "meet", "free", "grab", "tonight", "tomorrow", "weekend",
+    "monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday", "date"
+];
+
+const TIMING_QUANTILES: [f64; 5] = [0.1, 0.25, 0.5, 0.75, 0.9];
+
+fn mentions_plans(body: &str) -> bool {
+    body.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| PLAN_KEYWORDS.contains(&word))
+}
+
+fn days_between(from: NaiveDateTime, to: NaiveDateTime) -> f64 {
+    (to - from).num_minutes().max(0) as f64 / 1440.0
+}
+
+// Days spent in each stage between matching and meeting for matches you met. Plans are the first message that
+// mentions meeting up and the date is when you answered that you met, which Hinge asks shortly after the date.
+pub fn print_date_timing(export: &Export) {
+    let mut stages: [(&str, Vec<f64>); 3] = [("Match to First Message", Vec::new()), ("First Message to Plans", Vec::new()), ("Match to Date", Vec::new())];
+    let mut dates = 0;
+    for (matched_at, interaction) in export.matches() {
+        let Some(met_at) = interaction.met_at() else {
+            continue;
+        };
+        dates += 1;
+
+        let chat = interaction.messages();
+        if let Some(first) = chat.first() {
+            stages[0].1.push(days_between(matched_at, first.timestamp));
+            if let Some(plans) = chat.iter().find(|message| mentions_plans(&message.body)) {
+                stages[1].1.push(days_between(first.timestamp, plans.timestamp));
+            }
+        }
+        stages[2].1.push(days_between(matched_at, met_at));
+    }
+
+    if dates == 0 {
+        println!("\nNo matches you met in the export, skipping date timing");
+        return;
+    }
+
+    println!("\n\t         Time to Date in Days ({} Matches You Met)", dates);
+    println!("\t{:<25}   Matches   {}", "Stage", TIMING_QUANTILES.map(|q| format!("{:>7}", format!("p{}", (q * 100.0) as u32))).join("   "));
+    for (stage, days) in &mut stages {
+        days.sort_by(f64::total_cmp);
+        let quantiles = TIMING_QUANTILES.map(|q| if days.is_empty() { format!("{:>7}", "-") } else { format!("{:>7.1}", stats::quantile(days, q)) });
+        println!("\t{:<25}   {:<7}   {}", stage, days.len(), quantiles.join("   "));
+    }
+    println!("\tMedian is p50, matches missing from a stage had no messages or no message mentioning plans");
+}
+
+const CONVERSION_BAR_WIDTH: usize = 40;
+
+// Share of the likes you sent each local month that became a match, the trend of that share across months and, when
+// profile changes are given, the conversion between consecutive changes with a test of it being the same throughout
+pub fn print_like_conversion(export: &Export, changes: &[NaiveDate]) {
+    let likes = export
+        .interactions
+        .iter()
+        .filter_map(|interaction| Some((export.local(interaction.like.iter().map(|event| event.timestamp).min()?), interaction.matched_at().is_some())))
+        .collect::<Vec<_>>();
+    let Some(first) = likes.iter().map(|(liked_at, _)| month_index(*liked_at)).min() else {
+        println!("\nNo likes sent in the export, skipping like conversion");
+        return;
+    };
+    let last = likes.iter().map(|(liked_at, _)| month_index(*liked_at)).max().unwrap_or(first);
+
+    // [likes, matches] by month
+    let mut months = vec![[0_u32; 2]; (last - first + 1) as usize];
+    for &(liked_at, matched) in &likes {
+        let month = &mut months[(month_index(liked_at) - first) as usize];
+        month[0] += 1;
+        month[1] += matched as u32;
+    }
+
+    println!("\n\t         Like to Match Conversion by Month ({} Likes Sent)", likes.len());
+    println!("\t{:<10}   Likes     Matches   Conversion", "Month");
+    for (i, [sent, matched]) in months.iter().enumerate() {
+        let month = first + i as i32;
+        let rate = *matched as f64 / (*sent).max(1) as f64;
+        println!(
+            "\t{:<10}   {:<7}   {:<7}   {}   {}",
+            format!("{}-{:02}", month / 12, month % 12 + 1),
+            sent,
+            matched,
+            format::percent(rate),
+            "█".repeat((rate * CONVERSION_BAR_WIDTH as f64).round() as usize)
+        );
+        format::explain(&[format::Rate::new("conversion", *matched as usize, "matches", *sent as usize, "likes sent")]);
+    }
+
+    let (ages, matched): (Vec<f64>, Vec<bool>) = likes.iter().map(|(liked_at, matched)| ((month_index(*liked_at) - first) as f64, *matched)).unzip();
+    match stats::point_biserial(&ages, &matched) {
+        Some(correlation) => println!("\tTrend (Positive r = Later Likes Convert Better): {}", correlation),
+        None => println!("\tTrend: not enough variation to test")
+    }
+
+    if changes.is_empty() {
+        return;
+    }
+
+    let mut changes = changes.to_vec();
+    changes.sort();
+    // [likes, matches] before the first change, then after each change
+    let mut periods = vec![[0_u32; 2]; changes.len() + 1];
+    for &(liked_at, matched) in &likes {
+        let period = &mut periods[changes.partition_point(|change| *change <= liked_at.date())];
+        period[0] += 1;
+        period[1] += matched as u32;
+    }
+
+    println!("\n\t         Like to Match Conversion Between Profile Changes");
+    println!("\t{:<25}   Likes     Matches   Conversion", "Period");
+    for (i, [sent, matched]) in periods.iter().enumerate() {
+        let period = match i {
+            0 => format!("Before {}", changes[0]),
+            i => format!("From {}", changes[i - 1])
+        };
+        println!("\t{:<25}   {:<7}   {:<7}   {}", period, sent, matched, format::percent(*matched as f64 / (*sent).max(1) as f64));
+        format::explain(&[format::Rate::new("conversion", *matched as usize, "matches", *sent as usize, "likes sent")]);
+    }
+
+    let table = periods.iter().filter(|[sent, _]| *sent > 0).map(|[sent, matched]| vec![*matched, sent - matched]).collect::<Vec<_>>();
+    match stats::independence_test(&table) {
+        Some(test) => println!("\tConversion Independent of Period: {}", test),
+        None => println!("\tConversion Independent of Period: not enough variation to test")
+    }
+}
+
+// Inclusive range of local dates, written START..END
+#[derive(Debug, Clone, Copy)]
+pub struct Period {
+    start: NaiveDate,
+    end: NaiveDate
+}
+
+impl Period {
+    fn contains(&self, date: NaiveDate) -> bool {
+        (self.start..=self.end).contains(&date)
+    }
+}
+
+impl std::str::FromStr for Period {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (start, end) = value.split_once("..").ok_or_else(|| format!("Expected START..END for period {}", value))?;
+        let parse = |date: &str| date.trim().parse::<NaiveDate>().map_err(|err| format!("Invalid date {} in period: {}", date, err));
+        let (start, end) = (parse(start)?, parse(end)?);
+        if end < start {
+            return Err(format!("Period {} ends before it starts", value));
+        }
+        Ok(Period { start, end })
+    }
+}
+
+impl std::fmt::Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)?;
+        Ok(())
+    }
+}
+
+// Match volume per day, conversation rate and date conversion of matches made while subscribed against the rest of the
+// export, days are only counted between the first and last activity of the export
+pub fn print_subscription(export: &Export, periods: &[Period]) {
+    let timestamps = export.interactions.iter().flat_map(|interaction| interaction.timestamps()).map(|timestamp| export.local(timestamp));
+    let (Some(first), Some(last)) = (timestamps.clone().min(), timestamps.max()) else {
+        println!("\nNo activity in the export, skipping subscription comparison");
+        return;
+    };
+
+    let subscribed = |date: NaiveDate| periods.iter().any(|period| period.contains(date));
+    let mut days = [0_u32; 2];
+    for date in first.date().iter_days().take_while(|date| *date <= last.date()) {
+        days[subscribed(date) as usize] += 1;
+    }
+
+    let mut counts = [CohortCounts::default(); 2];
+    for (matched_at, interaction) in export.matches() {
+        counts[subscribed(export.local(matched_at).date()) as usize].add(export.conversation(interaction), interaction.met());
+    }
+
+    println!(
+        "\n\t         Subscribed ({}) Against Not Subscribed, {} to {}",
+        periods.iter().map(Period::to_string).collect::<Vec<_>>().join(", "),
+        first.date(),
+        last.date()
+    );
+    println!("\t{:<15}   Days      Matches per Day   Matches   Convos    Convo Rate    Dates     Date Conversion", "");
+    for (label, days, counts) in [("Not Subscribed", days[0], counts[0]), ("Subscribed", days[1], counts[1])] {
+        println!(
+            "\t{:<15}   {:<7}   {:<15}   {:<7}   {:<7}   {}     {:<7}   {}",
+            label,
+            days,
+            format::decimal(counts.matches as f64 / days.max(1) as f64, 3),
+            counts.matches,
+            counts.conversations,
+            format::percent(counts.conversations as f64 / counts.matches.max(1) as f64),
+            counts.dates,
+            format::percent(counts.dates as f64 / counts.conversations.max(1) as f64)
+        );
+        format::explain(&[
+            format::Rate::new("convo rate", counts.conversations as usize, "convos", counts.matches as usize, "matches"),
+            format::Rate::new("date conversion", counts.dates as usize, "dates", counts.conversations as usize, "convos")
+        ]);
+    }
+
+    let tests = [
+        ("Convo Rate", counts.map(|counts| vec![counts.conversations, counts.matches - counts.conversations])),
+        ("Date Conversion", counts.map(|counts| vec![counts.dates, counts.conversations - counts.dates]))
+    ];
+    for (outcome, table) in tests {
+        match stats::independence_test(&table) {
+            Some(test) => println!("\t{} Independent of Subscription: {}", outcome, test),
+            None => println!("\t{} Independent of Subscription: not enough variation to test", outcome)
+        }
+    }
+}
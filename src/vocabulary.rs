@@ -0,0 +1,42 @@
+use std::{collections::HashMap, error::Error, path::Path};
+
+const CANONICAL: [&str; 4] = ["You", "Them", "Met", "None"];
+
+#[derive(Debug, serde::Deserialize)]
+struct SynonymRecord {
+    synonym: String,
+    last_reply: String
+}
+
+// Other words for the Who Last Replied values, e.g. from a phone set to another language, read case insensitively
+#[derive(Debug, Default)]
+pub struct Vocabulary {
+    synonyms: HashMap<String, &'static str>
+}
+
+impl Vocabulary {
+    // The You/Them/Met/None value a last_reply cell stands for, the cell itself when it isn't a known synonym
+    pub fn canonical<'a>(&self, value: &'a str) -> &'a str {
+        self.synonyms.get(&value.trim().to_lowercase()).copied().unwrap_or(value)
+    }
+}
+
+// Synonym table with synonym and last_reply columns, an empty vocabulary when the file doesn't exist
+pub fn load(path: impl AsRef<Path>) -> Result<Vocabulary, Box<dyn Error>> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(Vocabulary::default());
+    }
+
+    let mut synonyms = HashMap::new();
+    for record in csv::Reader::from_path(path)?.deserialize::<SynonymRecord>() {
+        let record = record?;
+        let canonical = CANONICAL
+            .into_iter()
+            .find(|canonical| canonical.eq_ignore_ascii_case(record.last_reply.trim()))
+            .ok_or_else(|| format!("Invalid last_reply {} for synonym {} in {}, expected You, Them, Met or None", record.last_reply, record.synonym, path.display()))?;
+        synonyms.insert(record.synonym.trim().to_lowercase(), canonical);
+    }
+
+    Ok(Vocabulary { synonyms })
+}
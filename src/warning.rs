@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+
+use colored::Colorize;
+
+thread_local! {
+    // Warnings of a caller on this thread collecting them rather than having them printed
+    static COLLECTED: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+// Puts back what was collected before, also when the collecting work panics
+struct Restore(Option<Vec<String>>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        COLLECTED.set(self.0.take());
+    }
+}
+
+// Prints the warning, or keeps it for the caller collecting them on this thread
+pub fn warn(warning: String) {
+    COLLECTED.with_borrow_mut(|collected| match collected {
+        Some(collected) => collected.push(warning),
+        None => println!("{}", warning.yellow().bold())
+    });
+}
+
+// Runs `work` with the warnings it raises on this thread collected, for callers like the C library whose host owns
+// stdout
+pub fn collected<T>(work: impl FnOnce() -> T) -> (T, Vec<String>) {
+    let restore = Restore(COLLECTED.replace(Some(Vec::new())));
+    let result = work();
+    let warnings = COLLECTED.take().unwrap_or_default();
+    drop(restore);
+    (result, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_nested_warnings_separately() {
+        let (((), inner), outer) = collected(|| {
+            warn(String::from("outer"));
+            let inner = collected(|| warn(String::from("inner")));
+            warn(String::from("outer again"));
+            inner
+        });
+        assert_eq!(inner, vec![String::from("inner")]);
+        assert_eq!(outer, vec![String::from("outer"), String::from("outer again")]);
+        assert!(COLLECTED.with_borrow(Option::is_none));
+    }
+
+    #[test]
+    fn stops_collecting_when_the_work_panics() {
+        let _ = std::panic::catch_unwind(|| collected(|| panic!("failed")));
+        assert!(COLLECTED.with_borrow(Option::is_none));
+    }
+}
@@ -0,0 +1,66 @@
+use std::{error::Error, io::Write, path::Path, process};
+
+// Chat services with their own message format, anything else gets the fields of both
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Service {
+    Slack,
+    Discord,
+    Other
+}
+
+impl Service {
+    fn of(url: &str) -> Self {
+        if url.contains("hooks.slack.com") {
+            Service::Slack
+        } else if url.contains("discord.com/api/webhooks") || url.contains("discordapp.com/api/webhooks") {
+            Service::Discord
+        } else {
+            Service::Other
+        }
+    }
+}
+
+// curl's config syntax, quoted with backslash escapes
+pub fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n"))
+}
+
+// Posts the message to a Slack, Discord or other webhook through curl, with the image attached on Discord since its
+// webhooks take uploads while Slack's only take links. The URL and message go to curl on stdin since the URL is the
+// webhook's secret.
+pub fn post(url: &str, message: &str, image: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let service = Service::of(url);
+    let payload = match service {
+        Service::Slack => serde_json::json!({ "text": message }),
+        Service::Discord => serde_json::json!({ "content": message }),
+        Service::Other => serde_json::json!({ "text": message, "content": message })
+    };
+
+    let mut config = format!("url = {}\n", quote(url));
+    match image {
+        Some(image) if service == Service::Discord => {
+            config.push_str(&format!("form-string = {}\n", quote(&format!("payload_json={}", payload))));
+            config.push_str(&format!("form = {}\n", quote(&format!("files[0]=@\"{}\"", image.display()))));
+        },
+        Some(_) => return Err("Only Discord webhooks can take an image, Slack and other webhooks only take links".into()),
+        None => {
+            config.push_str("header = \"Content-Type: application/json\"\n");
+            config.push_str(&format!("data-binary = {}\n", quote(&payload.to_string())));
+        }
+    }
+
+    let mut child = process::Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--config", "-"])
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Could not run curl to post to the webhook: {}", err))?;
+    let written = child.stdin.take().map_or(Ok(()), |mut stdin| stdin.write_all(config.as_bytes()));
+    let output = child.wait_with_output()?;
+    written?;
+    if !output.status.success() {
+        return Err(format!("Could not post to the webhook: {}", String::from_utf8_lossy(&output.stderr).trim()).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use std::{error::Error, path::Path};
+
+use crate::census;
+
+// ZIP codes of the Chicago area with the county each mostly lies in and roughly where its center is. A file with the
+// same columns passed to --zip-crosswalk covers anywhere else.
+const BUNDLED_CROSSWALK: &str = include_str!("../data/zip_counties.csv");
+
+const EARTH_RADIUS_MILES: f64 = 3958.8;
+const MILES_PER_KILOMETER: f64 = 0.621371;
+
+// A ZIP code with the FIPS code of its county and its center
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct ZipCode {
+    pub zip: String,
+    pub county: String,
+    pub latitude: f64,
+    pub longitude: f64
+}
+
+// A distance written like 25mi or 40km, miles when there is no unit
+#[derive(Debug, Clone, Copy)]
+pub struct Radius {
+    pub miles: f64
+}
+
+impl std::str::FromStr for Radius {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_lowercase();
+        let (number, per_mile) = if let Some(number) = value.strip_suffix("km") {
+            (number, MILES_PER_KILOMETER)
+        } else {
+            (value.strip_suffix("mi").unwrap_or(&value), 1.0)
+        };
+        match number.trim().parse::<f64>() {
+            Ok(number) if number >= 0.0 => Ok(Radius { miles: number * per_mile }),
+            _ => Err(format!("Expected a distance like 25mi or 40km, found {}", value))
+        }
+    }
+}
+
+impl std::fmt::Display for Radius {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} mi", self.miles)?;
+        Ok(())
+    }
+}
+
+// The crosswalk at the path, or the bundled one
+pub fn read_crosswalk(crosswalk: Option<&Path>) -> Result<Vec<ZipCode>, Box<dyn Error>> {
+    let records = match crosswalk {
+        Some(path) => csv::Reader::from_path(path)?.deserialize().collect::<Result<Vec<_>, _>>(),
+        None => csv::Reader::from_reader(BUNDLED_CROSSWALK.as_bytes()).deserialize().collect::<Result<Vec<_>, _>>()
+    };
+    Ok(records?)
+}
+
+// Great circle distance between two (latitude, longitude) points
+fn miles_between(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (latitude_a, latitude_b) = (a.0.to_radians(), b.0.to_radians());
+    let half_chord = ((latitude_b - latitude_a) / 2.0).sin().powi(2)
+        + latitude_a.cos() * latitude_b.cos() * ((b.1 - a.1).to_radians() / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_MILES * half_chord.sqrt().asin()
+}
+
+// Counties with a ZIP code centered within the radius of the center of the ZIP code, its own county first and the rest
+// from nearest to furthest
+pub fn counties(zip: &str, radius: Radius, crosswalk: Option<&Path>) -> Result<Vec<census::County>, Box<dyn Error>> {
+    let records = read_crosswalk(crosswalk)?;
+    let source = crosswalk.map_or("the bundled Chicago area crosswalk".to_string(), |path| path.display().to_string());
+
+    let center = records.iter().find(|record| record.zip == zip.trim()).ok_or_else(|| {
+        format!("ZIP code {} isn't in {}, pass a crosswalk covering it with --zip-crosswalk", zip, source)
+    })?;
+    let center_point = (center.latitude, center.longitude);
+
+    let mut nearby = records
+        .iter()
+        .map(|record| (miles_between(center_point, (record.latitude, record.longitude)), record))
+        .filter(|(miles, _)| *miles <= radius.miles)
+        .collect::<Vec<_>>();
+    nearby.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut counties: Vec<census::County> = Vec::new();
+    for (_, record) in nearby {
+        let county = record.county.parse::<census::County>().map_err(|err| format!("ZIP code {} in {}: {}", record.zip, source, err))?;
+        if !counties.contains(&county) {
+            counties.push(county);
+        }
+    }
+    Ok(counties)
+}
@@ -0,0 +1,144 @@
+use crate::{attribute, format, HingeProfile};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
+pub enum Sign {
+    Aries,
+    Taurus,
+    Gemini,
+    Cancer,
+    Leo,
+    Virgo,
+    Libra,
+    Scorpio,
+    Sagittarius,
+    Capricorn,
+    Aquarius,
+    Pisces
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Element {
+    Fire,
+    Earth,
+    Air,
+    Water
+}
+
+const SIGNS: [Sign; 12] = [
+    Sign::Aries, Sign::Taurus, Sign::Gemini, Sign::Cancer, Sign::Leo, Sign::Virgo,
+    Sign::Libra, Sign::Scorpio, Sign::Sagittarius, Sign::Capricorn, Sign::Aquarius, Sign::Pisces
+];
+
+// (month, day) each sign starts on, in SIGNS order
+const STARTS: [(u32, u32); 12] = [(3, 21), (4, 20), (5, 21), (6, 21), (7, 23), (8, 23), (9, 23), (10, 23), (11, 22), (12, 22), (1, 20), (2, 19)];
+
+impl Sign {
+    pub fn from_date(month: u32, day: u32) -> Option<Self> {
+        const DAYS_IN_MONTH: [u32; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+        if !(1..=12).contains(&month) || day == 0 || day > DAYS_IN_MONTH[month as usize - 1] {
+            return None;
+        }
+
+        // the sign whose start is the latest one on or before the date, wrapping Capricorn over the new year
+        Some(SIGNS
+            .iter()
+            .zip(STARTS)
+            .filter(|(_, start)| *start <= (month, day))
+            .max_by_key(|(_, start)| *start)
+            .map_or(Sign::Capricorn, |(sign, _)| *sign))
+    }
+
+    // Birthdays as YYYY-MM-DD, MM/DD/YYYY or MM/DD
+    pub fn from_birthday(birthday: &str) -> Option<Self> {
+        let parts = birthday.trim().split(['-', '/']).map(|part| part.parse::<u32>().ok()).collect::<Option<Vec<_>>>()?;
+        match parts.as_slice() {
+            [year, month, day] if *year > 31 => Sign::from_date(*month, *day),
+            [month, day, _] | [month, day] => Sign::from_date(*month, *day),
+            _ => None
+        }
+    }
+
+    pub fn element(self) -> Element {
+        match self {
+            Sign::Aries | Sign::Leo | Sign::Sagittarius => Element::Fire,
+            Sign::Taurus | Sign::Virgo | Sign::Capricorn => Element::Earth,
+            Sign::Gemini | Sign::Libra | Sign::Aquarius => Element::Air,
+            Sign::Cancer | Sign::Scorpio | Sign::Pisces => Element::Water
+        }
+    }
+
+    // Traditional pairing, signs of the same element or a complementary one (fire with air, earth with water)
+    pub fn compatible(self, other: Sign) -> bool {
+        matches!(
+            (self.element(), other.element()),
+            (Element::Fire | Element::Air, Element::Fire | Element::Air) | (Element::Earth | Element::Water, Element::Earth | Element::Water)
+        )
+    }
+}
+
+impl std::str::FromStr for Sign {
+    type Err = &'static str;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        SIGNS
+            .into_iter()
+            .find(|sign| format!("{:?}", sign).eq_ignore_ascii_case(value.trim()))
+            .ok_or("Invalid value for Zodiac")
+    }
+}
+
+impl std::fmt::Display for Sign {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)?;
+        Ok(())
+    }
+}
+
+// Purely for entertainment, the date rate of a sign against the overall date rate
+fn verdict(date_rate: f64, overall_date_rate: f64, matches: u32) -> &'static str {
+    if matches < 3 {
+        "The stars are silent"
+    } else if date_rate >= 2.0 * overall_date_rate && date_rate > 0.0 {
+        "Written in the stars"
+    } else if date_rate > overall_date_rate {
+        "Favorable alignment"
+    } else if date_rate > 0.0 {
+        "Mercury in retrograde"
+    } else {
+        "Cosmically doomed"
+    }
+}
+
+pub fn print_compatibility(profiles: &[HingeProfile], my_sign: Option<Sign>) {
+    let rates = attribute::outcome_rates(profiles.iter(), |profile| profile.zodiac.map(|sign| sign.to_string()));
+    let total_matches = rates.iter().map(|rates| rates.matches).sum::<u32>();
+    let overall_date_rate = rates.iter().map(|rates| rates.dates).sum::<u32>() as f64 / total_matches.max(1) as f64;
+
+    println!("\n\t         Zodiac Compatibility (For Entertainment Only, {} Profiles with a Sign)", total_matches);
+    println!("\t{:^25}   Element   Matches   Convo Rate    Date Rate     Verdict", "Sign");
+    for rates in &rates {
+        let sign = rates.label.parse::<Sign>().expect("Bad zodiac label");
+        println!(
+            "\t{:<25}   {:<7}   {:<7}   {}     {}     {}",
+            rates.label,
+            format!("{:?}", sign.element()),
+            rates.matches,
+            format::percent(rates.conversation_rate()),
+            format::percent(rates.date_rate()),
+            verdict(rates.date_rate(), overall_date_rate, rates.matches)
+        );
+        format::explain(&[rates.conversation(), rates.date()]);
+    }
+
+    let element_rates = attribute::outcome_rates(profiles.iter(), |profile| profile.zodiac.map(|sign| format!("{:?}", sign.element())));
+    attribute::print_outcome_rates("Element", &element_rates);
+
+    if let Some(my_sign) = my_sign {
+        let compatibility_rates = attribute::outcome_rates(profiles.iter(), |profile| profile.zodiac.map(|sign| if my_sign.compatible(sign) {
+            "Compatible"
+        } else {
+            "Incompatible"
+        }));
+        attribute::print_outcome_rates(&format!("{} Compatibility", my_sign), &compatibility_rates);
+    }
+}